@@ -0,0 +1,44 @@
+//! 应收账龄模块 - 未付分期按逾期天数分段汇总，用于催收
+
+use serde::Serialize;
+
+/// 各账龄段的应收金额
+#[derive(Clone, Default, Serialize)]
+pub struct AgingBuckets {
+    /// 未到期（含当天到期）
+    pub current: i64,
+    pub days_1_30: i64,
+    pub days_31_60: i64,
+    pub days_61_90: i64,
+    pub days_over_90: i64,
+    pub total: i64,
+    /// 已逾期金额（除未到期外的各段之和）
+    pub overdue: i64,
+}
+
+/// 逾期天数对应的账龄段
+pub fn aging_bucket(days_past_due: i64) -> &'static str {
+    match days_past_due {
+        i64::MIN..=0 => "current",
+        1..=30 => "1-30",
+        31..=60 => "31-60",
+        61..=90 => "61-90",
+        _ => "90+",
+    }
+}
+
+impl AgingBuckets {
+    pub fn add(&mut self, days_past_due: i64, amount: i64) {
+        match days_past_due {
+            i64::MIN..=0 => self.current += amount,
+            1..=30 => self.days_1_30 += amount,
+            31..=60 => self.days_31_60 += amount,
+            61..=90 => self.days_61_90 += amount,
+            _ => self.days_over_90 += amount,
+        }
+        self.total += amount;
+        if days_past_due > 0 {
+            self.overdue += amount;
+        }
+    }
+}
@@ -0,0 +1,20 @@
+//! 签到模块 - 学员到场记录
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 单次签到记录
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CheckIn {
+    pub id: u64,
+    pub checked_in_at: DateTime<Utc>,
+    pub note: Option<String>,
+    /// 是否扣除了一次体验课次数
+    #[serde(default)]
+    pub trial_credit: bool,
+}
+
+/// 最近一次签到
+pub fn last_check_in(records: &[CheckIn]) -> Option<&CheckIn> {
+    records.iter().max_by_key(|r| r.checked_in_at)
+}
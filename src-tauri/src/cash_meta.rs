@@ -0,0 +1,90 @@
+//! 现金记录元数据模块 - 业务日期、录入/修改时间、操作员及修改审计
+//!
+//! qmx_backend_lib 的现金记录没有对外的日期字段，这里按现金记录UID补充保存。
+//! 本功能之前录入的记录没有元数据，响应中对应字段为空。
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 现金记录的元数据
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CashMeta {
+    /// 业务日期（俱乐部本地日期），可补录纸质收据的实际日期
+    pub business_date: NaiveDate,
+    /// 录入时间；本功能之前录入、后来补填业务日期的记录录入时间未知
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+    pub operator: Option<String>,
+    /// 收支类别：tuition / membership / trial / equipment / range / competition / other
+    #[serde(default)]
+    pub category: Option<String>,
+    /// 付款方式：cash / wechat / alipay / bank_card / wallet / other
+    #[serde(default)]
+    pub payment_method: Option<String>,
+}
+
+/// 单个字段的修改前后值
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+/// 现金记录修改审计，记录删除后仍然保留
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CashAuditEntry {
+    pub id: u64,
+    pub cash_uid: u64,
+    pub changes: Vec<FieldChange>,
+    pub operator: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CashMeta {
+    pub fn new(business_date: NaiveDate, operator: Option<String>, now: DateTime<Utc>) -> Self {
+        Self {
+            business_date,
+            created_at: Some(now),
+            updated_at: now,
+            operator,
+            category: None,
+            payment_method: None,
+        }
+    }
+
+    /// 为没有元数据的旧记录补充元数据（录入时间和操作员未知）
+    pub fn for_legacy(business_date: NaiveDate, now: DateTime<Utc>) -> Self {
+        Self {
+            created_at: None,
+            ..Self::new(business_date, None, now)
+        }
+    }
+}
+
+/// 收支类别的中文名称
+pub fn cash_category_label(category: &str) -> &'static str {
+    match category {
+        "tuition" => "学费",
+        "membership" => "会员费",
+        "trial" => "体验课",
+        "equipment" => "器材",
+        "range" => "场地",
+        "competition" => "比赛",
+        "late_fee" => "滞纳金",
+        _ => "其他",
+    }
+}
+
+/// 付款方式的中文名称
+pub fn payment_method_label(method: &str) -> &'static str {
+    match method {
+        "cash" => "现金",
+        "wechat" => "微信",
+        "alipay" => "支付宝",
+        "bank_card" => "银行卡",
+        "wallet" => "储值钱包",
+        _ => "其他",
+    }
+}
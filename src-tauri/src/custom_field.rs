@@ -0,0 +1,169 @@
+//! 自定义字段模块 - 由管理员定义的学员附加信息（拉力、主视眼、证件号、保险到期等）
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// 文本字段默认的最大长度
+pub const DEFAULT_TEXT_MAX_LENGTH: usize = 200;
+
+/// 字段类型
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CustomFieldType {
+    Text,
+    Number,
+    /// 日期，保存为 YYYY-MM-DD
+    Date,
+    /// 从预设选项中选择
+    Enum,
+}
+
+/// 自定义字段定义，学员的字段值按字段ID保存
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CustomFieldDef {
+    pub id: u64,
+    /// 字段标识，用于接口传参，创建后不可修改
+    pub key: String,
+    pub label: String,
+    pub field_type: CustomFieldType,
+    pub required: bool,
+    /// 枚举类型的可选值
+    pub options: Vec<String>,
+    /// 数值类型的取值范围
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// 文本类型的最大长度
+    pub max_length: Option<usize>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 学员搜索时的自定义字段筛选条件
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CustomFieldFilter {
+    pub key: String,
+    /// 值完全相等
+    pub equals: Option<String>,
+    /// 文本包含（不区分大小写）
+    pub contains: Option<String>,
+    /// 取值下限/上限（数值按大小，日期按先后比较）
+    pub min: Option<String>,
+    pub max: Option<String>,
+    /// true 只匹配已填写的学员，false 只匹配未填写的学员
+    pub has_value: Option<bool>,
+}
+
+impl CustomFieldDef {
+    /// 按字段规则校验并规范化输入值
+    pub fn normalize_value(&self, raw: &str) -> Result<String, String> {
+        let value = raw.trim();
+        if value.is_empty() {
+            return Err(format!("{}不能为空", self.label));
+        }
+        match self.field_type {
+            CustomFieldType::Text => {
+                let max_length = self.max_length.unwrap_or(DEFAULT_TEXT_MAX_LENGTH);
+                if value.chars().count() > max_length {
+                    return Err(format!("{}长度不能超过{}个字符", self.label, max_length));
+                }
+                if value.chars().any(|c| c.is_control()) {
+                    return Err(format!("{}包含非法字符", self.label));
+                }
+                Ok(value.to_string())
+            }
+            CustomFieldType::Number => {
+                let number = parse_number(value)
+                    .ok_or_else(|| format!("{}必须是数字: {}", self.label, value))?;
+                if self.min.is_some_and(|min| number < min) {
+                    return Err(format!("{}不能小于{}", self.label, self.min.unwrap_or(0.0)));
+                }
+                if self.max.is_some_and(|max| number > max) {
+                    return Err(format!("{}不能大于{}", self.label, self.max.unwrap_or(0.0)));
+                }
+                Ok(number.to_string())
+            }
+            CustomFieldType::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .map_err(|_| format!("{}日期格式应为YYYY-MM-DD: {}", self.label, value)),
+            CustomFieldType::Enum => self
+                .options
+                .iter()
+                .find(|o| o.as_str() == value)
+                .cloned()
+                .ok_or_else(|| {
+                    format!(
+                        "{}的值必须是以下选项之一: {}",
+                        self.label,
+                        self.options.join("、")
+                    )
+                }),
+        }
+    }
+
+    /// 比较两个值的大小：数值按大小，其余按字符串（日期格式可直接比较）
+    fn compare(&self, a: &str, b: &str) -> Option<Ordering> {
+        match self.field_type {
+            CustomFieldType::Number => parse_number(a)?.partial_cmp(&parse_number(b)?),
+            _ => Some(a.cmp(b)),
+        }
+    }
+
+    /// 学员的字段值是否满足筛选条件
+    pub fn matches(&self, value: Option<&str>, filter: &CustomFieldFilter) -> bool {
+        if let Some(has_value) = filter.has_value {
+            if value.is_some() != has_value {
+                return false;
+            }
+        }
+        let conditions = [&filter.equals, &filter.contains, &filter.min, &filter.max];
+        if conditions.iter().all(|c| c.is_none()) {
+            return true;
+        }
+        let Some(value) = value else {
+            return false;
+        };
+        if let Some(equals) = &filter.equals {
+            if self.compare(value, equals.trim()) != Some(Ordering::Equal) {
+                return false;
+            }
+        }
+        if let Some(contains) = &filter.contains {
+            if !value
+                .to_lowercase()
+                .contains(&contains.trim().to_lowercase())
+            {
+                return false;
+            }
+        }
+        if let Some(min) = &filter.min {
+            if !matches!(
+                self.compare(value, min.trim()),
+                Some(Ordering::Greater | Ordering::Equal)
+            ) {
+                return false;
+            }
+        }
+        if let Some(max) = &filter.max {
+            if !matches!(
+                self.compare(value, max.trim()),
+                Some(Ordering::Less | Ordering::Equal)
+            ) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn parse_number(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+pub fn parse_custom_field_type(field_type: &str) -> Result<CustomFieldType, String> {
+    match field_type {
+        "Text" => Ok(CustomFieldType::Text),
+        "Number" => Ok(CustomFieldType::Number),
+        "Date" => Ok(CustomFieldType::Date),
+        "Enum" => Ok(CustomFieldType::Enum),
+        _ => Err(format!("无效的字段类型: {}", field_type)),
+    }
+}
@@ -0,0 +1,110 @@
+//! 折扣模块 - 折扣规则、优惠券及交易的标价/折扣记录
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 折扣计算方式
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DiscountKind {
+    /// 按百分比减免，value 为 1-100
+    Percentage,
+    /// 固定金额减免，value 为减免金额
+    FixedAmount,
+}
+
+/// 折扣类别，用于报表统计
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DiscountCategory {
+    General,
+    Referral,
+    Sibling,
+    EarlyRenewal,
+}
+
+/// 折扣规则
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DiscountRule {
+    pub id: u64,
+    pub name: String,
+    pub kind: DiscountKind,
+    pub value: i64,
+    pub category: DiscountCategory,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 优惠券，使用时按关联的折扣规则计算减免金额
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Coupon {
+    pub id: u64,
+    pub code: String,
+    pub rule_id: u64,
+    pub max_uses: Option<u32>,
+    pub used_count: u32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 交易的标价与折扣信息，按现金记录UID保存
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CashPricing {
+    pub list_price: i64,
+    pub discount_amount: i64,
+    pub rule_id: Option<u64>,
+    pub coupon_code: Option<String>,
+    /// 折扣类别：使用规则时为规则的类别，家庭折扣为 Sibling，手工折扣为空
+    #[serde(default)]
+    pub category: Option<DiscountCategory>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DiscountRule {
+    /// 计算标价对应的减免金额，不会超过标价本身
+    pub fn discount_for(&self, list_price: i64) -> i64 {
+        let discount = match self.kind {
+            DiscountKind::Percentage => list_price * self.value / 100,
+            DiscountKind::FixedAmount => self.value,
+        };
+        discount.clamp(0, list_price.max(0))
+    }
+}
+
+impl Coupon {
+    /// 检查优惠券当前是否可用
+    pub fn check_usable(&self, now: &DateTime<Utc>) -> Result<(), String> {
+        if !self.active {
+            return Err(format!("优惠券已停用: {}", self.code));
+        }
+        if self.expires_at.is_some_and(|expires| expires < *now) {
+            return Err(format!("优惠券已过期: {}", self.code));
+        }
+        if self.max_uses.is_some_and(|max| self.used_count >= max) {
+            return Err(format!("优惠券已达到使用次数上限: {}", self.code));
+        }
+        Ok(())
+    }
+}
+
+/// 优惠券码统一去除空白并转为大写
+pub fn normalize_coupon_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+pub fn parse_discount_kind(kind: &str) -> Result<DiscountKind, String> {
+    match kind {
+        "Percentage" => Ok(DiscountKind::Percentage),
+        "FixedAmount" => Ok(DiscountKind::FixedAmount),
+        _ => Err(format!("无效的折扣方式: {}", kind)),
+    }
+}
+
+pub fn parse_discount_category(category: &str) -> Result<DiscountCategory, String> {
+    match category {
+        "General" => Ok(DiscountCategory::General),
+        "Referral" => Ok(DiscountCategory::Referral),
+        "Sibling" => Ok(DiscountCategory::Sibling),
+        "EarlyRenewal" => Ok(DiscountCategory::EarlyRenewal),
+        _ => Err(format!("无效的折扣类别: {}", category)),
+    }
+}
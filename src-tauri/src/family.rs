@@ -0,0 +1,52 @@
+//! 家庭模块 - 家庭账户、合并付款与家庭折扣
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 家庭账户，把兄弟姐妹等学员归为一组统一结算
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Family {
+    pub id: u64,
+    pub name: String,
+    pub member_uids: Vec<u64>,
+    /// 家庭折扣百分比（如 10 表示九折），0 表示无折扣
+    pub discount_percent: u32,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 一次家庭付款，拆分为每个成员各自的现金记录
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FamilyPayment {
+    pub id: u64,
+    pub family_id: u64,
+    pub list_amount: i64,
+    pub discount_amount: i64,
+    pub cash_uids: Vec<u64>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 按百分比计算折扣金额（向下取整到分）
+pub fn discount_amount(amount: i64, percent: u32) -> i64 {
+    amount * i64::from(percent) / 100
+}
+
+/// 把金额平均拆分为 n 份，除不尽的余数依次分给前几份
+pub fn split_evenly(total: i64, parts: usize) -> Vec<i64> {
+    if parts == 0 {
+        return Vec::new();
+    }
+    let parts_i64 = parts as i64;
+    let base = total / parts_i64;
+    let remainder = total % parts_i64;
+    (0..parts_i64)
+        .map(|i| {
+            base + if i < remainder.abs() {
+                remainder.signum()
+            } else {
+                0
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,617 @@
+//! 财务报表模块 - 月度/年度财务报表的分类汇总、与上期对比及PDF/XLSX导出
+//!
+//! 收入只统计实际收到的款项：分期中待付款、逾期和已取消的期数不计入收入，
+//! 未付的分期单独列为应收分期。关联学员的支出记为退款，其余支出按类别汇总。
+
+use chrono::{Datelike, Months, NaiveDate};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::cash_meta::{cash_category_label, payment_method_label};
+use crate::pdf::{Align, PdfDocument, MARGIN, PAGE_HEIGHT, PAGE_WIDTH};
+use crate::receipt::{format_yuan, ClubProfile};
+
+/// 报表期间：自然月或自然年
+#[derive(Clone, Copy)]
+pub enum ReportPeriod {
+    Month(NaiveDate),
+    Year(i32),
+}
+
+impl ReportPeriod {
+    /// 解析 YYYY-MM（月报）或 YYYY（年报）
+    pub fn parse(period: &str) -> Result<Self, String> {
+        let period = period.trim();
+        if let Ok(year) = period.parse::<i32>() {
+            if period.len() == 4 && NaiveDate::from_ymd_opt(year, 1, 1).is_some() {
+                return Ok(Self::Year(year));
+            }
+        }
+        NaiveDate::parse_from_str(&format!("{}-01", period), "%Y-%m-%d")
+            .map(Self::Month)
+            .map_err(|_| format!("报表期间格式应为YYYY-MM或YYYY: {}", period))
+    }
+
+    /// 期间的第一天和最后一天（本地日期）
+    pub fn range(&self) -> Result<(NaiveDate, NaiveDate), String> {
+        let (start, next) = match self {
+            Self::Month(start) => (*start, start.checked_add_months(Months::new(1))),
+            Self::Year(year) => (
+                NaiveDate::from_ymd_opt(*year, 1, 1).ok_or("日期超出范围")?,
+                NaiveDate::from_ymd_opt(*year + 1, 1, 1),
+            ),
+        };
+        let end = next.and_then(|d| d.pred_opt()).ok_or("日期超出范围")?;
+        Ok((start, end))
+    }
+
+    /// 上一期（上月或上一年）
+    pub fn previous(&self) -> Result<Self, String> {
+        match self {
+            Self::Month(start) => start
+                .checked_sub_months(Months::new(1))
+                .map(Self::Month)
+                .ok_or_else(|| "日期超出范围".to_string()),
+            Self::Year(year) => Ok(Self::Year(year - 1)),
+        }
+    }
+
+    /// 期间标识，如 2026-10 或 2026
+    pub fn key(&self) -> String {
+        match self {
+            Self::Month(start) => start.format("%Y-%m").to_string(),
+            Self::Year(year) => year.to_string(),
+        }
+    }
+
+    /// 期间名称，如 2026年10月 或 2026年
+    pub fn label(&self) -> String {
+        match self {
+            Self::Month(start) => format!("{}年{}月", start.year(), start.month()),
+            Self::Year(year) => format!("{}年", year),
+        }
+    }
+
+    fn kind_label(&self) -> &'static str {
+        match self {
+            Self::Month(_) => "月度财务报表",
+            Self::Year(_) => "年度财务报表",
+        }
+    }
+}
+
+/// 参与汇总的一条已结算现金记录
+pub struct ReportEntry {
+    pub amount: i64,
+    /// 是否关联了学员（关联学员的支出记为退款）
+    pub has_student: bool,
+    pub category: Option<String>,
+    pub payment_method: Option<String>,
+    /// 学员的班级（Class 的 Debug 字符串），学员不存在时为 None
+    pub class: Option<String>,
+    /// 学员的科目（Subject 的 Debug 字符串）
+    pub subject: Option<String>,
+}
+
+/// 应收分期汇总
+#[derive(Clone, Default, Serialize)]
+pub struct ReceivableSummary {
+    /// 本期到期但尚未付款的分期
+    pub due_in_period_amount: i64,
+    pub due_in_period_count: usize,
+    /// 截至报表生成时已逾期的分期
+    pub overdue_amount: i64,
+    pub overdue_count: usize,
+    /// 截至报表生成时全部未付的分期
+    pub outstanding_amount: i64,
+    pub outstanding_count: usize,
+}
+
+/// 期间合计
+#[derive(Clone, Default, Serialize)]
+pub struct ReportTotals {
+    pub income: i64,
+    pub income_count: usize,
+    /// 不含退款的支出
+    pub expense: i64,
+    pub expense_count: usize,
+    pub refunds: i64,
+    pub refund_count: usize,
+    /// 收入 - 支出 - 退款
+    pub net: i64,
+}
+
+/// 分类汇总的一行
+#[derive(Clone, Serialize)]
+pub struct BreakdownLine {
+    pub key: String,
+    pub label: String,
+    pub amount: i64,
+    pub count: usize,
+    /// 占该分类合计的百分比
+    pub share_percent: f64,
+}
+
+/// 与上期对比的一项
+#[derive(Clone, Serialize)]
+pub struct ComparisonLine {
+    pub item: String,
+    pub current: i64,
+    pub previous: i64,
+    pub change: i64,
+    /// 上期为0时为 None
+    pub change_percent: Option<f64>,
+}
+
+/// 完整的财务报表
+#[derive(Clone, Serialize)]
+pub struct FinancialReport {
+    /// 月度财务报表 / 年度财务报表
+    pub title: String,
+    pub period: String,
+    pub period_label: String,
+    pub period_start_date: String,
+    pub period_end_date: String,
+    pub totals: ReportTotals,
+    pub income_by_category: Vec<BreakdownLine>,
+    pub income_by_payment_method: Vec<BreakdownLine>,
+    pub income_by_class: Vec<BreakdownLine>,
+    pub income_by_subject: Vec<BreakdownLine>,
+    pub expense_by_category: Vec<BreakdownLine>,
+    pub refunds_by_payment_method: Vec<BreakdownLine>,
+    pub receivables: ReceivableSummary,
+    pub previous_period: String,
+    pub previous_period_label: String,
+    pub previous_totals: ReportTotals,
+    pub comparison: Vec<ComparisonLine>,
+}
+
+/// 班级的中文名称
+pub fn class_label(class: &str) -> &'static str {
+    match class {
+        "TenTry" => "体验课",
+        "Month" => "月卡",
+        "Year" => "年卡",
+        _ => "其他",
+    }
+}
+
+/// 科目的中文名称
+pub fn subject_label(subject: &str) -> &'static str {
+    match subject {
+        "Shooting" => "射击",
+        "Archery" => "射箭",
+        _ => "其他",
+    }
+}
+
+fn totals_of(entries: &[ReportEntry]) -> ReportTotals {
+    let mut totals = ReportTotals::default();
+    for entry in entries {
+        // 金额为0的记录（如钱包扣款对应的现金记录、提前结清后归零的分期）不计入任何笔数
+        if entry.amount == 0 {
+            continue;
+        }
+        if entry.amount > 0 {
+            totals.income += entry.amount;
+            totals.income_count += 1;
+        } else if entry.has_student {
+            totals.refunds += entry.amount.abs();
+            totals.refund_count += 1;
+        } else {
+            totals.expense += entry.amount.abs();
+            totals.expense_count += 1;
+        }
+    }
+    totals.net = totals.income - totals.expense - totals.refunds;
+    totals
+}
+
+/// 按指定维度汇总，金额按绝对值计算，金额大的排在前面
+fn breakdown<'a>(
+    entries: impl Iterator<Item = &'a ReportEntry>,
+    key_of: impl Fn(&ReportEntry) -> Option<&str>,
+    label_of: impl Fn(&str) -> &'static str,
+    missing_label: &str,
+) -> Vec<BreakdownLine> {
+    let mut groups: BTreeMap<String, (i64, usize)> = BTreeMap::new();
+    for entry in entries {
+        let key = key_of(entry).unwrap_or("").to_string();
+        let group = groups.entry(key).or_default();
+        group.0 += entry.amount.abs();
+        group.1 += 1;
+    }
+    let total: i64 = groups.values().map(|(amount, _)| amount).sum();
+    let mut lines: Vec<BreakdownLine> = groups
+        .into_iter()
+        .map(|(key, (amount, count))| BreakdownLine {
+            label: if key.is_empty() {
+                missing_label.to_string()
+            } else {
+                label_of(&key).to_string()
+            },
+            key,
+            amount,
+            count,
+            share_percent: if total == 0 {
+                0.0
+            } else {
+                (amount as f64 * 1000.0 / total as f64).round() / 10.0
+            },
+        })
+        .collect();
+    lines.sort_by(|a, b| b.amount.cmp(&a.amount).then_with(|| a.key.cmp(&b.key)));
+    lines
+}
+
+fn comparison_line(item: &str, current: i64, previous: i64) -> ComparisonLine {
+    ComparisonLine {
+        item: item.to_string(),
+        current,
+        previous,
+        change: current - previous,
+        change_percent: (previous != 0)
+            .then(|| ((current - previous) as f64 * 1000.0 / previous.abs() as f64).round() / 10.0),
+    }
+}
+
+/// 汇总本期和上期的已结算记录，生成报表
+pub fn build_report(
+    period: ReportPeriod,
+    entries: &[ReportEntry],
+    previous_entries: &[ReportEntry],
+    receivables: ReceivableSummary,
+) -> Result<FinancialReport, String> {
+    let (start, end) = period.range()?;
+    let previous = period.previous()?;
+    let totals = totals_of(entries);
+    let previous_totals = totals_of(previous_entries);
+
+    let income = || entries.iter().filter(|e| e.amount > 0);
+    let refunds = || entries.iter().filter(|e| e.amount < 0 && e.has_student);
+    let expenses = || entries.iter().filter(|e| e.amount < 0 && !e.has_student);
+
+    Ok(FinancialReport {
+        title: period.kind_label().to_string(),
+        period: period.key(),
+        period_label: period.label(),
+        period_start_date: start.format("%Y-%m-%d").to_string(),
+        period_end_date: end.format("%Y-%m-%d").to_string(),
+        income_by_category: breakdown(
+            income(),
+            |e| e.category.as_deref(),
+            cash_category_label,
+            "未分类",
+        ),
+        income_by_payment_method: breakdown(
+            income(),
+            |e| e.payment_method.as_deref(),
+            payment_method_label,
+            "未记录",
+        ),
+        income_by_class: breakdown(income(), |e| e.class.as_deref(), class_label, "无学员"),
+        income_by_subject: breakdown(income(), |e| e.subject.as_deref(), subject_label, "无学员"),
+        expense_by_category: breakdown(
+            expenses(),
+            |e| e.category.as_deref(),
+            cash_category_label,
+            "未分类",
+        ),
+        refunds_by_payment_method: breakdown(
+            refunds(),
+            |e| e.payment_method.as_deref(),
+            payment_method_label,
+            "未记录",
+        ),
+        receivables,
+        previous_period: previous.key(),
+        previous_period_label: previous.label(),
+        comparison: vec![
+            comparison_line("收入", totals.income, previous_totals.income),
+            comparison_line("支出", totals.expense, previous_totals.expense),
+            comparison_line("退款", totals.refunds, previous_totals.refunds),
+            comparison_line("净收入", totals.net, previous_totals.net),
+        ],
+        totals,
+        previous_totals,
+    })
+}
+
+fn format_percent(percent: Option<f64>) -> String {
+    match percent {
+        Some(p) if p > 0.0 => format!("+{:.1}%", p),
+        Some(p) => format!("{:.1}%", p),
+        None => "-".to_string(),
+    }
+}
+
+/// 导出为PDF，内容较多时自动分页
+pub fn render_pdf(report: &FinancialReport, club: ClubProfile, printed_at: &str) -> Vec<u8> {
+    let title = format!("{}（{}）", report.title, report.period_label);
+    let mut doc = PdfDocument::new(&format!("{} {}", club.name, title), club.logo);
+    let left = MARGIN;
+    let right = PAGE_WIDTH - MARGIN;
+    let count_x = right - 150.0;
+    let share_x = right - 100.0;
+
+    // 页眉
+    let mut y = PAGE_HEIGHT - MARGIN;
+    if let Some(width) = doc.logo().map(|logo| logo.width_for_height(40.0)) {
+        doc.draw_logo(left, y - 40.0, width, 40.0);
+    }
+    y -= 26.0;
+    doc.text(PAGE_WIDTH / 2.0, y, 18.0, &club.name, Align::Center);
+    y -= 28.0;
+    doc.text(PAGE_WIDTH / 2.0, y, 15.0, &title, Align::Center);
+    y -= 20.0;
+    doc.text(
+        left,
+        y,
+        10.0,
+        &format!(
+            "统计区间：{} 至 {}",
+            report.period_start_date, report.period_end_date
+        ),
+        Align::Left,
+    );
+    doc.text(
+        right,
+        y,
+        10.0,
+        &format!("打印时间：{}", printed_at),
+        Align::Right,
+    );
+    y -= 10.0;
+    doc.line(left, y, right, y, 1.0);
+    y -= 24.0;
+
+    let ensure_space = |doc: &mut PdfDocument, y: &mut f32, needed: f32| {
+        if *y - needed < MARGIN {
+            doc.new_page();
+            *y = PAGE_HEIGHT - MARGIN - 14.0;
+        }
+    };
+    let section_title = |doc: &mut PdfDocument, y: &mut f32, title: &str| {
+        ensure_space(doc, y, 60.0);
+        doc.text(left, *y, 13.0, title, Align::Left);
+        *y -= 8.0;
+        doc.line(left, *y, right, *y, 0.5);
+        *y -= 18.0;
+    };
+
+    let totals = &report.totals;
+    section_title(&mut doc, &mut y, "一、收支概况");
+    for (label, amount, count) in [
+        ("收入", totals.income, Some(totals.income_count)),
+        ("支出", totals.expense, Some(totals.expense_count)),
+        ("退款", totals.refunds, Some(totals.refund_count)),
+        ("净收入", totals.net, None),
+    ] {
+        doc.text(left, y, 11.0, label, Align::Left);
+        if let Some(count) = count {
+            doc.text(share_x, y, 11.0, &format!("{}笔", count), Align::Right);
+        }
+        doc.text(right, y, 11.0, &format_yuan(amount), Align::Right);
+        y -= 18.0;
+    }
+    y -= 10.0;
+
+    let sections = [
+        ("二、收入（按类别）", &report.income_by_category),
+        ("三、收入（按付款方式）", &report.income_by_payment_method),
+        ("四、收入（按班级）", &report.income_by_class),
+        ("五、收入（按科目）", &report.income_by_subject),
+        ("六、支出（按类别）", &report.expense_by_category),
+        ("七、退款（按付款方式）", &report.refunds_by_payment_method),
+    ];
+    for (title, lines) in sections {
+        section_title(&mut doc, &mut y, title);
+        if lines.is_empty() {
+            doc.text(left, y, 10.0, "无", Align::Left);
+            y -= 18.0;
+        }
+        for line in lines {
+            ensure_space(&mut doc, &mut y, 18.0);
+            doc.text(left, y, 10.0, &line.label, Align::Left);
+            doc.text(count_x, y, 10.0, &format!("{}笔", line.count), Align::Right);
+            doc.text(
+                share_x,
+                y,
+                10.0,
+                &format!("{:.1}%", line.share_percent),
+                Align::Right,
+            );
+            doc.text(right, y, 10.0, &format_yuan(line.amount), Align::Right);
+            y -= 16.0;
+        }
+        y -= 10.0;
+    }
+
+    let receivables = &report.receivables;
+    section_title(&mut doc, &mut y, "八、应收分期");
+    for (label, amount, count) in [
+        (
+            "本期到期未付",
+            receivables.due_in_period_amount,
+            receivables.due_in_period_count,
+        ),
+        (
+            "已逾期",
+            receivables.overdue_amount,
+            receivables.overdue_count,
+        ),
+        (
+            "全部未付",
+            receivables.outstanding_amount,
+            receivables.outstanding_count,
+        ),
+    ] {
+        doc.text(left, y, 10.0, label, Align::Left);
+        doc.text(share_x, y, 10.0, &format!("{}期", count), Align::Right);
+        doc.text(right, y, 10.0, &format_yuan(amount), Align::Right);
+        y -= 16.0;
+    }
+    y -= 10.0;
+
+    section_title(
+        &mut doc,
+        &mut y,
+        &format!("九、与上期（{}）对比", report.previous_period_label),
+    );
+    let previous_x = right - 200.0;
+    let change_x = right - 90.0;
+    doc.text(left, y, 10.0, "项目", Align::Left);
+    doc.text(previous_x - 110.0, y, 10.0, "本期", Align::Right);
+    doc.text(previous_x, y, 10.0, "上期", Align::Right);
+    doc.text(change_x, y, 10.0, "增减", Align::Right);
+    doc.text(right, y, 10.0, "增减比例", Align::Right);
+    y -= 16.0;
+    for line in &report.comparison {
+        ensure_space(&mut doc, &mut y, 18.0);
+        doc.text(left, y, 10.0, &line.item, Align::Left);
+        doc.text(
+            previous_x - 110.0,
+            y,
+            10.0,
+            &format_yuan(line.current),
+            Align::Right,
+        );
+        doc.text(
+            previous_x,
+            y,
+            10.0,
+            &format_yuan(line.previous),
+            Align::Right,
+        );
+        doc.text(change_x, y, 10.0, &format_yuan(line.change), Align::Right);
+        doc.text(
+            right,
+            y,
+            10.0,
+            &format_percent(line.change_percent),
+            Align::Right,
+        );
+        y -= 16.0;
+    }
+
+    doc.finish()
+}
+
+fn write_breakdown_sheet(
+    sheet: &mut Worksheet,
+    lines: &[BreakdownLine],
+    header: &Format,
+    money: &Format,
+) -> Result<(), XlsxError> {
+    for (col, title) in ["项目", "笔数", "金额", "占比(%)"].iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *title, header)?;
+    }
+    for (i, line) in lines.iter().enumerate() {
+        let row = i as u32 + 1;
+        sheet.write_string(row, 0, &line.label)?;
+        sheet.write_number(row, 1, line.count as f64)?;
+        sheet.write_number_with_format(row, 2, line.amount as f64, money)?;
+        sheet.write_number(row, 3, line.share_percent)?;
+    }
+    sheet.set_column_width(0, 20)?;
+    sheet.set_column_width(2, 16)?;
+    Ok(())
+}
+
+fn build_workbook(report: &FinancialReport) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let header = Format::new().set_bold();
+    let money = Format::new().set_num_format("#,##0.00");
+
+    let summary = workbook.add_worksheet();
+    summary.set_name("概况")?;
+    summary.write_string_with_format(0, 0, &report.period_label, &header)?;
+    summary.write_string(
+        1,
+        0,
+        format!("{} 至 {}", report.period_start_date, report.period_end_date),
+    )?;
+    for (col, title) in ["项目", "笔数", "金额"].iter().enumerate() {
+        summary.write_string_with_format(3, col as u16, *title, &header)?;
+    }
+    let totals = &report.totals;
+    let receivables = &report.receivables;
+    let rows = [
+        ("收入", Some(totals.income_count), totals.income),
+        ("支出", Some(totals.expense_count), totals.expense),
+        ("退款", Some(totals.refund_count), totals.refunds),
+        ("净收入", None, totals.net),
+        (
+            "应收分期-本期到期未付",
+            Some(receivables.due_in_period_count),
+            receivables.due_in_period_amount,
+        ),
+        (
+            "应收分期-已逾期",
+            Some(receivables.overdue_count),
+            receivables.overdue_amount,
+        ),
+        (
+            "应收分期-全部未付",
+            Some(receivables.outstanding_count),
+            receivables.outstanding_amount,
+        ),
+    ];
+    for (i, (label, count, amount)) in rows.iter().enumerate() {
+        let row = i as u32 + 4;
+        summary.write_string(row, 0, *label)?;
+        if let Some(count) = count {
+            summary.write_number(row, 1, *count as f64)?;
+        }
+        summary.write_number_with_format(row, 2, *amount as f64, &money)?;
+    }
+    summary.set_column_width(0, 24)?;
+    summary.set_column_width(2, 16)?;
+
+    let sheets = [
+        ("收入-类别", &report.income_by_category),
+        ("收入-付款方式", &report.income_by_payment_method),
+        ("收入-班级", &report.income_by_class),
+        ("收入-科目", &report.income_by_subject),
+        ("支出-类别", &report.expense_by_category),
+        ("退款-付款方式", &report.refunds_by_payment_method),
+    ];
+    for (name, lines) in sheets {
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(name)?;
+        write_breakdown_sheet(sheet, lines, &header, &money)?;
+    }
+
+    let comparison = workbook.add_worksheet();
+    comparison.set_name("上期对比")?;
+    let previous_header = format!("上期（{}）", report.previous_period_label);
+    let titles = [
+        "项目",
+        "本期",
+        previous_header.as_str(),
+        "增减",
+        "增减比例(%)",
+    ];
+    for (col, title) in titles.iter().enumerate() {
+        comparison.write_string_with_format(0, col as u16, *title, &header)?;
+    }
+    for (i, line) in report.comparison.iter().enumerate() {
+        let row = i as u32 + 1;
+        comparison.write_string(row, 0, &line.item)?;
+        comparison.write_number_with_format(row, 1, line.current as f64, &money)?;
+        comparison.write_number_with_format(row, 2, line.previous as f64, &money)?;
+        comparison.write_number_with_format(row, 3, line.change as f64, &money)?;
+        if let Some(percent) = line.change_percent {
+            comparison.write_number(row, 4, percent)?;
+        }
+    }
+    comparison.set_column_width(2, 18)?;
+
+    workbook.save_to_buffer()
+}
+
+/// 导出为XLSX：概况、各分类汇总和上期对比分别为一个工作表
+pub fn render_xlsx(report: &FinancialReport) -> Result<Vec<u8>, String> {
+    build_workbook(report).map_err(|e| format!("生成XLSX失败: {}", e))
+}
@@ -0,0 +1,48 @@
+//! 监护人模块 - 未成年学员的家长/监护人联系方式
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 监护人，可关联多个学员（如兄弟姐妹）
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Guardian {
+    pub id: u64,
+    pub name: String,
+    pub relationship: String,
+    pub phones: Vec<String>,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 学员与监护人的关联
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GuardianLink {
+    pub guardian_id: u64,
+    pub is_primary: bool,
+}
+
+impl Guardian {
+    /// 任一电话号码包含指定片段
+    pub fn phone_matches(&self, fragment: &str) -> bool {
+        self.phones.iter().any(|p| p.contains(fragment))
+    }
+}
+
+/// 整理电话号码列表：去除首尾空白、空项和重复项，保留原顺序
+pub fn normalize_phones(phones: Vec<String>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for phone in phones {
+        let phone = phone.trim().to_string();
+        if !phone.is_empty() && !result.contains(&phone) {
+            result.push(phone);
+        }
+    }
+    result
+}
+
+/// 设置主要联系人，保证每个学员最多只有一个主要监护人
+pub fn set_primary(links: &mut [GuardianLink], guardian_id: u64) {
+    for link in links.iter_mut() {
+        link.is_primary = link.guardian_id == guardian_id;
+    }
+}
@@ -0,0 +1,90 @@
+//! 滞纳金模块 - 分期计划的滞纳金规则及逾期产生的滞纳金应收
+//!
+//! 分期逾期超过宽限天数后按计划的规则生成一笔滞纳金，每期分期最多生成一笔；
+//! 滞纳金收款时记为类别 late_fee 的现金记录，减免时必须填写原因。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 滞纳金计算方式
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LateFeeKind {
+    /// 按逾期分期金额的百分比计算，value 为 1-100
+    Percentage,
+    /// 固定金额，value 为滞纳金金额
+    FixedAmount,
+}
+
+pub fn parse_late_fee_kind(kind: &str) -> Result<LateFeeKind, String> {
+    match kind {
+        "Percentage" => Ok(LateFeeKind::Percentage),
+        "FixedAmount" => Ok(LateFeeKind::FixedAmount),
+        _ => Err(format!("无效的滞纳金计算方式: {}", kind)),
+    }
+}
+
+/// 分期计划的滞纳金规则，按分期计划ID保存
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LateFeePolicy {
+    pub kind: LateFeeKind,
+    pub value: i64,
+    /// 到期后的宽限天数，逾期超过该天数才产生滞纳金
+    pub grace_days: u32,
+    /// 单笔滞纳金上限
+    pub cap: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LateFeePolicy {
+    /// 逾期分期对应的滞纳金金额
+    pub fn fee_for(&self, installment_amount: i64) -> i64 {
+        let fee = match self.kind {
+            LateFeeKind::Percentage => installment_amount.max(0) * self.value / 100,
+            LateFeeKind::FixedAmount => self.value,
+        };
+        self.cap.map_or(fee, |cap| fee.min(cap)).max(0)
+    }
+}
+
+/// 滞纳金状态
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LateFeeStatus {
+    Pending,
+    Paid,
+    Waived,
+}
+
+pub fn parse_late_fee_status(status: &str) -> Result<LateFeeStatus, String> {
+    match status {
+        "Pending" => Ok(LateFeeStatus::Pending),
+        "Paid" => Ok(LateFeeStatus::Paid),
+        "Waived" => Ok(LateFeeStatus::Waived),
+        _ => Err(format!("无效的滞纳金状态: {}", status)),
+    }
+}
+
+/// 滞纳金减免记录
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LateFeeWaiver {
+    pub reason: String,
+    pub operator: Option<String>,
+    pub waived_at: DateTime<Utc>,
+}
+
+/// 逾期分期产生的一笔滞纳金
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LateFee {
+    pub id: u64,
+    pub plan_id: u64,
+    /// 逾期分期的现金记录UID
+    pub installment_uid: u64,
+    pub student_uid: Option<u64>,
+    pub amount: i64,
+    pub status: LateFeeStatus,
+    pub created_at: DateTime<Utc>,
+    /// 收款对应的现金记录UID
+    pub payment_uid: Option<u64>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub waiver: Option<LateFeeWaiver>,
+}
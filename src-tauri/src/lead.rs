@@ -0,0 +1,91 @@
+//! 潜在客户模块 - 体验/咨询客户在成为正式学员之前的跟进记录
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 客户来源
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LeadSource {
+    WalkIn,
+    Douyin,
+    Referral,
+    Other,
+}
+
+/// 跟进状态
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum LeadStatus {
+    New,
+    Contacted,
+    TrialBooked,
+    Converted,
+    Lost,
+}
+
+/// 一条跟进记录，状态变化也会记录在这里
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LeadFollowUp {
+    pub status: LeadStatus,
+    pub note: Option<String>,
+    pub operator: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 潜在客户，转为学员后保留全部跟进历史
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Lead {
+    pub id: u64,
+    pub name: String,
+    pub phone: String,
+    pub age: Option<u8>,
+    pub source: LeadSource,
+    /// 来源为转介绍时的推荐学员UID
+    pub referrer_uid: Option<u64>,
+    pub status: LeadStatus,
+    pub follow_ups: Vec<LeadFollowUp>,
+    /// 转为学员后的学生UID
+    pub student_uid: Option<u64>,
+    pub created_at: DateTime<Utc>,
+    pub converted_at: Option<DateTime<Utc>>,
+}
+
+impl Lead {
+    /// 追加跟进记录并更新当前状态
+    pub fn follow_up(
+        &mut self,
+        status: LeadStatus,
+        note: Option<String>,
+        operator: Option<String>,
+        now: DateTime<Utc>,
+    ) {
+        self.status = status;
+        self.follow_ups.push(LeadFollowUp {
+            status,
+            note,
+            operator,
+            created_at: now,
+        });
+    }
+}
+
+pub fn parse_lead_source(source: &str) -> Result<LeadSource, String> {
+    match source {
+        "WalkIn" => Ok(LeadSource::WalkIn),
+        "Douyin" => Ok(LeadSource::Douyin),
+        "Referral" => Ok(LeadSource::Referral),
+        "Other" => Ok(LeadSource::Other),
+        _ => Err(format!("无效的客户来源: {}", source)),
+    }
+}
+
+/// 解析手动设置的跟进状态（已转化只能通过转为学员设置）
+pub fn parse_lead_status(status: &str) -> Result<LeadStatus, String> {
+    match status {
+        "New" => Ok(LeadStatus::New),
+        "Contacted" => Ok(LeadStatus::Contacted),
+        "TrialBooked" => Ok(LeadStatus::TrialBooked),
+        "Lost" => Ok(LeadStatus::Lost),
+        "Converted" => Err("请使用转为学员操作完成转化".to_string()),
+        _ => Err(format!("无效的跟进状态: {}", status)),
+    }
+}
@@ -1,23 +1,99 @@
 // src-tauri/src/lib.rs
 
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Datelike, Days, Duration, Months, NaiveDate, Utc};
 use qmx_backend_lib::cash::{Installment, InstallmentStatus, PaymentFrequency};
 use qmx_backend_lib::student::{Class, Subject};
 use qmx_backend_lib::{
     CashBuilder, CashQuery, CashUpdater, QmxManager, StudentBuilder, StudentQuery, StudentUpdater,
-    TimePeriod,
 };
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, OnceLock};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
 use tauri::WindowBuilder;
 
 // 引入验证模块
 mod validation;
 use validation::*;
 
+// 引入扩展数据存储及各业务扩展模块
+mod aging;
+mod attendance;
+mod cash_meta;
+mod custom_field;
+mod discount;
+mod family;
+mod financial_report;
+mod guardian;
+mod late_fee;
+mod lead;
+mod membership;
+mod pagination;
+mod pdf;
+mod receipt;
+mod referral;
+mod revenue_recognition;
+mod search;
+mod segment;
+mod store;
+mod tag;
+mod timeseries;
+mod timezone;
+mod trial;
+mod wallet;
+use aging::{aging_bucket, AgingBuckets};
+use attendance::{last_check_in, CheckIn};
+use cash_meta::{cash_category_label, payment_method_label, CashAuditEntry, CashMeta, FieldChange};
+use custom_field::{parse_custom_field_type, CustomFieldDef, CustomFieldFilter, CustomFieldType};
+use discount::{
+    normalize_coupon_code, parse_discount_category, parse_discount_kind, CashPricing, Coupon,
+    DiscountCategory, DiscountRule,
+};
+use family::{discount_amount, split_evenly, Family, FamilyPayment};
+use financial_report::{
+    build_report, render_pdf, render_xlsx, subject_label, FinancialReport, ReceivableSummary,
+    ReportEntry, ReportPeriod,
+};
+use guardian::{normalize_phones, set_primary, Guardian, GuardianLink};
+use late_fee::{
+    parse_late_fee_kind, parse_late_fee_status, LateFee, LateFeePolicy, LateFeeStatus,
+    LateFeeWaiver,
+};
+use lead::{parse_lead_source, parse_lead_status, Lead, LeadSource, LeadStatus};
+use membership::{
+    active_freeze, current_period_mut, frozen_days_between, membership_last_day,
+    parse_membership_plan, MembershipFreeze, MembershipPeriod,
+};
+use pagination::{Page, PageRequest};
+use pdf::Logo;
+use receipt::{render_receipt, render_statement, ClubProfile, StatementLine, DEFAULT_CLUB_NAME};
+use referral::{Referral, ReferralReward};
+use revenue_recognition::{
+    monthly_recognition, DeferredKind, DeferredPayment, MonthlyRecognition, RevenueSchedule,
+};
+use search::{
+    invalidate_index, name_pinyin, remove_indexed, upsert_indexed, IndexedStudent, SearchDoc,
+    SearchTerm,
+};
+use segment::{Segment, StudentSearchCriteria};
+use store::{init_store, read_store, write_store, ExtStore};
+use tag::{has_all_tags, normalize_tags, MAX_TAGS_PER_STUDENT};
+use timeseries::{
+    build_series, parse_granularity, Granularity, SeriesBucket, SeriesEntry, SeriesValues,
+    SplitLabels,
+};
+use timezone::{
+    club_timezone, club_today, local_day_end, local_day_start, local_days_between,
+    parse_date_input, parse_timezone, period_local_range, report_local_range, set_club_timezone,
+    to_local_date, to_local_date_string, to_local_datetime_string,
+};
+use trial::{TrialConversion, TrialPackage, TrialStatus};
+use wallet::{charge_category_label, wallet_balance, WalletEntry, WalletEntryKind};
+
 // v2 API - 全局QmxManager实例
 // 使用Arc<QmxManager>确保线程安全，自动保存启用
 static MANAGER: OnceLock<Arc<QmxManager>> = OnceLock::new();
+// 最近一次生成滞纳金时的俱乐部本地日期
+static LATE_FEES_SWEPT_ON: Mutex<Option<NaiveDate>> = Mutex::new(None);
 
 // v2 API - 枚举转换辅助函数
 fn parse_class_type(class_type: &str) -> Result<Class, String> {
@@ -45,6 +121,22 @@ fn parse_subject_type(subject: &str) -> Result<Subject, String> {
     }
 }
 
+fn parse_payment_frequency(frequency: &str) -> Result<PaymentFrequency, String> {
+    validate_frequency(frequency)?;
+    match frequency {
+        "Weekly" => Ok(PaymentFrequency::Weekly),
+        "Monthly" => Ok(PaymentFrequency::Monthly),
+        "Quarterly" => Ok(PaymentFrequency::Quarterly),
+        custom => {
+            let days = custom
+                .trim_start_matches("Custom")
+                .parse()
+                .map_err(|_| "自定义频率格式错误，应为Custom<天数>")?;
+            Ok(PaymentFrequency::Custom(days))
+        }
+    }
+}
+
 fn parse_installment_status(status: &str) -> Result<InstallmentStatus, String> {
     match status {
         "Pending" => Ok(InstallmentStatus::Pending),
@@ -60,6 +152,9 @@ fn parse_installment_status(status: &str) -> Result<InstallmentStatus, String> {
 
 // v2 API - 初始化QmxManager（优化版）
 fn init_manager() -> Result<(), String> {
+    // 先加载扩展数据，确保俱乐部时区等设置已生效
+    init_store()?;
+
     // 使用v2 API的线程安全初始化，启用自动保存
     if MANAGER.get().is_none() {
         match QmxManager::new(true) {
@@ -67,16 +162,33 @@ fn init_manager() -> Result<(), String> {
             Ok(manager) => {
                 MANAGER.get_or_init(|| Arc::new(manager));
                 log::info!("v2 API QmxManager初始化成功，自动保存已启用");
-                Ok(())
             }
             Err(e) => {
                 log::error!("v2 API QmxManager初始化失败: {}", e);
-                Err(format!("初始化QmxManager失败: {}", e))
+                return Err(format!("初始化QmxManager失败: {}", e));
             }
         }
-    } else {
-        Ok(())
     }
+
+    // 启动后及每跨过一天补生成逾期产生的滞纳金（程序长时间不关闭也能按时生成），失败不影响当前命令
+    let today = club_today();
+    let due = LATE_FEES_SWEPT_ON
+        .lock()
+        .map(|mut swept_on| {
+            let due = *swept_on != Some(today);
+            *swept_on = Some(today);
+            due
+        })
+        .unwrap_or(false);
+    if due {
+        if let Err(e) = sweep_late_fees(today) {
+            log::warn!("生成滞纳金失败: {}", e);
+            if let Ok(mut swept_on) = LATE_FEES_SWEPT_ON.lock() {
+                *swept_on = None;
+            }
+        }
+    }
+    Ok(())
 }
 
 // v2 API - 获取管理器实例（优化版）
@@ -88,7 +200,16 @@ fn get_manager() -> Result<Arc<QmxManager>, String> {
 }
 
 // v2 API - 学生数据转换辅助函数
-fn convert_student_to_response(student: &qmx_backend_lib::student::Student) -> StudentResponse {
+fn convert_student_to_response(
+    student: &qmx_backend_lib::student::Student,
+    ext: &ExtStore,
+) -> StudentResponse {
+    let freezes = ext
+        .membership_freezes
+        .get(&student.uid())
+        .map(|f| f.as_slice())
+        .unwrap_or_default();
+
     StudentResponse {
         uid: student.uid(),
         name: student.name().to_string(),
@@ -100,13 +221,805 @@ fn convert_student_to_response(student: &qmx_backend_lib::student::Student) -> S
         lesson_left: student.lesson_left(),
         membership_start_date: student.membership_start_date().map(|d| d.to_rfc3339()),
         membership_end_date: student.membership_end_date().map(|d| d.to_rfc3339()),
+        membership_start_local_date: student
+            .membership_start_date()
+            .map(|d| to_local_date_string(&d)),
+        membership_end_local_date: student
+            .membership_end_date()
+            .map(|d| to_local_date_string(&d)),
+        is_membership_active: student.is_membership_active(),
+        // 按俱乐部本地日期计算剩余天数，到期当天为0
+        membership_days_remaining: student
+            .membership_end_date()
+            .map(|end| (to_local_date(&end) - club_today()).num_days().max(0)),
+        is_membership_frozen: active_freeze(freezes).is_some(),
+        freeze_history: freezes.iter().map(convert_freeze_to_response).collect(),
+        referred_by: ext.referrals.get(&student.uid()).map(|r| r.referrer_uid),
+        trial: ext
+            .trials
+            .get(&student.uid())
+            .map(convert_trial_to_response),
+        tags: ext
+            .student_tags
+            .get(&student.uid())
+            .map(|tags| tags.iter().cloned().collect())
+            .unwrap_or_default(),
+        custom_fields: ext
+            .student_custom_fields
+            .get(&student.uid())
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|(field_id, value)| {
+                        ext.custom_fields
+                            .get(field_id)
+                            .map(|f| (f.key.clone(), value.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        wallet_balance: ext
+            .wallets
+            .get(&student.uid())
+            .map(|entries| wallet_balance(entries))
+            .unwrap_or(0),
+        guardians: ext
+            .student_guardians
+            .get(&student.uid())
+            .map(|links| {
+                links
+                    .iter()
+                    .filter_map(|link| {
+                        ext.guardians
+                            .get(&link.guardian_id)
+                            .map(|g| convert_student_guardian_to_response(g, link))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+// 学员监护人转换辅助函数
+fn convert_student_guardian_to_response(
+    guardian: &Guardian,
+    link: &GuardianLink,
+) -> StudentGuardianResponse {
+    StudentGuardianResponse {
+        guardian_id: guardian.id,
+        name: guardian.name.clone(),
+        relationship: guardian.relationship.clone(),
+        phones: guardian.phones.clone(),
+        is_primary: link.is_primary,
+    }
+}
+
+// 监护人转换辅助函数
+fn convert_guardian_to_response(guardian: &Guardian, ext: &ExtStore) -> GuardianResponse {
+    GuardianResponse {
+        id: guardian.id,
+        name: guardian.name.clone(),
+        relationship: guardian.relationship.clone(),
+        phones: guardian.phones.clone(),
+        note: guardian.note.clone(),
+        created_at: guardian.created_at.to_rfc3339(),
+        student_uids: ext.guardian_student_uids(guardian.id),
+    }
+}
+
+// 会员冻结记录转换辅助函数
+fn convert_freeze_to_response(freeze: &MembershipFreeze) -> MembershipFreezeResponse {
+    MembershipFreezeResponse {
+        start_date: freeze.start_date.to_rfc3339(),
+        end_date: freeze.end_date.map(|d| d.to_rfc3339()),
+        frozen_days: freeze.frozen_days,
+        reason: freeze.reason.clone(),
+        is_active: freeze.is_active(),
+    }
+}
+
+// 会员历史记录转换辅助函数
+fn convert_period_to_response(
+    period: &MembershipPeriod,
+    now: &DateTime<Utc>,
+) -> MembershipPeriodResponse {
+    MembershipPeriodResponse {
+        id: period.id,
+        membership_type: period.membership_type.clone(),
+        start_date: period.start_date.to_rfc3339(),
+        end_date: period.end_date.to_rfc3339(),
+        start_local_date: to_local_date_string(&period.start_date),
+        end_local_date: to_local_date_string(&period.end_date),
+        payment_uid: period.payment_uid,
+        operator: period.operator.clone(),
+        created_at: period.created_at.to_rfc3339(),
+        frozen_days: period.frozen_days,
+        cancelled_at: period.cancelled_at.map(|d| d.to_rfc3339()),
+        cancel_reason: period.cancel_reason.clone(),
+        status: period.status(now).to_string(),
+    }
+}
+
+// 会员历史 - 校验关联的付款记录与操作员
+fn validate_membership_link(
+    manager: &QmxManager,
+    payment_uid: Option<u64>,
+    operator: Option<&str>,
+) -> Result<(), String> {
+    if let Some(operator_str) = operator {
+        validate_operator(operator_str)?;
+    }
+    if let Some(uid) = payment_uid {
+        validate_transaction_uid(uid)?;
+        manager
+            .get_cash(uid)
+            .map_err(|e| format!("获取付款记录失败: {}", e))?
+            .ok_or("关联的付款记录不存在")?;
+    }
+    Ok(())
+}
+
+// 会员历史 - 追加一段会员期
+fn record_membership_period(
+    student_uid: u64,
+    membership_type: &str,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    payment_uid: Option<u64>,
+    operator: Option<String>,
+) -> Result<(), String> {
+    write_store(|ext| {
+        let id = ext.alloc_id();
+        ext.membership_history
+            .entry(student_uid)
+            .or_default()
+            .push(MembershipPeriod {
+                id,
+                membership_type: membership_type.to_string(),
+                start_date,
+                end_date,
+                payment_uid,
+                operator: operator.map(|o| o.trim().to_string()),
+                created_at: Utc::now(),
+                frozen_days: 0,
+                cancelled_at: None,
+                cancel_reason: None,
+            });
+        Ok(())
+    })
+}
+
+// 会员历史 - 会员日期被直接修改或清除时同步会员历史
+//
+// 修改为新的起止日期时更正仍有效（未取消且未过期）的最近一期，没有有效会员期时追加一期；
+// 清除会员时取消所有仍有效的会员期，已过期的会员期保持不变
+fn sync_membership_history(
+    ext: &mut ExtStore,
+    student_uid: u64,
+    membership: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    reason: Option<String>,
+) {
+    let now = Utc::now();
+    let periods = ext.membership_history.entry(student_uid).or_default();
+    match membership {
+        Some((start_date, end_date)) => {
+            let live = periods
+                .iter_mut()
+                .filter(|p| p.cancelled_at.is_none() && p.end_date >= now)
+                .max_by_key(|p| p.created_at);
+            if let Some(period) = live {
+                period.start_date = start_date;
+                period.end_date = end_date;
+                return;
+            }
+            let id = ext.alloc_id();
+            ext.membership_history
+                .entry(student_uid)
+                .or_default()
+                .push(MembershipPeriod {
+                    id,
+                    membership_type: "custom".to_string(),
+                    start_date,
+                    end_date,
+                    payment_uid: None,
+                    operator: None,
+                    created_at: now,
+                    frozen_days: 0,
+                    cancelled_at: None,
+                    cancel_reason: None,
+                });
+        }
+        None => {
+            for period in periods
+                .iter_mut()
+                .filter(|p| p.cancelled_at.is_none() && p.end_date >= now)
+            {
+                period.cancelled_at = Some(now);
+                period.cancel_reason = reason.clone();
+            }
+            ext.membership_history
+                .retain(|_, periods| !periods.is_empty());
+        }
+    }
+}
+
+// 签到记录转换辅助函数
+fn convert_check_in_to_response(record: &CheckIn) -> CheckInResponse {
+    CheckInResponse {
+        id: record.id,
+        checked_in_at: record.checked_in_at.to_rfc3339(),
+        local_date: to_local_date_string(&record.checked_in_at),
+        note: record.note.clone(),
+        trial_credit: record.trial_credit,
+    }
+}
+
+// 家庭账户转换辅助函数
+fn convert_family_to_response(family: &Family, manager: &QmxManager) -> FamilyResponse {
+    let members = family
+        .member_uids
+        .iter()
+        .map(|uid| FamilyMemberResponse {
+            uid: *uid,
+            name: manager
+                .get_student(*uid)
+                .ok()
+                .flatten()
+                .map(|s| s.name().to_string())
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    FamilyResponse {
+        id: family.id,
+        name: family.name.clone(),
+        members,
+        discount_percent: family.discount_percent,
+        note: family.note.clone(),
+        created_at: family.created_at.to_rfc3339(),
+    }
+}
+
+// 家庭付款转换辅助函数
+fn convert_family_payment_to_response(payment: &FamilyPayment) -> FamilyPaymentResponse {
+    FamilyPaymentResponse {
+        id: payment.id,
+        family_id: payment.family_id,
+        list_amount: payment.list_amount,
+        discount_amount: payment.discount_amount,
+        paid_amount: payment.list_amount - payment.discount_amount,
+        cash_uids: payment.cash_uids.clone(),
+        note: payment.note.clone(),
+        created_at: payment.created_at.to_rfc3339(),
+    }
+}
+
+// 家庭账户 - 校验学员存在且未加入其他家庭
+fn validate_family_member(
+    manager: &QmxManager,
+    ext: &ExtStore,
+    student_uid: u64,
+    family_id: Option<u64>,
+) -> Result<(), String> {
+    validate_student_uid(student_uid)?;
+    manager
+        .get_student(student_uid)
+        .map_err(|e| format!("获取学生失败: {}", e))?
+        .ok_or_else(|| format!("学员不存在 - UID: {}", student_uid))?;
+    match ext.family_of(student_uid) {
+        Some(existing) if Some(existing) != family_id => {
+            Err(format!("学员已属于其他家庭 - UID: {}", student_uid))
+        }
+        _ => Ok(()),
+    }
+}
+
+// 钱包流水转换辅助函数
+fn convert_wallet_entry_to_response(entry: &WalletEntry) -> WalletEntryResponse {
+    WalletEntryResponse {
+        id: entry.id,
+        kind: format!("{:?}", entry.kind),
+        amount: entry.amount,
+        balance_after: entry.balance_after,
+        category: entry.category.clone(),
+        cash_uid: entry.cash_uid,
+        note: entry.note.clone(),
+        created_at: entry.created_at.to_rfc3339(),
+    }
+}
+
+// 钱包 - 写入一条流水，失败时回滚关联的现金记录
+fn append_wallet_entry(
+    manager: &QmxManager,
+    student_uid: u64,
+    kind: WalletEntryKind,
+    amount: i64,
+    category: Option<String>,
+    cash_uid: u64,
+    note: Option<String>,
+) -> Result<WalletEntry, String> {
+    let result = write_store(|ext| {
+        let entries = ext.wallets.entry(student_uid).or_default();
+        let balance_after = wallet_balance(entries) + amount;
+        if balance_after < 0 {
+            return Err(format!(
+                "钱包余额不足，当前余额: {}",
+                balance_after - amount
+            ));
+        }
+        let id = ext.alloc_id();
+        let entry = WalletEntry {
+            id,
+            kind,
+            amount,
+            balance_after,
+            category,
+            cash_uid,
+            note,
+            created_at: Utc::now(),
+        };
+        ext.wallets
+            .entry(student_uid)
+            .or_default()
+            .push(entry.clone());
+        let meta = ext.record_cash_meta(cash_uid, None, None);
+        if kind == WalletEntryKind::Charge {
+            meta.payment_method = Some("wallet".to_string());
+        }
+        Ok(entry)
+    });
+
+    if result.is_err() {
+        let _ = manager.delete_cash(cash_uid);
+    }
+    result
+}
+
+// 折扣规则转换辅助函数
+fn convert_discount_rule_to_response(rule: &DiscountRule) -> DiscountRuleResponse {
+    DiscountRuleResponse {
+        id: rule.id,
+        name: rule.name.clone(),
+        kind: format!("{:?}", rule.kind),
+        value: rule.value,
+        category: format!("{:?}", rule.category),
+        active: rule.active,
+        created_at: rule.created_at.to_rfc3339(),
+    }
+}
+
+// 优惠券转换辅助函数
+fn convert_coupon_to_response(coupon: &Coupon) -> CouponResponse {
+    CouponResponse {
+        id: coupon.id,
+        code: coupon.code.clone(),
+        rule_id: coupon.rule_id,
+        max_uses: coupon.max_uses,
+        used_count: coupon.used_count,
+        expires_at: coupon.expires_at.map(|d| d.to_rfc3339()),
+        active: coupon.active,
+        created_at: coupon.created_at.to_rfc3339(),
+    }
+}
+
+// 折扣 - 计算交易的标价与折扣（不修改优惠券使用次数）
+//
+// - 指定折扣规则或优惠券：标价为 list_price（未指定时取 amount），折扣由规则计算
+// - 仅指定 list_price：视为手工折扣，折扣为标价与 amount 之差
+fn resolve_cash_pricing(
+    amount: i64,
+    list_price: Option<i64>,
+    discount_rule_id: Option<u64>,
+    coupon_code: Option<String>,
+) -> Result<Option<CashPricing>, String> {
+    if discount_rule_id.is_none() && coupon_code.is_none() {
+        return match list_price {
+            Some(price) if price != amount => {
+                if amount > price || amount < 0 {
+                    return Err("实收金额必须在0与标价之间".to_string());
+                }
+                Ok(Some(CashPricing {
+                    list_price: price,
+                    discount_amount: price - amount,
+                    rule_id: None,
+                    coupon_code: None,
+                    category: None,
+                    created_at: Utc::now(),
+                }))
+            }
+            _ => Ok(None),
+        };
+    }
+
+    let list_price = list_price.unwrap_or(amount);
+    if list_price <= 0 {
+        return Err("只有收入交易可以使用折扣".to_string());
+    }
+
+    let now = Utc::now();
+    read_store(|ext| {
+        let (rule, coupon_code) = match coupon_code {
+            Some(code) => {
+                let code = normalize_coupon_code(&code);
+                let coupon = ext
+                    .coupons
+                    .values()
+                    .find(|c| c.code == code)
+                    .ok_or_else(|| format!("优惠券不存在: {}", code))?;
+                coupon.check_usable(&now)?;
+                if discount_rule_id.is_some_and(|id| id != coupon.rule_id) {
+                    return Err("优惠券与指定的折扣规则不一致".to_string());
+                }
+                let rule = ext
+                    .discount_rules
+                    .get(&coupon.rule_id)
+                    .ok_or("优惠券关联的折扣规则不存在")?;
+                (rule, Some(code))
+            }
+            None => {
+                let rule_id = discount_rule_id.ok_or("未指定折扣规则")?;
+                let rule = ext.discount_rules.get(&rule_id).ok_or("折扣规则不存在")?;
+                (rule, None)
+            }
+        };
+        if !rule.active {
+            return Err(format!("折扣规则已停用: {}", rule.name));
+        }
+
+        Ok(Some(CashPricing {
+            list_price,
+            discount_amount: rule.discount_for(list_price),
+            rule_id: Some(rule.id),
+            coupon_code,
+            category: Some(rule.category),
+            created_at: now,
+        }))
+    })?
+}
+
+// 折扣 - 自动适用的折扣规则：被推荐学员的首笔付款（Referral）、家庭中还有其他成员的学员（Sibling）、
+// 会员到期前续费（EarlyRenewal），有多条规则适用时取减免金额最多的一条
+fn auto_discount_rule(
+    ext: &ExtStore,
+    student_uid: u64,
+    list_price: i64,
+    renewing: bool,
+) -> Option<u64> {
+    let referred = ext
+        .referrals
+        .get(&student_uid)
+        .is_some_and(|r| r.discount_cash_uid.is_none());
+    let has_sibling = ext
+        .family_of(student_uid)
+        .and_then(|id| ext.families.get(&id))
+        .is_some_and(|f| f.member_uids.len() > 1);
+    ext.discount_rules
+        .values()
+        .filter(|rule| rule.active)
+        .filter(|rule| match rule.category {
+            DiscountCategory::Referral => referred,
+            DiscountCategory::Sibling => has_sibling,
+            DiscountCategory::EarlyRenewal => renewing,
+            DiscountCategory::General => false,
+        })
+        .max_by_key(|rule| (rule.discount_for(list_price), std::cmp::Reverse(rule.id)))
+        .map(|rule| rule.id)
+}
+
+// 转介绍 - 记录推荐关系，并按设置自动给推荐人发放奖励
+//
+// 奖励发放失败不影响学员创建，只记录实际发放的部分
+fn record_referral(
+    manager: &QmxManager,
+    referee_uid: u64,
+    referrer_uid: u64,
+) -> Result<(), String> {
+    let reward = read_store(|ext| ext.referral_reward.clone())?;
+    let mut rewarded_lessons = 0;
+    let mut rewarded_days = 0;
+    let mut restarted_at = None;
+    let mut reward_period = None;
+
+    if let Some(referrer) = manager
+        .get_student(referrer_uid)
+        .map_err(|e| format!("获取推荐人失败: {}", e))?
+    {
+        let mut updater = StudentUpdater::new();
+        let mut has_update = false;
+        if reward.extra_lessons > 0 {
+            let lessons = referrer.lesson_left().unwrap_or(0) + reward.extra_lessons;
+            updater = updater.lesson_left(Some(lessons));
+            has_update = true;
+        }
+        // 只有有会员的推荐人才能顺延会员天数；会员已过期时从今天起重新计算，不顺延已结束的会员期
+        let now = Utc::now();
+        let days = Duration::days(i64::from(reward.membership_days));
+        let extended_end = referrer
+            .membership_end_date()
+            .filter(|_| reward.membership_days > 0)
+            .map(|end| {
+                if end >= now {
+                    end + days
+                } else {
+                    restarted_at = Some(now);
+                    now + days
+                }
+            });
+        if let Some(end) = extended_end {
+            let start = restarted_at.or(referrer.membership_start_date());
+            updater = updater.membership(start, Some(end));
+            has_update = true;
+        }
+
+        if has_update {
+            match manager.update_student(referrer_uid, updater) {
+                Ok(_) => {
+                    rewarded_lessons = reward.extra_lessons;
+                    if let Some(end) = extended_end {
+                        rewarded_days = reward.membership_days;
+                        reward_period = restarted_at.map(|start| (start, end));
+                    }
+                }
+                Err(e) => log::error!(
+                    "发放推荐奖励失败 - 推荐人UID: {}, 错误: {}",
+                    referrer_uid,
+                    e
+                ),
+            }
+        }
+    }
+
+    write_store(|ext| {
+        match reward_period {
+            // 会员已过期时奖励作为新的一段会员期
+            Some((start, end)) => {
+                let id = ext.alloc_id();
+                ext.membership_history
+                    .entry(referrer_uid)
+                    .or_default()
+                    .push(MembershipPeriod {
+                        id,
+                        membership_type: "referral_reward".to_string(),
+                        start_date: start,
+                        end_date: end,
+                        payment_uid: None,
+                        operator: None,
+                        created_at: start,
+                        frozen_days: 0,
+                        cancelled_at: None,
+                        cancel_reason: None,
+                    });
+            }
+            None if rewarded_days > 0 => {
+                if let Some(period) = ext
+                    .membership_history
+                    .get_mut(&referrer_uid)
+                    .and_then(|periods| current_period_mut(periods))
+                {
+                    period.end_date += Duration::days(i64::from(rewarded_days));
+                }
+            }
+            None => {}
+        }
+        ext.referrals.insert(
+            referee_uid,
+            Referral {
+                referrer_uid,
+                created_at: Utc::now(),
+                rewarded_lessons,
+                rewarded_days,
+                discount_cash_uid: None,
+            },
+        );
+        Ok(())
+    })?;
+
+    log::info!(
+        "成功记录推荐关系 - 推荐人UID: {}, 新学员UID: {}, 赠送课时: {}, 赠送天数: {}",
+        referrer_uid,
+        referee_uid,
+        rewarded_lessons,
+        rewarded_days
+    );
+    Ok(())
+}
+
+// 潜在客户转换辅助函数
+fn convert_lead_to_response(lead: &Lead) -> LeadResponse {
+    LeadResponse {
+        id: lead.id,
+        name: lead.name.clone(),
+        phone: lead.phone.clone(),
+        age: lead.age,
+        source: format!("{:?}", lead.source),
+        referrer_uid: lead.referrer_uid,
+        status: format!("{:?}", lead.status),
+        follow_ups: lead
+            .follow_ups
+            .iter()
+            .map(|f| LeadFollowUpResponse {
+                status: format!("{:?}", f.status),
+                note: f.note.clone(),
+                operator: f.operator.clone(),
+                created_at: f.created_at.to_rfc3339(),
+            })
+            .collect(),
+        student_uid: lead.student_uid,
+        created_at: lead.created_at.to_rfc3339(),
+        converted_at: lead.converted_at.map(|d| d.to_rfc3339()),
+    }
+}
+
+// 潜在客户 - 校验来源与推荐人（仅转介绍来源可指定推荐人）
+fn validate_lead_referrer(source: LeadSource, referrer_uid: Option<u64>) -> Result<(), String> {
+    let Some(referrer_uid) = referrer_uid else {
+        return Ok(());
+    };
+    if source != LeadSource::Referral {
+        return Err("只有转介绍来源的客户可以指定推荐人".to_string());
+    }
+    validate_student_uid(referrer_uid)?;
+    get_manager()?
+        .get_student(referrer_uid)
+        .map_err(|e| format!("获取推荐人失败: {}", e))?
+        .ok_or("推荐人不存在")?;
+    Ok(())
+}
+
+// 体验课转换辅助函数
+fn convert_trial_to_response(package: &TrialPackage) -> TrialPackageResponse {
+    TrialPackageResponse {
+        credits_total: package.credits_total,
+        credits_used: package.credits_used,
+        credits_left: package.credits_left(),
+        started_at: package.started_at.to_rfc3339(),
+        expires_at: package.expires_at.to_rfc3339(),
+        expires_local_date: to_local_date_string(&package.expires_at),
+        status: format!("{:?}", package.status(&Utc::now())),
+        converted_class: package.conversion.as_ref().map(|c| c.class_type.clone()),
+        payment_uid: package.conversion.as_ref().map(|c| c.payment_uid),
+        converted_at: package
+            .conversion
+            .as_ref()
+            .map(|c| c.converted_at.to_rfc3339()),
+    }
+}
+
+// 体验课 - 为TenTry学员开始体验课包（已有体验课包时保持不变）
+fn start_trial_package(student_uid: u64) -> Result<(), String> {
+    write_store(|ext| {
+        ext.trials
+            .entry(student_uid)
+            .or_insert_with(|| TrialPackage::new(Utc::now()));
+        Ok(())
+    })
+}
+
+// 自定义字段转换辅助函数
+fn convert_custom_field_to_response(field: &CustomFieldDef) -> CustomFieldResponse {
+    CustomFieldResponse {
+        id: field.id,
+        key: field.key.clone(),
+        label: field.label.clone(),
+        field_type: format!("{:?}", field.field_type),
+        required: field.required,
+        options: field.options.clone(),
+        min: field.min,
+        max: field.max,
+        max_length: field.max_length,
+        created_at: field.created_at.to_rfc3339(),
+    }
+}
+
+// 自定义字段 - 按字段定义校验学员的字段值（按字段标识传入，空值表示清除）
+fn resolve_custom_field_values(
+    ext: &ExtStore,
+    values: &HashMap<String, Option<String>>,
+) -> Result<Vec<(u64, Option<String>)>, String> {
+    let mut resolved = Vec::new();
+    for (key, value) in values {
+        let field = ext
+            .custom_field_by_key(key.trim())
+            .ok_or_else(|| format!("自定义字段不存在: {}", key))?;
+        let value = value.as_deref().map(str::trim).filter(|v| !v.is_empty());
+        match value {
+            Some(value) => resolved.push((field.id, Some(field.normalize_value(value)?))),
+            None if field.required => return Err(format!("{}为必填字段，不能清除", field.label)),
+            None => resolved.push((field.id, None)),
+        }
+    }
+    Ok(resolved)
+}
+
+// 自定义字段 - 校验字段类型对应的规则
+fn validate_custom_field_rules(
+    field_type: CustomFieldType,
+    options: &[String],
+    min: Option<f64>,
+    max: Option<f64>,
+    max_length: Option<usize>,
+) -> Result<(), String> {
+    if field_type == CustomFieldType::Enum {
+        validate_custom_field_options(options)?;
+    } else if !options.is_empty() {
+        return Err("只有枚举字段可以设置选项".to_string());
+    }
+    if field_type == CustomFieldType::Number {
+        validate_custom_field_range(min, max)?;
+    } else if min.is_some() || max.is_some() {
+        return Err("只有数值字段可以设置取值范围".to_string());
+    }
+    match (field_type, max_length) {
+        (CustomFieldType::Text, Some(length)) if length == 0 || length > 1000 => {
+            Err("文本字段最大长度必须在1-1000之间".to_string())
+        }
+        (CustomFieldType::Text, _) | (_, None) => Ok(()),
+        _ => Err("只有文本字段可以设置最大长度".to_string()),
+    }
+}
+
+// 学员分组转换辅助函数
+fn convert_segment_to_response(segment: &Segment) -> SegmentResponse {
+    SegmentResponse {
+        id: segment.id,
+        name: segment.name.clone(),
+        criteria: segment.criteria.clone(),
+        created_at: segment.created_at.to_rfc3339(),
+        updated_at: segment.updated_at.to_rfc3339(),
+    }
+}
+
+// 学员列表精简字段转换辅助函数
+fn convert_student_to_summary(
+    student: &qmx_backend_lib::student::Student,
+    ext: &ExtStore,
+) -> StudentSummaryResponse {
+    StudentSummaryResponse {
+        uid: student.uid(),
+        name: student.name().to_string(),
+        class: format!("{:?}", student.class()),
+        subject: format!("{:?}", student.subject()),
+        phone: student.phone().to_string(),
+        lesson_left: student.lesson_left(),
+        membership_end_local_date: student
+            .membership_end_date()
+            .map(|d| to_local_date_string(&d)),
         is_membership_active: student.is_membership_active(),
-        membership_days_remaining: student.membership_days_remaining(),
+        tags: ext
+            .student_tags
+            .get(&student.uid())
+            .map(|tags| tags.iter().cloned().collect())
+            .unwrap_or_default(),
+    }
+}
+
+// 现金记录列表精简字段转换辅助函数
+fn convert_cash_to_summary(
+    cash: &qmx_backend_lib::cash::Cash,
+    ext: &ExtStore,
+) -> TransactionSummaryResponse {
+    TransactionSummaryResponse {
+        uid: cash.uid,
+        business_date: ext
+            .cash_meta
+            .get(&cash.uid)
+            .map(|m| m.business_date.format("%Y-%m-%d").to_string()),
+        student_id: cash.student_id,
+        amount: cash.cash,
+        note: cash.note.clone(),
+        installment_status: cash.installment.as_ref().map(|i| format!("{:?}", i.status)),
+        installment_due_local_date: cash
+            .installment
+            .as_ref()
+            .map(|i| to_local_date_string(&i.due_date)),
     }
 }
 
 // v2 API - 现金记录转换辅助函数
-fn convert_cash_to_response(cash: &qmx_backend_lib::cash::Cash) -> TransactionResponse {
+fn convert_cash_to_response(
+    cash: &qmx_backend_lib::cash::Cash,
+    ext: &ExtStore,
+) -> TransactionResponse {
     let (is_installment, plan_id, current, total, due_date_str, status_str) =
         if let Some(installment) = &cash.installment {
             (
@@ -120,6 +1033,12 @@ fn convert_cash_to_response(cash: &qmx_backend_lib::cash::Cash) -> TransactionRe
         } else {
             (false, None, None, None, None, None)
         };
+    let due_local_date = cash
+        .installment
+        .as_ref()
+        .map(|installment| to_local_date_string(&installment.due_date));
+    let pricing = ext.cash_pricing.get(&cash.uid);
+    let meta = ext.cash_meta.get(&cash.uid);
 
     TransactionResponse {
         uid: cash.uid,
@@ -136,67 +1055,532 @@ fn convert_cash_to_response(cash: &qmx_backend_lib::cash::Cash) -> TransactionRe
         installment_current: current,
         installment_total: total,
         installment_due_date: due_date_str,
+        installment_due_local_date: due_local_date,
         installment_status: status_str,
+        list_price: pricing.map(|p| p.list_price),
+        discount_amount: pricing.map(|p| p.discount_amount),
+        discount_rule_id: pricing.and_then(|p| p.rule_id),
+        coupon_code: pricing.and_then(|p| p.coupon_code.clone()),
+        business_date: meta.map(|m| m.business_date.format("%Y-%m-%d").to_string()),
+        created_at: meta.and_then(|m| m.created_at).map(|t| t.to_rfc3339()),
+        updated_at: meta.map(|m| m.updated_at.to_rfc3339()),
+        operator: meta.and_then(|m| m.operator.clone()),
+        category: meta.and_then(|m| m.category.clone()),
+        category_label: meta
+            .and_then(|m| m.category.as_deref())
+            .map(|c| cash_category_label(c).to_string()),
+        payment_method: meta.and_then(|m| m.payment_method.clone()),
+        payment_method_label: meta
+            .and_then(|m| m.payment_method.as_deref())
+            .map(|m| payment_method_label(m).to_string()),
     }
 }
 
-// 已在上面合并处理
+// 现金记录 - 解析业务日期（YYYY-MM-DD或RFC3339，按俱乐部本地日期）
+fn parse_business_date(input: &str) -> Result<NaiveDate, String> {
+    let date = to_local_date(&parse_date_input(input).map_err(|e| format!("业务日期{}", e))?);
+    validate_business_date(date, club_today())?;
+    Ok(date)
+}
 
-// 窗口管理命令
-#[cfg(not(any(target_os = "android", target_os = "ios")))]
-#[tauri::command]
-fn open_main_window(app: tauri::AppHandle) {
-    // 桌面端：完整功能
-    let _ = WindowBuilder::new(&app, "main")
-        .title("启明星管理系统")
-        .inner_size(1200.0, 800.0)
-        .min_inner_size(800.0, 600.0)
-        .center()
-        .build();
+// 收据 - 读取收据抬头使用的俱乐部名称和 Logo（Logo 读取失败时不显示 Logo）
+fn load_club_profile(club_name: Option<String>, logo_path: Option<String>) -> ClubProfile {
+    let logo = logo_path.and_then(|path| {
+        std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(Logo::from_jpeg)
+            .map_err(|e| log::warn!("Logo 加载失败 - 路径: {}, 错误: {}", path, e))
+            .ok()
+    });
+    ClubProfile {
+        name: club_name.unwrap_or_else(|| DEFAULT_CLUB_NAME.to_string()),
+        logo,
+    }
 }
 
-// v2 API - 学员管理命令（完全优化版）
-#[tauri::command]
-fn add_student(
-    name: String,
-    age: Option<u8>,
-    class_type: String,
-    phone: String,
-    note: String,
-    subject: String,
-) -> Result<StudentResponse, String> {
-    init_manager()?;
+// 收据 - 写入PDF文件（自动创建目录）
+fn write_pdf_file(path: &std::path::Path, content: &[u8]) -> Result<(), String> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| format!("创建输出目录失败: {}", e))?;
+    }
+    std::fs::write(path, content).map_err(|e| format!("写入PDF文件失败: {}", e))
+}
 
-    // v2 API - 增强输入验证（完整的后端验证）
-    validate_student_name(&name)?;
-    if let Some(age_val) = age {
-        validate_age(age_val)?;
+// 分期 - 是否已逾期：状态为 Overdue，或仍待付款且到期日（本地日期）早于今天
+fn is_installment_overdue(installment: &Installment, today: NaiveDate) -> bool {
+    match installment.status {
+        InstallmentStatus::Overdue => true,
+        InstallmentStatus::Pending => to_local_date(&installment.due_date) < today,
+        _ => false,
     }
-    validate_phone_number(&phone)?;
-    validate_note(&note)?;
-    validate_class_type(&class_type)?;
-    validate_subject_type(&subject)?;
+}
 
-    // v2 API - 使用枚举转换辅助函数
-    let class = parse_class_type(&class_type)?;
-    let subject_enum = parse_subject_type(&subject)?;
+// 财务报表 - 是否为实际收付的款项（未付和已取消的分期不计入收支）
+fn is_cash_settled(cash: &qmx_backend_lib::cash::Cash) -> bool {
+    cash.installment
+        .as_ref()
+        .is_none_or(|i| matches!(i.status, InstallmentStatus::Paid))
+}
 
-    // v2 API - 使用构建器模式创建学生
+// 财务报表 - 本地日期区间内的现金记录：按业务日期筛选，没有业务日期的旧记录按录入时间筛选
+fn cash_in_local_range(
+    start_day: NaiveDate,
+    end_day: NaiveDate,
+) -> Result<Vec<qmx_backend_lib::cash::Cash>, String> {
     let manager = get_manager()?;
-    let mut builder = StudentBuilder::new(name.trim())
-        .phone(phone.trim())
-        .class(class)
-        .subject(subject_enum)
-        .note(note.trim());
-    
-    // 如果提供了年龄，则设置年龄
-    if let Some(age_value) = age {
-        builder = builder.age(age_value);
-    }
-    
-    let uid = manager.create_student(builder).map_err(|e| {
-        log::error!("v2 API创建学生失败: {}", e);
-        format!("创建学生失败: {}", e)
+    let legacy_in_range: HashSet<u64> = manager
+        .search_cash(
+            CashQuery::new().date_range(local_day_start(start_day), local_day_end(end_day)),
+        )
+        .map_err(|e| format!("获取现金记录失败: {}", e))?
+        .iter()
+        .map(|cash| cash.uid)
+        .collect();
+    let all_cash = manager
+        .search_cash(CashQuery::new())
+        .map_err(|e| format!("获取现金记录失败: {}", e))?;
+
+    read_store(|ext| {
+        all_cash
+            .into_iter()
+            .filter(|cash| match ext.cash_meta.get(&cash.uid) {
+                Some(meta) => meta.business_date >= start_day && meta.business_date <= end_day,
+                None => legacy_in_range.contains(&cash.uid),
+            })
+            .collect()
+    })
+}
+
+// 收支趋势 - 现金记录所在的本地日期：优先取业务日期；没有业务日期的旧记录只能按录入时间查询，
+// 逐个时间段定位，取所在时间段的第一天（不在任何时间段内的旧记录不返回）
+fn cash_local_dates(
+    cash_list: &[qmx_backend_lib::cash::Cash],
+    buckets: &[(NaiveDate, NaiveDate)],
+) -> Result<HashMap<u64, NaiveDate>, String> {
+    let mut dates: HashMap<u64, NaiveDate> = read_store(|ext| {
+        cash_list
+            .iter()
+            .filter_map(|cash| {
+                ext.cash_meta
+                    .get(&cash.uid)
+                    .map(|m| (cash.uid, m.business_date))
+            })
+            .collect()
+    })?;
+    if cash_list.iter().all(|cash| dates.contains_key(&cash.uid)) {
+        return Ok(dates);
+    }
+
+    let legacy: HashSet<u64> = cash_list
+        .iter()
+        .map(|cash| cash.uid)
+        .filter(|uid| !dates.contains_key(uid))
+        .collect();
+    let manager = get_manager()?;
+    for (bucket_start, bucket_end) in buckets {
+        let found = manager
+            .search_cash(
+                CashQuery::new()
+                    .date_range(local_day_start(*bucket_start), local_day_end(*bucket_end)),
+            )
+            .map_err(|e| format!("获取现金记录失败: {}", e))?;
+        for cash in found.iter().filter(|cash| legacy.contains(&cash.uid)) {
+            dates.entry(cash.uid).or_insert(*bucket_start);
+        }
+    }
+    Ok(dates)
+}
+
+// 收入确认 - 需要延期确认的会员费和体验课包收款（只统计已收款的收入记录）
+//
+// dates 为现金记录的业务日期；查不到日期的收款按确认开始日期作为收款日期
+fn collect_deferred_payments(
+    cash_list: &[qmx_backend_lib::cash::Cash],
+    dates: &HashMap<u64, NaiveDate>,
+) -> Result<Vec<DeferredPayment>, String> {
+    let income: HashMap<u64, &qmx_backend_lib::cash::Cash> = cash_list
+        .iter()
+        .filter(|cash| cash.cash > 0 && is_cash_settled(cash))
+        .map(|cash| (cash.uid, cash))
+        .collect();
+
+    read_store(|ext| {
+        let mut payments: Vec<DeferredPayment> = Vec::new();
+        let mut seen: HashSet<u64> = HashSet::new();
+
+        let mut periods: Vec<&MembershipPeriod> =
+            ext.membership_history.values().flatten().collect();
+        periods.sort_by_key(|p| p.created_at);
+        for period in periods {
+            let Some(cash) = period.payment_uid.and_then(|uid| income.get(&uid)) else {
+                continue;
+            };
+            if !seen.insert(cash.uid) {
+                continue;
+            }
+            let start = to_local_date(&period.start_date);
+            let mut end = to_local_date(&period.end_date);
+            if let Some(cancelled_at) = &period.cancelled_at {
+                end = end.min(to_local_date(cancelled_at));
+            }
+            payments.push(DeferredPayment {
+                cash_uid: cash.uid,
+                kind: DeferredKind::Membership,
+                amount: cash.cash,
+                received_on: dates.get(&cash.uid).copied().unwrap_or(start),
+                schedule: RevenueSchedule::Straight {
+                    start,
+                    end: end.max(start),
+                },
+            });
+        }
+
+        for (student_uid, package) in &ext.trials {
+            let usage_dates: Vec<NaiveDate> = ext
+                .attendance
+                .get(student_uid)
+                .into_iter()
+                .flatten()
+                .filter(|check_in| check_in.trial_credit)
+                .map(|check_in| to_local_date(&check_in.checked_in_at))
+                .collect();
+            let expires_on = to_local_date(&package.expires_at);
+            let break_date = package
+                .conversion
+                .as_ref()
+                .map(|c| to_local_date(&c.converted_at).min(expires_on))
+                .unwrap_or(expires_on);
+            let trial_payments = income.values().filter(|cash| {
+                cash.student_id == Some(*student_uid)
+                    && ext
+                        .cash_meta
+                        .get(&cash.uid)
+                        .is_some_and(|m| m.category.as_deref() == Some("trial"))
+            });
+            for cash in trial_payments {
+                if !seen.insert(cash.uid) {
+                    continue;
+                }
+                payments.push(DeferredPayment {
+                    cash_uid: cash.uid,
+                    kind: DeferredKind::LessonPack,
+                    amount: cash.cash,
+                    received_on: dates
+                        .get(&cash.uid)
+                        .copied()
+                        .unwrap_or_else(|| to_local_date(&package.started_at)),
+                    schedule: RevenueSchedule::Usage {
+                        total_units: package.credits_total,
+                        usage_dates: usage_dates.clone(),
+                        break_date,
+                    },
+                });
+            }
+        }
+
+        payments.sort_by_key(|p| p.cash_uid);
+        payments
+    })
+}
+
+// 滞纳金 - 为逾期超过宽限天数、且所属计划设置了滞纳金规则的分期生成滞纳金（每期最多一笔）
+fn generate_late_fees(
+    installments: &[qmx_backend_lib::cash::Cash],
+    as_of: NaiveDate,
+) -> Result<Vec<LateFee>, String> {
+    write_store(|ext| {
+        let mut created = Vec::new();
+        for cash in installments {
+            let Some(installment) = &cash.installment else {
+                continue;
+            };
+            if !is_installment_overdue(installment, as_of) {
+                continue;
+            }
+            let Some(policy) = ext.late_fee_policies.get(&installment.plan_id) else {
+                continue;
+            };
+            // 手动标记为逾期的分期同样要超过宽限天数才产生滞纳金
+            let days_past_due = (as_of - to_local_date(&installment.due_date)).num_days();
+            if days_past_due <= i64::from(policy.grace_days) {
+                continue;
+            }
+            let amount = policy.fee_for(cash.cash);
+            if amount <= 0
+                || ext
+                    .late_fees
+                    .values()
+                    .any(|fee| fee.installment_uid == cash.uid)
+            {
+                continue;
+            }
+            let fee = LateFee {
+                id: ext.alloc_id(),
+                plan_id: installment.plan_id,
+                installment_uid: cash.uid,
+                student_uid: cash.student_id,
+                amount,
+                status: LateFeeStatus::Pending,
+                created_at: Utc::now(),
+                payment_uid: None,
+                paid_at: None,
+                waiver: None,
+            };
+            ext.late_fees.insert(fee.id, fee.clone());
+            created.push(fee);
+        }
+        Ok(created)
+    })
+}
+
+// 滞纳金 - 检查所有分期并生成截至指定日期应产生的滞纳金
+fn sweep_late_fees(as_of: NaiveDate) -> Result<Vec<LateFee>, String> {
+    let installments = get_manager()?
+        .search_cash(CashQuery::new().has_installment(true))
+        .map_err(|e| format!("获取分期记录失败: {}", e))?;
+    let created = generate_late_fees(&installments, as_of)?;
+    if !created.is_empty() {
+        log::info!("已生成{}笔滞纳金 - 截至: {}", created.len(), as_of);
+    }
+    Ok(created)
+}
+
+// 滞纳金转换辅助函数
+fn convert_late_fee_to_response(fee: &LateFee) -> LateFeeResponse {
+    LateFeeResponse {
+        id: fee.id,
+        plan_id: fee.plan_id,
+        installment_uid: fee.installment_uid,
+        student_uid: fee.student_uid,
+        amount: fee.amount,
+        status: format!("{:?}", fee.status),
+        created_at: fee.created_at.to_rfc3339(),
+        payment_uid: fee.payment_uid,
+        paid_at: fee.paid_at.map(|t| t.to_rfc3339()),
+        waiver_reason: fee.waiver.as_ref().map(|w| w.reason.clone()),
+        waived_by: fee.waiver.as_ref().and_then(|w| w.operator.clone()),
+        waived_at: fee.waiver.as_ref().map(|w| w.waived_at.to_rfc3339()),
+    }
+}
+
+// 滞纳金规则转换辅助函数
+fn convert_late_fee_policy_to_response(
+    plan_id: u64,
+    policy: &LateFeePolicy,
+) -> LateFeePolicyResponse {
+    LateFeePolicyResponse {
+        plan_id,
+        kind: format!("{:?}", policy.kind),
+        value: policy.value,
+        grace_days: policy.grace_days,
+        cap: policy.cap,
+        created_at: policy.created_at.to_rfc3339(),
+        updated_at: policy.updated_at.to_rfc3339(),
+    }
+}
+
+// 分期 - 获取分期计划的所有分期（含已取消），按期数排序
+fn find_plan_installments(
+    manager: &QmxManager,
+    plan_id: u64,
+) -> Result<Vec<(qmx_backend_lib::cash::Cash, Installment)>, String> {
+    let mut plan: Vec<_> = manager
+        .search_cash(CashQuery::new().has_installment(true))
+        .map_err(|e| format!("查询分期付款失败: {}", e))?
+        .into_iter()
+        .filter_map(|cash| {
+            let installment = cash.installment.clone()?;
+            (installment.plan_id == plan_id).then_some((cash, installment))
+        })
+        .collect();
+    if plan.is_empty() {
+        return Err("未找到指定的分期计划".to_string());
+    }
+    plan.sort_by_key(|(_, installment)| installment.current_installment);
+    Ok(plan)
+}
+
+// 分期 - 计划的未付余额：总金额减去已付分期的实收金额，以及结清时给予的折扣减免
+fn plan_balance(
+    plan: &[&(qmx_backend_lib::cash::Cash, Installment)],
+    total_amount: i64,
+) -> Result<i64, String> {
+    let paid: Vec<&qmx_backend_lib::cash::Cash> = plan
+        .iter()
+        .filter(|(_, i)| i.status == InstallmentStatus::Paid)
+        .map(|(cash, _)| cash)
+        .collect();
+    let discounted: i64 = read_store(|ext| {
+        paid.iter()
+            .filter_map(|cash| ext.cash_pricing.get(&cash.uid))
+            .map(|p| p.discount_amount)
+            .sum()
+    })?;
+    Ok(total_amount - paid.iter().map(|cash| cash.cash).sum::<i64>() - discounted)
+}
+
+// 分期 - 调整或结清分期计划中途失败时回滚：恢复已修改的分期记录，删除新生成的记录
+fn rollback_plan_changes(
+    manager: &QmxManager,
+    originals: &[(qmx_backend_lib::cash::Cash, Installment)],
+    updated_uids: &[u64],
+    created_uids: &[u64],
+) {
+    for (cash, installment) in originals
+        .iter()
+        .filter(|(cash, _)| updated_uids.contains(&cash.uid))
+    {
+        let updater = CashUpdater::new()
+            .cash(cash.cash)
+            .installment(Some(installment.clone()));
+        if let Err(e) = manager.update_cash(cash.uid, updater) {
+            log::error!("恢复分期记录失败 - UID: {}, 错误: {}", cash.uid, e);
+        }
+    }
+    for uid in created_uids {
+        let _ = manager.delete_cash(*uid);
+    }
+}
+
+// 分期 - 按付款频率将到期日向后推若干期
+fn advance_due_date(
+    due_date: DateTime<Utc>,
+    frequency: PaymentFrequency,
+    periods: u32,
+) -> Result<DateTime<Utc>, String> {
+    match frequency {
+        PaymentFrequency::Weekly => due_date.checked_add_days(Days::new(7 * u64::from(periods))),
+        PaymentFrequency::Monthly => due_date.checked_add_months(Months::new(periods)),
+        PaymentFrequency::Quarterly => due_date.checked_add_months(Months::new(3 * periods)),
+        PaymentFrequency::Custom(days) => {
+            due_date.checked_add_days(Days::new(u64::from(days) * u64::from(periods)))
+        }
+    }
+    .ok_or_else(|| "到期日期超出范围".to_string())
+}
+
+// 快速搜索 - 学员的搜索索引内容（姓名、电话、监护人电话、标签、自定义字段、备注）
+fn index_student(student: &qmx_backend_lib::student::Student, ext: &ExtStore) -> IndexedStudent {
+    let uid = student.uid();
+    let doc = SearchDoc {
+        name: student.name(),
+        phones: vec![student.phone()],
+        guardian_phones: ext
+            .student_guardians
+            .get(&uid)
+            .into_iter()
+            .flatten()
+            .filter_map(|link| ext.guardians.get(&link.guardian_id))
+            .flat_map(|g| g.phones.iter().map(|p| p.as_str()))
+            .collect(),
+        tags: ext
+            .student_tags
+            .get(&uid)
+            .into_iter()
+            .flatten()
+            .map(|t| t.as_str())
+            .collect(),
+        custom_values: ext
+            .student_custom_fields
+            .get(&uid)
+            .into_iter()
+            .flat_map(|values| values.values().map(|v| v.as_str()))
+            .collect(),
+        note: student.note(),
+    };
+    IndexedStudent::new(uid, &doc)
+}
+
+// 快速搜索 - 学员信息修改后刷新搜索索引（学员已删除时移出索引；刷新失败时索引整体失效）
+fn refresh_search_index(student_uids: &[u64]) {
+    let refresh = || -> Result<(), String> {
+        let manager = get_manager()?;
+        let mut indexed = Vec::new();
+        for uid in student_uids {
+            match manager
+                .get_student(*uid)
+                .map_err(|e| format!("获取学生信息失败: {}", e))?
+            {
+                Some(student) => indexed.push(read_store(|ext| index_student(&student, ext))?),
+                None => remove_indexed(*uid),
+            }
+        }
+        upsert_indexed(indexed);
+        Ok(())
+    };
+    if let Err(e) = refresh() {
+        log::warn!("刷新搜索索引失败，下次搜索时重新建立: {}", e);
+        invalidate_index();
+    }
+}
+
+// 已在上面合并处理
+
+// 窗口管理命令
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+#[tauri::command]
+fn open_main_window(app: tauri::AppHandle) {
+    // 桌面端：完整功能
+    let _ = WindowBuilder::new(&app, "main")
+        .title("启明星管理系统")
+        .inner_size(1200.0, 800.0)
+        .min_inner_size(800.0, 600.0)
+        .center()
+        .build();
+}
+
+// v2 API - 学员管理命令（完全优化版）
+#[tauri::command]
+fn add_student(
+    name: String,
+    age: Option<u8>,
+    class_type: String,
+    phone: String,
+    note: String,
+    subject: String,
+    referred_by: Option<u64>,
+) -> Result<StudentResponse, String> {
+    init_manager()?;
+
+    // v2 API - 增强输入验证（完整的后端验证）
+    validate_student_name(&name)?;
+    if let Some(age_val) = age {
+        validate_age(age_val)?;
+    }
+    validate_phone_number(&phone)?;
+    validate_note(&note)?;
+    validate_class_type(&class_type)?;
+    validate_subject_type(&subject)?;
+    if let Some(referrer_uid) = referred_by {
+        validate_student_uid(referrer_uid)?;
+    }
+
+    // v2 API - 使用枚举转换辅助函数
+    let class = parse_class_type(&class_type)?;
+    let subject_enum = parse_subject_type(&subject)?;
+
+    // v2 API - 使用构建器模式创建学生
+    let manager = get_manager()?;
+    if let Some(referrer_uid) = referred_by {
+        manager
+            .get_student(referrer_uid)
+            .map_err(|e| format!("获取推荐人失败: {}", e))?
+            .ok_or("推荐人不存在")?;
+    }
+    let mut builder = StudentBuilder::new(name.trim())
+        .phone(phone.trim())
+        .class(class)
+        .subject(subject_enum)
+        .note(note.trim());
+
+    // 如果提供了年龄，则设置年龄
+    if let Some(age_value) = age {
+        builder = builder.age(age_value);
+    }
+
+    let uid = manager.create_student(builder).map_err(|e| {
+        log::error!("v2 API创建学生失败: {}", e);
+        format!("创建学生失败: {}", e)
     })?;
 
     // v2 API - 获取创建的学生信息（自动保存已处理）
@@ -206,21 +1590,19 @@ fn add_student(
         .ok_or("学生创建后未找到")?;
 
     log::info!("v2 API成功创建学生: {} (UID: {})", student.name(), uid);
+    refresh_search_index(&[uid]);
 
-    Ok(StudentResponse {
-        uid: student.uid(),
-        name: student.name().to_string(),
-        age: student.age(),
-        class: format!("{:?}", student.class()),
-        phone: student.phone().to_string(),
-        note: student.note().to_string(),
-        subject: format!("{:?}", student.subject()),
-        lesson_left: student.lesson_left(),
-        membership_start_date: student.membership_start_date().map(|d| d.to_rfc3339()),
-        membership_end_date: student.membership_end_date().map(|d| d.to_rfc3339()),
-        is_membership_active: student.is_membership_active(),
-        membership_days_remaining: student.membership_days_remaining(),
-    })
+    // 体验课学员开始计算体验次数和有效期
+    if class_type == "TenTry" {
+        start_trial_package(uid)?;
+    }
+
+    // 记录推荐关系并给推荐人发放奖励
+    if let Some(referrer_uid) = referred_by {
+        record_referral(&manager, uid, referrer_uid)?;
+    }
+
+    read_store(|ext| convert_student_to_response(&student, ext))
 }
 
 // v2 API - 获取所有学生（优化版）
@@ -235,10 +1617,12 @@ fn get_all_students() -> Result<Vec<StudentResponse>, String> {
     })?;
 
     // v2 API - 使用迭代器和辅助函数进行高效转换
-    let student_responses: Vec<StudentResponse> = students
-        .into_iter()
-        .map(|student| convert_student_to_response(&student))
-        .collect();
+    let student_responses: Vec<StudentResponse> = read_store(|ext| {
+        students
+            .iter()
+            .map(|student| convert_student_to_response(student, ext))
+            .collect()
+    })?;
 
     log::info!("v2 API成功获取{}个学生记录", student_responses.len());
     Ok(student_responses)
@@ -387,6 +1771,7 @@ fn update_student_info(
     lesson_left: Option<u32>,
     membership_start_date: Option<String>,
     membership_end_date: Option<String>,
+    custom_fields: Option<HashMap<String, Option<String>>>,
 ) -> Result<(), String> {
     init_manager()?;
 
@@ -418,6 +1803,12 @@ fn update_student_info(
         }
     }
 
+    // 自定义字段按定义的规则校验，空值表示清除
+    let custom_values = match &custom_fields {
+        Some(values) => read_store(|ext| resolve_custom_field_values(ext, values))??,
+        None => Vec::new(),
+    };
+
     let manager = get_manager()?;
     let mut updater = StudentUpdater::new();
 
@@ -427,6 +1818,7 @@ fn update_student_info(
     if let Some(age) = age {
         updater = updater.age(age);
     }
+    let starts_trial = class_type.as_deref() == Some("TenTry");
     // v2 API - 使用辅助函数进行类型转换
     if let Some(class_type) = class_type {
         let class = parse_class_type(&class_type)?;
@@ -446,7 +1838,8 @@ fn update_student_info(
         updater = updater.lesson_left(Some(lesson_left));
     }
 
-    // 处理会员时间更新
+    // 处理会员时间更新，修改后的起止日期同步到会员历史
+    let mut membership_change = None;
     match (membership_start_date, membership_end_date) {
         (Some(start_str), Some(end_str)) => {
             let start_date = DateTime::parse_from_rfc3339(&start_str)
@@ -456,6 +1849,7 @@ fn update_student_info(
                 .map_err(|e| format!("会员结束日期格式错误: {}", e))?
                 .with_timezone(&Utc);
             updater = updater.membership(Some(start_date), Some(end_date));
+            membership_change = Some((Some(start_date), Some(end_date)));
         }
         (Some(start_str), None) => {
             let start_date = DateTime::parse_from_rfc3339(&start_str)
@@ -467,6 +1861,7 @@ fn update_student_info(
                 .map_err(|e| format!("获取学生失败: {}", e))?
             {
                 updater = updater.membership(Some(start_date), student.membership_end_date());
+                membership_change = Some((Some(start_date), student.membership_end_date()));
             }
         }
         (None, Some(end_str)) => {
@@ -479,6 +1874,7 @@ fn update_student_info(
                 .map_err(|e| format!("获取学生失败: {}", e))?
             {
                 updater = updater.membership(student.membership_start_date(), Some(end_date));
+                membership_change = Some((student.membership_start_date(), Some(end_date)));
             }
         }
         (None, None) => {
@@ -490,6 +1886,31 @@ fn update_student_info(
         .update_student(student_uid, updater)
         .map_err(|e| format!("更新学员信息失败: {}", e))?;
 
+    if let Some((Some(start_date), Some(end_date))) = membership_change {
+        write_store(|ext| {
+            sync_membership_history(ext, student_uid, Some((start_date, end_date)), None);
+            Ok(())
+        })?;
+    }
+    if starts_trial {
+        start_trial_package(student_uid)?;
+    }
+    if !custom_values.is_empty() {
+        write_store(|ext| {
+            let values = ext.student_custom_fields.entry(student_uid).or_default();
+            for (field_id, value) in custom_values {
+                match value {
+                    Some(value) => values.insert(field_id, value),
+                    None => values.remove(&field_id),
+                };
+            }
+            ext.student_custom_fields
+                .retain(|_, values| !values.is_empty());
+            Ok(())
+        })?;
+    }
+    refresh_search_index(&[student_uid]);
+
     Ok(())
 }
 
@@ -499,6 +1920,8 @@ fn set_student_membership(
     student_uid: u64,
     start_date: Option<String>,
     end_date: Option<String>,
+    payment_uid: Option<u64>,
+    operator: Option<String>,
 ) -> Result<(), String> {
     init_manager()?;
 
@@ -540,6 +1963,7 @@ fn set_student_membership(
     }
 
     let manager = get_manager()?;
+    validate_membership_link(&manager, payment_uid, operator.as_deref())?;
     manager
         .update_student(
             student_uid,
@@ -550,6 +1974,11 @@ fn set_student_membership(
             format!("设置会员时间失败: {}", e)
         })?;
 
+    // 完整的会员期才记入会员历史
+    if let (Some(start), Some(end)) = (parsed_start, parsed_end) {
+        record_membership_period(student_uid, "custom", start, end, payment_uid, operator)?;
+    }
+
     log::info!(
         "v2 API成功设置学生会员时间 - UID: {}, 开始: {:?}, 结束: {:?}",
         student_uid,
@@ -561,11 +1990,14 @@ fn set_student_membership(
 
 // v2 API - 清除会员信息（优化版）
 #[tauri::command]
-fn clear_student_membership(student_uid: u64) -> Result<(), String> {
+fn clear_student_membership(student_uid: u64, reason: Option<String>) -> Result<(), String> {
     init_manager()?;
 
     // 输入验证（完整的后端验证）
     validate_student_uid(student_uid)?;
+    if let Some(reason_str) = &reason {
+        validate_note(reason_str)?;
+    }
 
     let manager = get_manager()?;
     manager
@@ -575,6 +2007,12 @@ fn clear_student_membership(student_uid: u64) -> Result<(), String> {
             format!("清除会员信息失败: {}", e)
         })?;
 
+    // 在会员历史中把仍有效的会员期标记为已取消
+    write_store(|ext| {
+        sync_membership_history(ext, student_uid, None, reason.map(|r| r.trim().to_string()));
+        Ok(())
+    })?;
+
     log::info!("v2 API成功清除学生会员信息 - UID: {}", student_uid);
     Ok(())
 }
@@ -583,41 +2021,58 @@ fn clear_student_membership(student_uid: u64) -> Result<(), String> {
 #[tauri::command]
 fn set_membership_by_type(
     student_uid: u64,
-    membership_type: String, // "month" 或 "year"
+    membership_type: String, // "month" | "quarter" | "half_year" | "year" | "days" | "months"
     start_from_today: Option<bool>,
+    custom_days: Option<u32>,
+    custom_months: Option<u32>,
+    start_date: Option<String>,
+    payment_uid: Option<u64>,
+    operator: Option<String>,
 ) -> Result<(), String> {
     init_manager()?;
 
     // 输入验证（完整的后端验证）
     validate_student_uid(student_uid)?;
+    validate_membership_type(&membership_type)?;
+    if let Some(days) = custom_days {
+        validate_membership_days(days)?;
+    }
+    if let Some(months) = custom_months {
+        validate_membership_months(months)?;
+    }
 
+    let plan = parse_membership_plan(&membership_type, custom_days, custom_months)?;
     let manager = get_manager()?;
-
-    // v2 API - 智能确定开始日期
-    let start_date = if start_from_today.unwrap_or(true) {
-        Utc::now()
+    validate_membership_link(&manager, payment_uid, operator.as_deref())?;
+
+    // v2 API - 智能确定开始日期（俱乐部本地日期）
+    let start_day = if let Some(start_str) = start_date {
+        let parsed = parse_date_input(&start_str).map_err(|e| format!("会员开始日期{}", e))?;
+        to_local_date(&parsed)
+    } else if start_from_today.unwrap_or(true) {
+        club_today()
     } else {
-        // 如果已有会员，从现有结束时间开始
+        // 如果已有会员，从现有结束日期的次日开始续期
         if let Some(student) = manager.get_student(student_uid).map_err(|e| {
             log::error!("v2 API获取学生失败 - UID: {}, 错误: {}", student_uid, e);
             format!("获取学生失败: {}", e)
         })? {
-            student.membership_end_date().unwrap_or(Utc::now())
+            match student.membership_end_date() {
+                Some(end) => to_local_date(&end)
+                    .succ_opt()
+                    .ok_or("会员开始日期超出范围")?,
+                None => club_today(),
+            }
         } else {
             log::warn!("v2 API学员不存在 - UID: {}", student_uid);
             return Err("学员不存在".to_string());
         }
     };
 
-    // v2 API - 计算结束日期
-    let end_date = match membership_type.as_str() {
-        "month" => start_date + Duration::days(30),
-        "year" => start_date + Duration::days(365),
-        _ => {
-            log::error!("v2 API无效的会员类型: {}", membership_type);
-            return Err("无效的会员类型，只支持 'month' 或 'year'".to_string());
-        }
-    };
+    // v2 API - 按自然月/自然日计算结束日期，会员在最后一天结束时到期
+    let last_day = membership_last_day(start_day, &plan)?;
+    let start_date = local_day_start(start_day);
+    let end_date = local_day_end(last_day);
 
     manager
         .update_student(
@@ -634,6 +2089,15 @@ fn set_membership_by_type(
             format!("设置{}会员失败: {}", membership_type, e)
         })?;
 
+    record_membership_period(
+        student_uid,
+        &membership_type,
+        start_date,
+        end_date,
+        payment_uid,
+        operator,
+    )?;
+
     log::info!(
         "v2 API成功设置{}会员 - UID: {}, 开始: {}, 结束: {}",
         membership_type,
@@ -644,67 +2108,310 @@ fn set_membership_by_type(
     Ok(())
 }
 
-// v2 API - 删除学生（优化版）
+// 会员冻结 - 开始冻结会员（假期、伤病等）
 #[tauri::command]
-fn delete_student(student_uid: u64) -> Result<(), String> {
+fn freeze_membership(
+    student_uid: u64,
+    start_date: Option<String>,
+    reason: Option<String>,
+) -> Result<MembershipFreezeResponse, String> {
     init_manager()?;
 
     // 输入验证（完整的后端验证）
     validate_student_uid(student_uid)?;
+    if let Some(reason_str) = &reason {
+        validate_note(reason_str)?;
+    }
+
+    let start = match start_date {
+        Some(start_str) => {
+            parse_date_input(&start_str).map_err(|e| format!("冻结开始日期{}", e))?
+        }
+        None => Utc::now(),
+    };
 
     let manager = get_manager()?;
-    let deleted = manager.delete_student(student_uid).map_err(|e| {
-        log::error!("v2 API删除学员失败 - UID: {}, 错误: {}", student_uid, e);
-        format!("删除学员失败: {}", e)
-    })?;
+    let student = manager
+        .get_student(student_uid)
+        .map_err(|e| format!("获取学生失败: {}", e))?
+        .ok_or("学员不存在")?;
 
-    if deleted {
-        log::info!("v2 API成功删除学员 - UID: {}", student_uid);
-        Ok(())
-    } else {
-        log::warn!("v2 API尝试删除不存在的学员 - UID: {}", student_uid);
-        Err("学员不存在".to_string())
+    let end_date = student
+        .membership_end_date()
+        .ok_or("该学员没有会员，无法冻结")?;
+    if start > end_date {
+        return Err("冻结开始日期不能晚于会员结束日期".to_string());
     }
+
+    let freeze = write_store(|ext| {
+        let freezes = ext.membership_freezes.entry(student_uid).or_default();
+        if active_freeze(freezes).is_some() {
+            return Err("该学员的会员已处于冻结状态".to_string());
+        }
+        if freezes
+            .iter()
+            .any(|f| f.end_date.is_some_and(|end| start < end))
+        {
+            return Err("冻结开始日期不能早于之前冻结的结束日期".to_string());
+        }
+        let freeze = MembershipFreeze::new(start, reason.map(|r| r.trim().to_string()));
+        freezes.push(freeze.clone());
+        Ok(freeze)
+    })?;
+
+    log::info!(
+        "成功冻结会员 - UID: {}, 开始: {}",
+        student_uid,
+        start.to_rfc3339()
+    );
+    Ok(convert_freeze_to_response(&freeze))
 }
 
-// v2 API - 财务管理命令（完全重构版）
+// 会员冻结 - 解除冻结并按冻结天数顺延会员结束日期
 #[tauri::command]
-fn add_cash_transaction(
-    student_uid: Option<u64>,
-    amount: i64,
-    note: Option<String>,
-    is_installment: Option<bool>,
-    total_amount: Option<i64>,
-    total_installments: Option<u32>,
-    frequency: Option<String>,
-    due_date: Option<String>,
-    current_installment: Option<u32>,
-    plan_id: Option<u64>,
-) -> Result<TransactionResponse, String> {
+fn unfreeze_membership(
+    student_uid: u64,
+    end_date: Option<String>,
+) -> Result<MembershipFreezeResponse, String> {
     init_manager()?;
 
-    // v2 API - 增强输入验证（完整的后端验证）
-    validate_amount(amount)?;
-    if let Some(total) = total_amount {
-        validate_amount(total)?;
-    }
-    if let Some(note_str) = &note {
-        validate_note(note_str)?;
-    }
-    if let Some(installments) = total_installments {
-        validate_installment_count(installments)?;
-    }
-    if let Some(sid) = student_uid {
-        validate_student_uid(sid)?;
-    }
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+
+    let end = match end_date {
+        Some(end_str) => parse_date_input(&end_str).map_err(|e| format!("冻结结束日期{}", e))?,
+        None => Utc::now(),
+    };
 
     let manager = get_manager()?;
+    let student = manager
+        .get_student(student_uid)
+        .map_err(|e| format!("获取学生失败: {}", e))?
+        .ok_or("学员不存在")?;
+
+    // 先保存冻结结束，再顺延学员的会员结束日期；顺延失败时撤销冻结结束，避免重试时重复顺延
+    let (freeze, period_id) = write_store(|ext| {
+        let freeze = ext
+            .membership_freezes
+            .get_mut(&student_uid)
+            .and_then(|freezes| freezes.iter_mut().find(|f| f.is_active()))
+            .ok_or("该学员没有进行中的会员冻结")?;
+        if end < freeze.start_date {
+            return Err("冻结结束日期不能早于开始日期".to_string());
+        }
 
-    // v2 API - 使用构建器模式创建现金记录
-    let mut builder = CashBuilder::new(amount);
+        let frozen_days = frozen_days_between(&freeze.start_date, &end);
+        freeze.end_date = Some(end);
+        freeze.frozen_days = frozen_days;
+        let freeze = freeze.clone();
+
+        // 同步顺延会员历史中的当前会员期
+        let period_id = ext
+            .membership_history
+            .get_mut(&student_uid)
+            .and_then(|periods| current_period_mut(periods))
+            .map(|period| {
+                period.end_date += Duration::days(frozen_days);
+                period.frozen_days += frozen_days;
+                period.id
+            });
+        Ok((freeze, period_id))
+    })?;
 
-    if let Some(student_id) = student_uid {
-        builder = builder.student_id(student_id);
+    let new_end_date = student
+        .membership_end_date()
+        .map(|d| d + Duration::days(freeze.frozen_days));
+    if let Err(e) = manager.update_student(
+        student_uid,
+        StudentUpdater::new().membership(student.membership_start_date(), new_end_date),
+    ) {
+        log::error!("顺延会员结束日期失败 - UID: {}, 错误: {}", student_uid, e);
+        write_store(|ext| {
+            if let Some(active) = ext
+                .membership_freezes
+                .get_mut(&student_uid)
+                .and_then(|freezes| {
+                    freezes
+                        .iter_mut()
+                        .find(|f| f.start_date == freeze.start_date && f.end_date == Some(end))
+                })
+            {
+                active.end_date = None;
+                active.frozen_days = 0;
+            }
+            if let Some(period) = ext
+                .membership_history
+                .get_mut(&student_uid)
+                .and_then(|periods| periods.iter_mut().find(|p| Some(p.id) == period_id))
+            {
+                period.end_date -= Duration::days(freeze.frozen_days);
+                period.frozen_days -= freeze.frozen_days;
+            }
+            Ok(())
+        })?;
+        return Err(format!("顺延会员结束日期失败: {}", e));
+    }
+
+    log::info!(
+        "成功解除会员冻结 - UID: {}, 冻结天数: {}",
+        student_uid,
+        freeze.frozen_days
+    );
+    Ok(convert_freeze_to_response(&freeze))
+}
+
+// 会员历史 - 获取学生的全部会员期（按开始时间排序）
+#[tauri::command]
+fn get_membership_history(student_uid: u64) -> Result<Vec<MembershipPeriodResponse>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+
+    let now = Utc::now();
+    read_store(|ext| {
+        let mut periods: Vec<&MembershipPeriod> = ext
+            .membership_history
+            .get(&student_uid)
+            .map(|periods| periods.iter().collect())
+            .unwrap_or_default();
+        periods.sort_by_key(|p| (p.start_date, p.created_at));
+        periods
+            .into_iter()
+            .map(|p| convert_period_to_response(p, &now))
+            .collect()
+    })
+}
+
+// v2 API - 删除学生（优化版）
+#[tauri::command]
+fn delete_student(student_uid: u64) -> Result<(), String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+
+    // 钱包还有余额时不能删除学员，需先退款或扣完余额
+    let balance = read_store(|ext| {
+        ext.wallets
+            .get(&student_uid)
+            .map(|entries| wallet_balance(entries))
+            .unwrap_or(0)
+    })?;
+    if balance != 0 {
+        return Err(format!("该学员钱包余额为{}，不能删除", balance));
+    }
+
+    let manager = get_manager()?;
+    let deleted = manager.delete_student(student_uid).map_err(|e| {
+        log::error!("v2 API删除学员失败 - UID: {}, 错误: {}", student_uid, e);
+        format!("删除学员失败: {}", e)
+    })?;
+
+    if deleted {
+        // 同步清理该学员的扩展数据
+        remove_indexed(student_uid);
+        write_store(|ext| {
+            ext.purge_student(student_uid);
+            Ok(())
+        })?;
+        log::info!("v2 API成功删除学员 - UID: {}", student_uid);
+        Ok(())
+    } else {
+        log::warn!("v2 API尝试删除不存在的学员 - UID: {}", student_uid);
+        Err("学员不存在".to_string())
+    }
+}
+
+// v2 API - 财务管理命令（完全重构版）
+#[tauri::command]
+fn add_cash_transaction(
+    student_uid: Option<u64>,
+    amount: i64,
+    note: Option<String>,
+    is_installment: Option<bool>,
+    total_amount: Option<i64>,
+    total_installments: Option<u32>,
+    frequency: Option<String>,
+    due_date: Option<String>,
+    current_installment: Option<u32>,
+    plan_id: Option<u64>,
+    list_price: Option<i64>,
+    discount_rule_id: Option<u64>,
+    coupon_code: Option<String>,
+    business_date: Option<String>,
+    operator: Option<String>,
+    category: Option<String>,
+    payment_method: Option<String>,
+    auto_discount: Option<bool>,
+) -> Result<TransactionResponse, String> {
+    init_manager()?;
+
+    // v2 API - 增强输入验证（完整的后端验证）
+    validate_amount(amount)?;
+    if let Some(total) = total_amount {
+        validate_amount(total)?;
+    }
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+    if let Some(installments) = total_installments {
+        validate_installment_count(installments)?;
+    }
+    if let Some(sid) = student_uid {
+        validate_student_uid(sid)?;
+    }
+    if let Some(price) = list_price {
+        validate_amount(price)?;
+    }
+    if let Some(code) = &coupon_code {
+        validate_coupon_code(code)?;
+    }
+    if let Some(operator_str) = &operator {
+        validate_operator(operator_str)?;
+    }
+    let business_date = business_date
+        .as_deref()
+        .map(parse_business_date)
+        .transpose()?;
+    if let Some(category_str) = &category {
+        validate_cash_category(category_str)?;
+    }
+    if let Some(method) = &payment_method {
+        validate_payment_method(method)?;
+    }
+
+    let manager = get_manager()?;
+
+    // auto_discount=true 且未指定任何折扣时，amount 视为标价，按学员情况自动适用推荐、家庭和提前续费折扣；
+    // 默认不自动折扣，amount 即为实收金额
+    let discount_rule_id = match (discount_rule_id, &coupon_code, list_price, student_uid) {
+        (None, None, None, Some(sid))
+            if auto_discount.unwrap_or(false) && amount > 0 && !is_installment.unwrap_or(false) =>
+        {
+            let renewing = category.as_deref() == Some("membership")
+                && manager
+                    .get_student(sid)
+                    .map_err(|e| format!("获取学生失败: {}", e))?
+                    .and_then(|student| student.membership_end_date())
+                    .is_some_and(|end| end >= Utc::now());
+            read_store(|ext| auto_discount_rule(ext, sid, amount, renewing))?
+        }
+        _ => discount_rule_id,
+    };
+
+    // 计算折扣：使用规则或优惠券时 amount 视为标价，实收金额由规则计算
+    let pricing = resolve_cash_pricing(amount, list_price, discount_rule_id, coupon_code)?;
+    let amount = pricing
+        .as_ref()
+        .map(|p| p.list_price - p.discount_amount)
+        .unwrap_or(amount);
+
+    // v2 API - 使用构建器模式创建现金记录
+    let mut builder = CashBuilder::new(amount);
+
+    if let Some(student_id) = student_uid {
+        builder = builder.student_id(student_id);
     }
     if let Some(note_str) = note.clone() {
         builder = builder.note(note_str);
@@ -717,29 +2424,12 @@ fn add_cash_transaction(
         let current_installment = current_installment.unwrap_or(1);
         let due_date_str = due_date.ok_or("分期付款需要指定到期日期")?;
 
-        // 解析日期字符串
-        let due_date = DateTime::parse_from_rfc3339(&due_date_str)
-            .map_err(|e| format!("日期格式错误: {}", e))?
-            .with_timezone(&Utc);
+        // 解析日期字符串（支持RFC3339或俱乐部本地日期）
+        let due_date = parse_date_input(&due_date_str)?;
 
         // 解析付款频率
         let frequency_enum = match frequency.as_deref() {
-            Some(freq) => {
-                validate_frequency(freq)?;
-                match freq {
-                    "Weekly" => PaymentFrequency::Weekly,
-                    "Monthly" => PaymentFrequency::Monthly,
-                    "Quarterly" => PaymentFrequency::Quarterly,
-                    custom if custom.starts_with("Custom") => {
-                        let days = custom
-                            .trim_start_matches("Custom")
-                            .parse()
-                            .map_err(|_| "自定义频率格式错误，应为Custom<天数>")?;
-                        PaymentFrequency::Custom(days)
-                    }
-                    _ => PaymentFrequency::Monthly, // 默认月度
-                }
-            }
+            Some(freq) => parse_payment_frequency(freq)?,
             None => PaymentFrequency::Monthly, // 默认月度
         };
 
@@ -760,44 +2450,39 @@ fn add_cash_transaction(
         .record_cash(builder)
         .map_err(|e| format!("保存交易记录失败: {}", e))?;
 
+    write_store(|ext| {
+        let meta = ext.record_cash_meta(
+            cash_id,
+            business_date,
+            operator.map(|o| o.trim().to_string()),
+        );
+        meta.category = category;
+        meta.payment_method = payment_method;
+        if let Some(pricing) = pricing {
+            // 优惠券在交易成功入账后才计入使用次数
+            if let Some(code) = &pricing.coupon_code {
+                if let Some(coupon) = ext.coupon_by_code_mut(code) {
+                    coupon.used_count += 1;
+                }
+            }
+            // 推荐折扣只适用一次
+            if pricing.category == Some(DiscountCategory::Referral) {
+                if let Some(referral) = student_uid.and_then(|sid| ext.referrals.get_mut(&sid)) {
+                    referral.discount_cash_uid.get_or_insert(cash_id);
+                }
+            }
+            ext.cash_pricing.insert(cash_id, pricing);
+        }
+        Ok(())
+    })?;
+
     // 获取创建的现金记录用于响应
     let cash = manager
         .get_cash(cash_id)
         .map_err(|e| format!("获取现金记录失败: {}", e))?
         .ok_or("现金记录创建后未找到")?;
 
-    // 构建响应
-    let (is_installment, plan_id, current, total, due_date_str, status_str) =
-        if let Some(installment) = &cash.installment {
-            (
-                true,
-                Some(installment.plan_id),
-                Some(installment.current_installment),
-                Some(installment.total_installments),
-                Some(installment.due_date.to_rfc3339()),
-                Some(format!("{:?}", installment.status)),
-            )
-        } else {
-            (false, None, None, None, None, None)
-        };
-
-    Ok(TransactionResponse {
-        uid: cash.uid,
-        student_id: cash.student_id,
-        amount: cash.cash,
-        note: cash.note.clone(),
-        description: if is_installment {
-            format!("分期付款 {}/{}", current.unwrap_or(0), total.unwrap_or(0))
-        } else {
-            "普通付款".to_string()
-        },
-        is_installment,
-        installment_plan_id: plan_id,
-        installment_current: current,
-        installment_total: total,
-        installment_due_date: due_date_str,
-        installment_status: status_str,
-    })
+    read_store(|ext| convert_cash_to_response(&cash, ext))
 }
 
 // v2 API - 获取所有交易记录（优化版）
@@ -814,15 +2499,272 @@ fn get_all_transactions() -> Result<Vec<TransactionResponse>, String> {
         })?;
 
     // v2 API - 使用迭代器和辅助函数进行高效转换
-    let transactions: Vec<TransactionResponse> = cash_list
-        .into_iter()
-        .map(|cash| convert_cash_to_response(&cash))
-        .collect();
+    let transactions: Vec<TransactionResponse> = read_store(|ext| {
+        cash_list
+            .iter()
+            .map(|cash| convert_cash_to_response(cash, ext))
+            .collect()
+    })?;
 
     log::info!("v2 API成功获取{}条交易记录", transactions.len());
     Ok(transactions)
 }
 
+// 现金记录 - 修改交易（金额、备注、学员、业务日期、类别、付款方式），并写入审计记录
+//
+// note / category / payment_method 传空字符串表示清除；clear_student 为 true 时解除关联的学员
+#[tauri::command]
+fn update_cash_transaction(
+    transaction_uid: u64,
+    amount: Option<i64>,
+    note: Option<String>,
+    student_uid: Option<u64>,
+    clear_student: Option<bool>,
+    business_date: Option<String>,
+    category: Option<String>,
+    payment_method: Option<String>,
+    operator: Option<String>,
+    reason: Option<String>,
+) -> Result<TransactionResponse, String> {
+    init_manager()?;
+
+    // 输入验证（与新增交易一致的后端验证）
+    validate_transaction_uid(transaction_uid)?;
+    if let Some(amount_val) = amount {
+        validate_amount(amount_val)?;
+    }
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+    if let Some(sid) = student_uid {
+        validate_student_uid(sid)?;
+    }
+    let student_uid = match (student_uid, clear_student.unwrap_or(false)) {
+        (Some(_), true) => return Err("不能同时修改和解除关联学员".to_string()),
+        (_, true) => Some(None),
+        (sid, false) => sid.map(Some),
+    };
+    let business_date = business_date
+        .as_deref()
+        .map(parse_business_date)
+        .transpose()?;
+    let category = category.map(|c| c.trim().to_string());
+    if let Some(category_str) = category.as_deref().filter(|c| !c.is_empty()) {
+        validate_cash_category(category_str)?;
+    }
+    let payment_method = payment_method.map(|m| m.trim().to_string());
+    if let Some(method) = payment_method.as_deref().filter(|m| !m.is_empty()) {
+        validate_payment_method(method)?;
+    }
+    if let Some(operator_str) = &operator {
+        validate_operator(operator_str)?;
+    }
+    if let Some(reason_str) = &reason {
+        validate_note(reason_str)?;
+    }
+
+    let manager = get_manager()?;
+    let cash = manager
+        .get_cash(transaction_uid)
+        .map_err(|e| format!("获取交易记录失败: {}", e))?
+        .ok_or("交易记录不存在")?;
+    if let Some(Some(sid)) = student_uid {
+        manager
+            .get_student(sid)
+            .map_err(|e| format!("获取学生失败: {}", e))?
+            .ok_or("学员不存在")?;
+    }
+
+    let (linked, meta, list_price) = read_store(|ext| {
+        (
+            ext.is_cash_linked(transaction_uid),
+            ext.cash_meta.get(&transaction_uid).cloned(),
+            ext.cash_pricing
+                .get(&transaction_uid)
+                .map(|pricing| pricing.list_price),
+        )
+    })?;
+
+    // 整理修改前后的值，只处理实际发生变化的字段
+    let new_note = note
+        .map(|n| n.trim().to_string())
+        .map(|n| Some(n).filter(|n| !n.is_empty()));
+    let new_category = category.map(|c| Some(c).filter(|c| !c.is_empty()));
+    let new_payment_method = payment_method.map(|m| Some(m).filter(|m| !m.is_empty()));
+    let amount = amount.filter(|a| *a != cash.cash);
+    let new_note = new_note.filter(|n| *n != cash.note);
+    let student_uid = student_uid.filter(|s| *s != cash.student_id);
+    let business_date =
+        business_date.filter(|d| meta.as_ref().map(|m| m.business_date) != Some(*d));
+    let new_category =
+        new_category.filter(|c| *c != meta.as_ref().and_then(|m| m.category.clone()));
+    let new_payment_method = new_payment_method
+        .filter(|m| *m != meta.as_ref().and_then(|meta| meta.payment_method.clone()));
+
+    if linked && (amount.is_some() || student_uid.is_some()) {
+        return Err("该记录关联了钱包流水、家庭付款或滞纳金，不能修改金额和学员".to_string());
+    }
+    if let Some(new_amount) = amount {
+        // 分期金额由分期计划的总金额决定，单独修改会使计划余额对不上
+        if cash.installment.is_some() {
+            return Err("分期记录不能直接修改金额，请使用调整分期计划".to_string());
+        }
+        // 有折扣的交易与新增时一致，实收金额必须在0与标价之间
+        if list_price.is_some_and(|price| new_amount < 0 || new_amount > price) {
+            return Err("实收金额必须在0与标价之间".to_string());
+        }
+    }
+    if meta.is_none()
+        && business_date.is_none()
+        && (new_category.is_some() || new_payment_method.is_some())
+    {
+        return Err("该记录录入时间较早，没有业务日期，请同时指定业务日期".to_string());
+    }
+
+    let mut changes = Vec::new();
+    let mut record_change = |field: &str, old_value: Option<String>, new_value: Option<String>| {
+        changes.push(FieldChange {
+            field: field.to_string(),
+            old_value,
+            new_value,
+        });
+    };
+    if let Some(new_amount) = amount {
+        record_change(
+            "amount",
+            Some(cash.cash.to_string()),
+            Some(new_amount.to_string()),
+        );
+    }
+    if let Some(new_note) = &new_note {
+        record_change("note", cash.note.clone(), new_note.clone());
+    }
+    if let Some(new_student) = student_uid {
+        record_change(
+            "student_id",
+            cash.student_id.map(|s| s.to_string()),
+            new_student.map(|s| s.to_string()),
+        );
+    }
+    if let Some(date) = business_date {
+        record_change(
+            "business_date",
+            meta.as_ref()
+                .map(|m| m.business_date.format("%Y-%m-%d").to_string()),
+            Some(date.format("%Y-%m-%d").to_string()),
+        );
+    }
+    if let Some(new_category) = &new_category {
+        record_change(
+            "category",
+            meta.as_ref().and_then(|m| m.category.clone()),
+            new_category.clone(),
+        );
+    }
+    if let Some(new_method) = &new_payment_method {
+        record_change(
+            "payment_method",
+            meta.as_ref().and_then(|m| m.payment_method.clone()),
+            new_method.clone(),
+        );
+    }
+    if changes.is_empty() {
+        return Err("没有需要修改的内容".to_string());
+    }
+
+    if amount.is_some() || new_note.is_some() || student_uid.is_some() {
+        let mut updater = CashUpdater::new();
+        if let Some(new_amount) = amount {
+            updater = updater.cash(new_amount);
+        }
+        if let Some(new_note) = new_note {
+            updater = updater.note(new_note);
+        }
+        if let Some(new_student) = student_uid {
+            updater = updater.student_id(new_student);
+        }
+        manager.update_cash(transaction_uid, updater).map_err(|e| {
+            log::error!("修改交易记录失败 - UID: {}, 错误: {}", transaction_uid, e);
+            format!("修改交易记录失败: {}", e)
+        })?;
+    }
+
+    let operator = operator.map(|o| o.trim().to_string());
+    write_store(|ext| {
+        if let Some(date) = business_date {
+            ext.cash_meta
+                .entry(transaction_uid)
+                .or_insert_with(|| CashMeta::for_legacy(date, Utc::now()))
+                .business_date = date;
+        }
+        if let Some(meta) = ext.cash_meta.get_mut(&transaction_uid) {
+            if let Some(new_category) = new_category {
+                meta.category = new_category;
+            }
+            if let Some(new_method) = new_payment_method {
+                meta.payment_method = new_method;
+            }
+        }
+        // 修改实收金额时保持标价不变，折扣金额随之调整
+        if let Some(new_amount) = amount {
+            if let Some(pricing) = ext.cash_pricing.get_mut(&transaction_uid) {
+                pricing.discount_amount = pricing.list_price - new_amount;
+            }
+        }
+        ext.touch_cash(transaction_uid);
+        let entry = CashAuditEntry {
+            id: ext.alloc_id(),
+            cash_uid: transaction_uid,
+            changes,
+            operator,
+            reason: reason
+                .map(|r| r.trim().to_string())
+                .filter(|r| !r.is_empty()),
+            created_at: Utc::now(),
+        };
+        ext.cash_audit.push(entry);
+        Ok(())
+    })?;
+
+    log::info!("成功修改交易记录 - UID: {}", transaction_uid);
+
+    let cash = manager
+        .get_cash(transaction_uid)
+        .map_err(|e| format!("获取现金记录失败: {}", e))?
+        .ok_or("交易记录不存在")?;
+    read_store(|ext| convert_cash_to_response(&cash, ext))
+}
+
+// 现金记录 - 获取修改审计记录（最近的在前，可按交易筛选）
+#[tauri::command]
+fn get_cash_audit_log(transaction_uid: Option<u64>) -> Result<Vec<CashAuditEntryResponse>, String> {
+    init_manager()?;
+
+    read_store(|ext| {
+        ext.cash_audit
+            .iter()
+            .rev()
+            .filter(|entry| transaction_uid.is_none_or(|uid| entry.cash_uid == uid))
+            .map(|entry| CashAuditEntryResponse {
+                id: entry.id,
+                cash_uid: entry.cash_uid,
+                changes: entry
+                    .changes
+                    .iter()
+                    .map(|c| FieldChangeResponse {
+                        field: c.field.clone(),
+                        old_value: c.old_value.clone(),
+                        new_value: c.new_value.clone(),
+                    })
+                    .collect(),
+                operator: entry.operator.clone(),
+                reason: entry.reason.clone(),
+                created_at: entry.created_at.to_rfc3339(),
+            })
+            .collect()
+    })
+}
+
 // v2 API - 删除现金交易（优化版）
 #[tauri::command]
 fn delete_cash_transaction(transaction_uid: u64) -> Result<(), String> {
@@ -832,6 +2774,11 @@ fn delete_cash_transaction(transaction_uid: u64) -> Result<(), String> {
     validate_transaction_uid(transaction_uid)?;
 
     let manager = get_manager()?;
+    // 钱包流水、家庭付款和滞纳金引用的现金记录不能单独删除
+    if read_store(|ext| ext.is_cash_linked(transaction_uid))? {
+        return Err("该记录关联了钱包流水、家庭付款或滞纳金，不能删除".to_string());
+    }
+
     let deleted = manager.delete_cash(transaction_uid).map_err(|e| {
         log::error!(
             "v2 API删除交易记录失败 - UID: {}, 错误: {}",
@@ -842,6 +2789,10 @@ fn delete_cash_transaction(transaction_uid: u64) -> Result<(), String> {
     })?;
 
     if deleted {
+        write_store(|ext| {
+            ext.purge_cash(transaction_uid);
+            Ok(())
+        })?;
         log::info!("v2 API成功删除交易记录 - UID: {}", transaction_uid);
         Ok(())
     } else {
@@ -888,6 +2839,7 @@ fn update_installment_status(transaction_uid: u64, status: String) -> Result<(),
     // v2 API - 状态枚举转换和验证
     validate_transaction_uid(transaction_uid)?;
     let status_enum = parse_installment_status(&status)?;
+    let became_overdue = status_enum == InstallmentStatus::Overdue;
 
     let manager = get_manager()?;
 
@@ -924,6 +2876,19 @@ fn update_installment_status(transaction_uid: u64, status: String) -> Result<(),
                 format!("更新分期状态失败: {}", e)
             })?;
 
+        write_store(|ext| {
+            ext.touch_cash(transaction_uid);
+            Ok(())
+        })?;
+
+        // 标记为逾期时按滞纳金规则生成滞纳金
+        if became_overdue {
+            let updated = manager
+                .get_cash(transaction_uid)
+                .map_err(|e| format!("获取交易记录失败: {}", e))?;
+            generate_late_fees(updated.as_slice(), club_today())?;
+        }
+
         log::info!(
             "v2 API成功更新分期状态 - UID: {}, 新状态: {}",
             transaction_uid,
@@ -945,10 +2910,8 @@ fn generate_next_installment(plan_id: u64, due_date: String) -> Result<u64, Stri
 
     let manager = get_manager()?;
 
-    // 解析日期字符串
-    let due_date = DateTime::parse_from_rfc3339(&due_date)
-        .map_err(|e| format!("日期格式错误: {}", e))?
-        .with_timezone(&Utc);
+    // 解析日期字符串（支持RFC3339或俱乐部本地日期）
+    let due_date = parse_date_input(&due_date)?;
 
     // 查找该计划的所有分期付款
     let installments = manager
@@ -1013,6 +2976,11 @@ fn generate_next_installment(plan_id: u64, due_date: String) -> Result<u64, Stri
         )
         .map_err(|e| format!("生成下一期分期失败: {}", e))?;
 
+    write_store(|ext| {
+        ext.record_cash_meta(cash_id, None, None);
+        Ok(())
+    })?;
+
     Ok(cash_id)
 }
 
@@ -1030,7 +2998,7 @@ fn cancel_installment_plan(plan_id: u64) -> Result<usize, String> {
         .search_cash(CashQuery::new().has_installment(true))
         .map_err(|e| format!("查询分期付款失败: {}", e))?;
 
-    let mut cancelled_count = 0;
+    let mut cancelled_uids = Vec::new();
 
     for cash in installments {
         if let Some(mut installment) = cash.installment {
@@ -1043,15 +3011,21 @@ fn cancel_installment_plan(plan_id: u64) -> Result<usize, String> {
                     .update_cash(cash.uid, CashUpdater::new().installment(Some(installment)))
                     .map_err(|e| format!("取消分期付款失败: {}", e))?;
 
-                cancelled_count += 1;
+                cancelled_uids.push(cash.uid);
             }
         }
     }
 
-    if cancelled_count == 0 {
+    if cancelled_uids.is_empty() {
         Err("未找到可取消的分期计划".to_string())
     } else {
-        Ok(cancelled_count)
+        write_store(|ext| {
+            for uid in &cancelled_uids {
+                ext.touch_cash(*uid);
+            }
+            Ok(())
+        })?;
+        Ok(cancelled_uids.len())
     }
 }
 
@@ -1069,29 +3043,17 @@ fn get_installments_by_plan(plan_id: u64) -> Result<Vec<TransactionResponse>, St
         .search_cash(CashQuery::new().has_installment(true))
         .map_err(|e| format!("查询分期付款失败: {}", e))?;
 
-    let mut transactions = Vec::new();
-    for cash in all_installments {
-        if let Some(installment) = &cash.installment {
-            if installment.plan_id == plan_id {
-                transactions.push(TransactionResponse {
-                    uid: cash.uid,
-                    student_id: cash.student_id,
-                    amount: cash.cash,
-                    note: cash.note.clone(),
-                    description: format!(
-                        "分期付款 {}/{}",
-                        installment.current_installment, installment.total_installments
-                    ),
-                    is_installment: true,
-                    installment_plan_id: Some(installment.plan_id),
-                    installment_current: Some(installment.current_installment),
-                    installment_total: Some(installment.total_installments),
-                    installment_due_date: Some(installment.due_date.to_rfc3339()),
-                    installment_status: Some(format!("{:?}", installment.status)),
-                });
-            }
-        }
-    }
+    let transactions: Vec<TransactionResponse> = read_store(|ext| {
+        all_installments
+            .iter()
+            .filter(|cash| {
+                cash.installment
+                    .as_ref()
+                    .is_some_and(|installment| installment.plan_id == plan_id)
+            })
+            .map(|cash| convert_cash_to_response(cash, ext))
+            .collect()
+    })?;
 
     Ok(transactions)
 }
@@ -1116,65 +3078,291 @@ fn get_student_stats(student_uid: u64) -> Result<StudentStatsResponse, String> {
 }
 
 // v2 API功能 - 财务统计
+//
+// basis 可选 Cash（收付实现制，默认）或 Accrual（权责发生制：会员费和体验课包收入按确认进度计入）
 #[tauri::command]
-fn get_financial_stats(period: String) -> Result<FinancialStatsResponse, String> {
+fn get_financial_stats(
+    period: String,
+    basis: Option<String>,
+) -> Result<FinancialStatsResponse, String> {
     init_manager()?;
 
-    let time_period = match period.as_str() {
-        "Today" => TimePeriod::Today,
-        "ThisWeek" => TimePeriod::ThisWeek,
-        "ThisMonth" => TimePeriod::ThisMonth,
-        "ThisYear" => TimePeriod::ThisYear,
-        _ => return Err("无效的时间段".to_string()),
-    };
+    let basis = basis.unwrap_or_else(|| "Cash".to_string());
+    if !matches!(basis.as_str(), "Cash" | "Accrual") {
+        return Err(format!("无效的统计口径: {}", basis));
+    }
 
+    // 按俱乐部本地时间确定统计区间，避免晚间交易被计入次日
+    let (start_day, end_day) = period_local_range(&period)?;
+
+    // 按业务日期统计，与财务报表和收支趋势口径一致
     let manager = get_manager()?;
-    let stats = manager
-        .get_financial_stats(time_period)
-        .map_err(|e| format!("获取财务统计失败: {}", e))?;
+    let cash_list = cash_in_local_range(start_day, end_day)?;
+
+    let mut total_income = 0;
+    let mut total_expense = 0;
+    let mut installment_total = 0;
+    let mut installment_paid = 0;
+    let mut installment_pending = 0;
+    for cash in &cash_list {
+        // 未付和已取消的分期不计入收支，与财务报表口径一致
+        if is_cash_settled(cash) {
+            if cash.cash > 0 {
+                total_income += cash.cash;
+            } else {
+                total_expense += cash.cash.abs();
+            }
+        }
+        if let Some(installment) = &cash.installment {
+            match installment.status {
+                InstallmentStatus::Paid => installment_paid += 1,
+                InstallmentStatus::Pending | InstallmentStatus::Overdue => installment_pending += 1,
+                InstallmentStatus::Cancelled => continue,
+            }
+            installment_total += 1;
+        }
+    }
+
+    // 权责发生制：去掉本期收到的待确认收款，加上本期确认的部分
+    let mut deferred_balance = None;
+    if basis == "Accrual" {
+        let all_cash = manager
+            .search_cash(CashQuery::new())
+            .map_err(|e| format!("获取现金记录失败: {}", e))?;
+        // 收款日期与本期收支使用同一口径（业务日期，旧记录按录入时间落在本期的取本期第一天），
+        // 补录的收款在扣除和确认两侧都按业务日期计算
+        let dates = cash_local_dates(&all_cash, &[(start_day, end_day)])?;
+        let payments = collect_deferred_payments(&all_cash, &dates)?;
+        let received_uids: HashSet<u64> = cash_list.iter().map(|cash| cash.uid).collect();
+        let deferred_received: i64 = payments
+            .iter()
+            .filter(|p| received_uids.contains(&p.cash_uid))
+            .map(|p| p.amount)
+            .sum();
+        let recognized: i64 = payments
+            .iter()
+            .map(|p| p.recognized_between(start_day, end_day))
+            .sum();
+        total_income = total_income - deferred_received + recognized;
+        deferred_balance = Some(
+            payments
+                .iter()
+                .filter(|p| p.received_on <= end_day)
+                .map(|p| p.amount - p.recognized_through(end_day))
+                .sum(),
+        );
+    }
 
     Ok(FinancialStatsResponse {
-        total_income: stats.total_income,
-        total_expense: stats.total_expense,
-        net_income: stats.net_income,
-        installment_total: stats.transaction_count as i64,
-        installment_paid: stats.installment_count as i64,
-        installment_pending: 0, // 计算pending状态的分期付款数量
+        total_income,
+        total_expense,
+        net_income: total_income - total_expense,
+        basis,
+        deferred_balance,
+        installment_total,
+        installment_paid,
+        installment_pending,
+        period_start_date: start_day.format("%Y-%m-%d").to_string(),
+        period_end_date: end_day.format("%Y-%m-%d").to_string(),
+        timezone: club_timezone().name().to_string(),
     })
 }
 
-// v2 API功能 - 搜索学生
+// 俱乐部设置 - 获取当前设置
 #[tauri::command]
-fn search_students(
-    name_contains: Option<String>,
-    min_age: Option<u8>,
+fn get_club_settings() -> Result<ClubSettingsResponse, String> {
+    init_manager()?;
+
+    let (club_name, logo_path) = read_store(|ext| {
+        (
+            ext.settings.club_name.clone(),
+            ext.settings.logo_path.clone(),
+        )
+    })?;
+    Ok(ClubSettingsResponse {
+        timezone: club_timezone().name().to_string(),
+        today: club_today().format("%Y-%m-%d").to_string(),
+        club_name: club_name.unwrap_or_else(|| DEFAULT_CLUB_NAME.to_string()),
+        logo_path,
+    })
+}
+
+// 俱乐部设置 - 修改俱乐部时区
+#[tauri::command]
+fn set_club_timezone_setting(timezone: String) -> Result<ClubSettingsResponse, String> {
+    init_manager()?;
+
+    let tz = parse_timezone(&timezone)?;
+    write_store(|ext| {
+        ext.settings.timezone = tz.name().to_string();
+        Ok(())
+    })?;
+    set_club_timezone(tz);
+
+    log::info!("成功修改俱乐部时区: {}", tz.name());
+    get_club_settings()
+}
+
+// 俱乐部设置 - 修改收据抬头的俱乐部名称和 Logo（JPEG）
+//
+// 传空字符串表示清除（名称恢复默认、不显示 Logo）
+#[tauri::command]
+fn update_club_profile(
+    club_name: Option<String>,
+    logo_path: Option<String>,
+) -> Result<ClubSettingsResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    let club_name = club_name.map(|n| n.trim().to_string());
+    if let Some(name) = club_name.as_deref().filter(|n| !n.is_empty()) {
+        validate_club_name(name)?;
+    }
+    let logo_path = logo_path.map(|p| p.trim().to_string());
+    if let Some(path) = logo_path.as_deref().filter(|p| !p.is_empty()) {
+        let data = std::fs::read(path).map_err(|e| format!("读取 Logo 文件失败: {}", e))?;
+        Logo::from_jpeg(data)?;
+    }
+
+    write_store(|ext| {
+        if let Some(name) = club_name {
+            ext.settings.club_name = (!name.is_empty()).then_some(name);
+        }
+        if let Some(path) = logo_path {
+            ext.settings.logo_path = (!path.is_empty()).then_some(path);
+        }
+        Ok(())
+    })?;
+
+    log::info!("成功修改俱乐部抬头信息");
+    get_club_settings()
+}
+
+// v2 API功能 - 搜索学生
+#[tauri::command]
+fn search_students(
+    name_contains: Option<String>,
+    min_age: Option<u8>,
     max_age: Option<u8>,
     min_score: Option<f64>,
     max_score: Option<f64>,
     class_type: Option<String>,
     subject: Option<String>,
     has_membership: Option<bool>,
+    phone_contains: Option<String>,
+    custom_field_filters: Option<Vec<CustomFieldFilter>>,
+    tags: Option<Vec<String>>,
 ) -> Result<Vec<StudentResponse>, String> {
     init_manager()?;
 
+    let criteria = StudentSearchCriteria {
+        name_contains,
+        min_age,
+        max_age,
+        min_score,
+        max_score,
+        class_type,
+        subject,
+        has_membership,
+        phone_contains,
+        custom_field_filters,
+        tags,
+    };
+    let students = run_student_search(&criteria)?;
+
+    read_store(|ext| {
+        students
+            .iter()
+            .map(|student| convert_student_to_response(student, ext))
+            .collect()
+    })
+}
+
+// 快速搜索 - 前台统一搜索框（姓名、拼音、电话、标签、自定义字段、备注），按匹配程度排序
+#[tauri::command]
+fn quick_search_students(
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<StudentSearchHitResponse>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    if query.chars().count() > 100 {
+        return Err("搜索内容不能超过100个字符".to_string());
+    }
+    let limit = limit.unwrap_or(20).clamp(1, 200);
+    let Some(term) = SearchTerm::new(&query) else {
+        return Ok(Vec::new());
+    };
+
+    let hits = search::search(&term, limit, || {
+        let students = get_manager()?
+            .list_students()
+            .map_err(|e| format!("获取学生列表失败: {}", e))?;
+        read_store(|ext| {
+            students
+                .iter()
+                .map(|student| index_student(student, ext))
+                .collect()
+        })
+    })?;
+
+    let manager = get_manager()?;
+    let mut results = Vec::new();
+    for (uid, score, matched_field) in hits {
+        let Some(student) = manager
+            .get_student(uid)
+            .map_err(|e| format!("获取学生信息失败: {}", e))?
+        else {
+            continue;
+        };
+        results.push(read_store(|ext| StudentSearchHitResponse {
+            score,
+            matched_field: matched_field.to_string(),
+            student: convert_student_to_response(&student, ext),
+        })?);
+    }
+    Ok(results)
+}
+
+// 学员搜索 - 按筛选条件查找学员（搜索学生和学员分组共用）
+fn run_student_search(
+    criteria: &StudentSearchCriteria,
+) -> Result<Vec<qmx_backend_lib::student::Student>, String> {
+    // 自定义字段筛选条件转换为字段定义
+    let custom_field_filters: Vec<(CustomFieldDef, CustomFieldFilter)> = read_store(|ext| {
+        criteria
+            .custom_field_filters
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|filter| {
+                ext.custom_field_by_key(filter.key.trim())
+                    .cloned()
+                    .map(|def| (def, filter.clone()))
+                    .ok_or_else(|| format!("自定义字段不存在: {}", filter.key))
+            })
+            .collect::<Result<_, String>>()
+    })??;
+
     let manager = get_manager()?;
     let mut query = StudentQuery::new();
 
-    if let Some(name) = name_contains {
-        query = query.name_contains(name);
+    if let Some(name) = &criteria.name_contains {
+        query = query.name_contains(name.clone());
     }
-    if let (Some(min), Some(max)) = (min_age, max_age) {
+    if let (Some(min), Some(max)) = (criteria.min_age, criteria.max_age) {
         // 验证年龄范围
         validate_age_range(min, max)?;
         query = query.age_range(min, max);
     }
-    if let (Some(min), Some(max)) = (min_score, max_score) {
+    if let (Some(min), Some(max)) = (criteria.min_score, criteria.max_score) {
         // 验证成绩范围
         validate_score_range(min, max)?;
         query = query.score_range(min, max);
     }
-    if let Some(class_str) = class_type {
-        validate_class_type(&class_str)?;
+    if let Some(class_str) = &criteria.class_type {
+        validate_class_type(class_str)?;
         let class = match class_str.as_str() {
             "TenTry" => Class::TenTry,
             "Month" => Class::Month,
@@ -1183,8 +3371,8 @@ fn search_students(
         };
         query = query.class(class);
     }
-    if let Some(subject_str) = subject {
-        validate_subject_type(&subject_str)?;
+    if let Some(subject_str) = &criteria.subject {
+        validate_subject_type(subject_str)?;
         let subject_enum = match subject_str.as_str() {
             "Shooting" => Subject::Shooting,
             "Archery" => Subject::Archery,
@@ -1192,7 +3380,7 @@ fn search_students(
         };
         query = query.subject(subject_enum);
     }
-    if let Some(has_mem) = has_membership {
+    if let Some(has_mem) = criteria.has_membership {
         query = query.has_membership(has_mem);
     }
 
@@ -1200,25 +3388,43 @@ fn search_students(
         .search_students(query)
         .map_err(|e| format!("搜索学生失败: {}", e))?;
 
-    let mut student_responses = Vec::new();
-    for student in students {
-        student_responses.push(StudentResponse {
-            uid: student.uid(),
-            name: student.name().to_string(),
-            age: student.age(),
-            class: format!("{:?}", student.class()),
-            phone: student.phone().to_string(),
-            note: student.note().to_string(),
-            subject: format!("{:?}", student.subject()),
-            lesson_left: student.lesson_left(),
-            membership_start_date: student.membership_start_date().map(|d| d.to_rfc3339()),
-            membership_end_date: student.membership_end_date().map(|d| d.to_rfc3339()),
-            is_membership_active: student.is_membership_active(),
-            membership_days_remaining: student.membership_days_remaining(),
-        });
-    }
-
-    Ok(student_responses)
+    // 电话号码片段同时匹配学员本人和监护人的电话
+    let phone_fragment = criteria
+        .phone_contains
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty());
+    let required_tags = normalize_tags(criteria.tags.clone().unwrap_or_default());
+
+    read_store(|ext| {
+        students
+            .into_iter()
+            .filter(|student| match phone_fragment {
+                Some(fragment) => {
+                    student.phone().contains(fragment)
+                        || ext
+                            .student_guardians
+                            .get(&student.uid())
+                            .is_some_and(|links| {
+                                links.iter().any(|link| {
+                                    ext.guardians
+                                        .get(&link.guardian_id)
+                                        .is_some_and(|g| g.phone_matches(fragment))
+                                })
+                            })
+                }
+                None => true,
+            })
+            .filter(|student| {
+                let values = ext.student_custom_fields.get(&student.uid());
+                custom_field_filters.iter().all(|(def, filter)| {
+                    let value = values.and_then(|v| v.get(&def.id)).map(|v| v.as_str());
+                    def.matches(value, filter)
+                })
+            })
+            .filter(|student| has_all_tags(ext.student_tags.get(&student.uid()), &required_tags))
+            .collect()
+    })
 }
 
 // v2 API功能 - 获取学生现金记录
@@ -1231,40 +3437,12 @@ fn get_student_cash(student_uid: u64) -> Result<Vec<TransactionResponse>, String
         .get_student_cash(student_uid)
         .map_err(|e| format!("获取学生现金记录失败: {}", e))?;
 
-    let mut transactions = Vec::new();
-    for cash in cash_list {
-        let (is_installment, plan_id, current, total, due_date_str, status_str) =
-            if let Some(installment) = &cash.installment {
-                (
-                    true,
-                    Some(installment.plan_id),
-                    Some(installment.current_installment),
-                    Some(installment.total_installments),
-                    Some(installment.due_date.to_rfc3339()),
-                    Some(format!("{:?}", installment.status)),
-                )
-            } else {
-                (false, None, None, None, None, None)
-            };
-
-        transactions.push(TransactionResponse {
-            uid: cash.uid,
-            student_id: cash.student_id,
-            amount: cash.cash,
-            note: cash.note.clone(),
-            description: if is_installment {
-                format!("分期付款 {}/{}", current.unwrap_or(0), total.unwrap_or(0))
-            } else {
-                "普通付款".to_string()
-            },
-            is_installment,
-            installment_plan_id: plan_id,
-            installment_current: current,
-            installment_total: total,
-            installment_due_date: due_date_str,
-            installment_status: status_str,
-        });
-    }
+    let transactions: Vec<TransactionResponse> = read_store(|ext| {
+        cash_list
+            .iter()
+            .map(|cash| convert_cash_to_response(cash, ext))
+            .collect()
+    })?;
 
     Ok(transactions)
 }
@@ -1281,91 +3459,246 @@ fn search_cash(
 ) -> Result<Vec<TransactionResponse>, String> {
     init_manager()?;
 
+    let filter = CashSearchFilter {
+        student_id,
+        min_amount,
+        max_amount,
+        has_installment,
+        date_from,
+        date_to,
+    };
+    let cash_list = run_cash_search(&filter)?;
+
+    let transactions: Vec<TransactionResponse> = read_store(|ext| {
+        cash_list
+            .iter()
+            .map(|cash| convert_cash_to_response(cash, ext))
+            .collect()
+    })?;
+
+    Ok(transactions)
+}
+
+// 现金搜索 - 按筛选条件查找现金记录（现金搜索和分页列表共用）
+fn run_cash_search(filter: &CashSearchFilter) -> Result<Vec<qmx_backend_lib::cash::Cash>, String> {
     let manager = get_manager()?;
     let mut query = CashQuery::new();
 
-    if let Some(sid) = student_id {
+    if let Some(sid) = filter.student_id {
         validate_student_uid(sid)?;
         query = query.student_id(sid);
     }
-    if let (Some(min), Some(max)) = (min_amount, max_amount) {
+    if let (Some(min), Some(max)) = (filter.min_amount, filter.max_amount) {
         // 验证金额范围
         validate_amount_range(min, max)?;
         query = query.amount_range(min, max);
     }
-    if let Some(has_inst) = has_installment {
+    if let Some(has_inst) = filter.has_installment {
         query = query.has_installment(has_inst);
     }
 
-    // 添加日期范围查询支持
-    if let (Some(from_str), Some(to_str)) = (date_from, date_to) {
-        let start_date = DateTime::parse_from_rfc3339(&from_str)
-            .map_err(|e| format!("开始日期格式错误: {}", e))?
-            .with_timezone(&Utc);
-        let end_date = DateTime::parse_from_rfc3339(&to_str)
-            .map_err(|e| format!("结束日期格式错误: {}", e))?
-            .with_timezone(&Utc);
-
-        // 验证日期范围
-        validate_date_range(&start_date, &end_date)?;
+    // 日期范围按业务日期（俱乐部本地日期）筛选，没有业务日期的旧记录按录入时间
+    let date_range = match (&filter.date_from, &filter.date_to) {
+        (Some(from_str), Some(to_str)) => {
+            let start_date = parse_date_input(from_str).map_err(|e| format!("开始日期{}", e))?;
+            let end_date = parse_date_input(to_str).map_err(|e| format!("结束日期{}", e))?;
 
-        query = query.date_range(start_date, end_date);
-    }
+            // 验证日期范围
+            validate_date_range(&start_date, &end_date)?;
+            Some((to_local_date(&start_date), to_local_date(&end_date)))
+        }
+        _ => None,
+    };
 
     let cash_list = manager
         .search_cash(query)
         .map_err(|e| format!("搜索现金记录失败: {}", e))?;
+    match date_range {
+        Some((start_day, end_day)) => {
+            let in_range: HashSet<u64> = cash_in_local_range(start_day, end_day)?
+                .iter()
+                .map(|cash| cash.uid)
+                .collect();
+            Ok(cash_list
+                .into_iter()
+                .filter(|cash| in_range.contains(&cash.uid))
+                .collect())
+        }
+        None => Ok(cash_list),
+    }
+}
 
-    let mut transactions = Vec::new();
-    for cash in cash_list {
-        let (is_installment, plan_id, current, total, due_date_str, status_str) =
-            if let Some(installment) = &cash.installment {
-                (
-                    true,
-                    Some(installment.plan_id),
-                    Some(installment.current_installment),
-                    Some(installment.total_installments),
-                    Some(installment.due_date.to_rfc3339()),
-                    Some(format!("{:?}", installment.status)),
-                )
-            } else {
-                (false, None, None, None, None, None)
-            };
+// 分页列表 - 学员（不传筛选条件时为全部学员）
+//
+// 排序字段：uid（默认）、name（按拼音）、age、expiry（会员结束日期，无会员的排在最后）
+#[tauri::command]
+fn list_students_paged(
+    criteria: Option<StudentSearchCriteria>,
+    page: Option<PageRequest>,
+) -> Result<Page<StudentListItem>, String> {
+    init_manager()?;
 
-        transactions.push(TransactionResponse {
-            uid: cash.uid,
-            student_id: cash.student_id,
-            amount: cash.cash,
-            note: cash.note.clone(),
-            description: if is_installment {
-                format!("分期付款 {}/{}", current.unwrap_or(0), total.unwrap_or(0))
-            } else {
-                "普通付款".to_string()
-            },
-            is_installment,
-            installment_plan_id: plan_id,
-            installment_current: current,
-            installment_total: total,
-            installment_due_date: due_date_str,
-            installment_status: status_str,
-        });
+    let page = page.unwrap_or_default();
+    let mut students = match &criteria {
+        Some(criteria) => run_student_search(criteria)?,
+        None => get_manager()?
+            .list_students()
+            .map_err(|e| format!("获取学生列表失败: {}", e))?,
+    };
+
+    match page.sort_by.as_deref().unwrap_or("uid") {
+        "uid" => students.sort_by_key(|s| s.uid()),
+        "name" => students.sort_by_cached_key(|s| (name_pinyin(s.name()).full, s.uid())),
+        "age" => students.sort_by_key(|s| (s.age().is_none(), s.age(), s.uid())),
+        "expiry" => students.sort_by_key(|s| {
+            (
+                s.membership_end_date().is_none(),
+                s.membership_end_date(),
+                s.uid(),
+            )
+        }),
+        other => return Err(format!("无效的排序字段: {}", other)),
+    }
+    if page.is_descending() {
+        students.reverse();
     }
 
-    Ok(transactions)
+    let total = students.len();
+    let window = page.window(total);
+    let items = read_store(|ext| {
+        students[window]
+            .iter()
+            .map(|student| {
+                if page.is_summary() {
+                    StudentListItem::Summary(convert_student_to_summary(student, ext))
+                } else {
+                    StudentListItem::Full(Box::new(convert_student_to_response(student, ext)))
+                }
+            })
+            .collect()
+    })?;
+    Ok(page.page(items, total))
+}
+
+// 分页列表 - 现金记录（不传筛选条件时为全部记录）
+//
+// 排序字段：date（默认，按业务日期）、uid（录入顺序）、amount、due_date（分期到期日，非分期的排在最后）
+#[tauri::command]
+fn list_transactions_paged(
+    filter: Option<CashSearchFilter>,
+    page: Option<PageRequest>,
+) -> Result<Page<TransactionListItem>, String> {
+    init_manager()?;
+
+    let page = page.unwrap_or_default();
+    let mut cash_list = run_cash_search(&filter.unwrap_or_default())?;
+
+    match page.sort_by.as_deref().unwrap_or("date") {
+        // 按业务日期排序，同一天按录入顺序；旧记录没有业务日期，排在最前
+        "date" => {
+            let dates: HashMap<u64, NaiveDate> = read_store(|ext| {
+                ext.cash_meta
+                    .iter()
+                    .map(|(uid, meta)| (*uid, meta.business_date))
+                    .collect()
+            })?;
+            cash_list.sort_by_key(|c| (dates.get(&c.uid).copied(), c.uid));
+        }
+        "uid" => cash_list.sort_by_key(|c| c.uid),
+        "amount" => cash_list.sort_by_key(|c| (c.cash, c.uid)),
+        "due_date" => cash_list.sort_by_key(|c| {
+            let due_date = c.installment.as_ref().map(|i| i.due_date);
+            (due_date.is_none(), due_date, c.uid)
+        }),
+        other => return Err(format!("无效的排序字段: {}", other)),
+    }
+    if page.is_descending() {
+        cash_list.reverse();
+    }
+
+    let total = cash_list.len();
+    let window = page.window(total);
+    let items = read_store(|ext| {
+        cash_list[window]
+            .iter()
+            .map(|cash| {
+                if page.is_summary() {
+                    TransactionListItem::Summary(convert_cash_to_summary(cash, ext))
+                } else {
+                    TransactionListItem::Full(Box::new(convert_cash_to_response(cash, ext)))
+                }
+            })
+            .collect()
+    })?;
+    Ok(page.page(items, total))
 }
 
 // v2 API功能 - 批量操作学生
+//
+// 新增标签后超出标签数量上限的学员整条跳过（不做任何修改），在 skipped_uids 中返回
 #[tauri::command]
 fn update_multiple_students(
-    student_uids: Vec<u64>,
+    student_uids: Option<Vec<u64>>,
+    segment_id: Option<u64>,
     updates: StudentUpdateBatch,
-) -> Result<usize, String> {
+) -> Result<BatchUpdateResponse, String> {
     init_manager()?;
 
+    // 标签变更先校验，避免部分学员更新后才发现错误
+    let add_tags = normalize_tags(updates.add_tags.clone().unwrap_or_default());
+    let remove_tags = normalize_tags(updates.remove_tags.clone().unwrap_or_default());
+    validate_tags(&add_tags)?;
+
+    // 指定分组时对分组当前匹配的全部学员操作
+    let student_uids = match (student_uids, segment_id) {
+        (Some(_), Some(_)) => return Err("学员列表和分组不能同时指定".to_string()),
+        (Some(uids), None) => uids,
+        (None, Some(segment_id)) => {
+            let segment = read_store(|ext| ext.segments.get(&segment_id).cloned())?
+                .ok_or("学员分组不存在")?;
+            run_student_search(&segment.criteria)?
+                .iter()
+                .map(|s| s.uid())
+                .collect()
+        }
+        (None, None) => return Err("请指定学员列表或分组".to_string()),
+    };
+    let has_student_updates = updates.name.is_some()
+        || updates.age.is_some()
+        || updates.class_type.is_some()
+        || updates.subject.is_some();
+
+    // 新的标签集合在副本上计算，超出上限时保留原有标签
+    let tags_after = |current: Option<&BTreeSet<String>>| -> BTreeSet<String> {
+        let mut tags = current.cloned().unwrap_or_default();
+        tags.extend(add_tags.iter().cloned());
+        tags.retain(|t| !remove_tags.contains(t));
+        tags
+    };
+    let over_limit: HashSet<u64> = read_store(|ext| {
+        student_uids
+            .iter()
+            .copied()
+            .filter(|uid| tags_after(ext.student_tags.get(uid)).len() > MAX_TAGS_PER_STUDENT)
+            .collect()
+    })?;
+
     let manager = get_manager()?;
-    let mut success_count = 0;
+    let mut updated_uids = Vec::new();
+    let mut skipped_uids = Vec::new();
 
     for uid in student_uids {
+        if over_limit.contains(&uid) {
+            log::warn!("学员标签超过上限，跳过批量修改 - UID: {}", uid);
+            skipped_uids.push(uid);
+            continue;
+        }
+        if !has_student_updates {
+            if matches!(manager.get_student(uid), Ok(Some(_))) {
+                updated_uids.push(uid);
+            }
+            continue;
+        }
         let mut updater = StudentUpdater::new();
 
         if let Some(ref name) = updates.name {
@@ -1392,52 +3725,3683 @@ fn update_multiple_students(
             updater = updater.subject(subject_enum);
         }
 
-        if manager.update_student(uid, updater).is_ok() {
-            success_count += 1;
+        if manager.update_student(uid, updater).is_ok() {
+            updated_uids.push(uid);
+        }
+    }
+
+    if !add_tags.is_empty() || !remove_tags.is_empty() {
+        write_store(|ext| {
+            for uid in &updated_uids {
+                let tags = tags_after(ext.student_tags.get(uid));
+                if tags.len() <= MAX_TAGS_PER_STUDENT {
+                    ext.student_tags.insert(*uid, tags);
+                }
+            }
+            ext.student_tags.retain(|_, tags| !tags.is_empty());
+            Ok(())
+        })?;
+    }
+
+    refresh_search_index(&updated_uids);
+
+    Ok(BatchUpdateResponse {
+        updated_count: updated_uids.len(),
+        skipped_uids,
+    })
+}
+
+// v2 API功能 - 获取会员到期提醒
+#[tauri::command]
+fn get_membership_expiring_soon(days: i64) -> Result<Vec<StudentResponse>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_days(days)?;
+
+    let manager = get_manager()?;
+    // 截止到俱乐部本地日期第N天结束
+    let cutoff_day = club_today()
+        .checked_add_days(Days::new(days as u64))
+        .ok_or("天数超出范围")?;
+    let cutoff_date = local_day_end(cutoff_day);
+
+    let students = manager
+        .search_students(StudentQuery::new().has_membership(true))
+        .map_err(|e| format!("搜索会员学生失败: {}", e))?;
+
+    read_store(|ext| {
+        let mut expiring_students = Vec::new();
+        for student in &students {
+            // 冻结中的会员不参与到期提醒
+            let is_frozen = ext
+                .membership_freezes
+                .get(&student.uid())
+                .is_some_and(|f| active_freeze(f).is_some());
+            if is_frozen {
+                continue;
+            }
+            if let Some(end_date) = student.membership_end_date() {
+                if end_date <= cutoff_date && student.is_membership_active() {
+                    expiring_students.push(convert_student_to_response(student, ext));
+                }
+            }
+        }
+        expiring_students
+    })
+}
+
+// 监护人 - 新增监护人
+#[tauri::command]
+fn add_guardian(
+    name: String,
+    relationship: String,
+    phones: Vec<String>,
+    note: Option<String>,
+) -> Result<GuardianResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_name(&name)?;
+    validate_guardian_relationship(&relationship)?;
+    let phones = normalize_phones(phones);
+    validate_guardian_phones(&phones)?;
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+
+    write_store(|ext| {
+        let guardian = Guardian {
+            id: ext.alloc_id(),
+            name: name.trim().to_string(),
+            relationship: relationship.trim().to_string(),
+            phones,
+            note: note.map(|n| n.trim().to_string()),
+            created_at: Utc::now(),
+        };
+        log::info!("成功新增监护人: {} (ID: {})", guardian.name, guardian.id);
+        let response = convert_guardian_to_response(&guardian, ext);
+        ext.guardians.insert(guardian.id, guardian);
+        Ok(response)
+    })
+}
+
+// 监护人 - 修改监护人信息
+#[tauri::command]
+fn update_guardian(
+    guardian_id: u64,
+    name: Option<String>,
+    relationship: Option<String>,
+    phones: Option<Vec<String>>,
+    note: Option<String>,
+) -> Result<GuardianResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    if let Some(name_str) = &name {
+        validate_student_name(name_str)?;
+    }
+    if let Some(relationship_str) = &relationship {
+        validate_guardian_relationship(relationship_str)?;
+    }
+    let phones = phones.map(normalize_phones);
+    if let Some(phone_list) = &phones {
+        validate_guardian_phones(phone_list)?;
+    }
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+
+    let (response, student_uids) = write_store(|ext| {
+        let guardian = ext.guardians.get_mut(&guardian_id).ok_or("监护人不存在")?;
+        if let Some(name) = name {
+            guardian.name = name.trim().to_string();
+        }
+        if let Some(relationship) = relationship {
+            guardian.relationship = relationship.trim().to_string();
+        }
+        if let Some(phones) = phones {
+            guardian.phones = phones;
+        }
+        if let Some(note) = note {
+            guardian.note = Some(note.trim().to_string()).filter(|n| !n.is_empty());
+        }
+        let guardian = guardian.clone();
+        Ok((
+            convert_guardian_to_response(&guardian, ext),
+            ext.guardian_student_uids(guardian_id),
+        ))
+    })?;
+    refresh_search_index(&student_uids);
+    Ok(response)
+}
+
+// 监护人 - 删除监护人并解除与所有学员的关联
+#[tauri::command]
+fn delete_guardian(guardian_id: u64) -> Result<(), String> {
+    init_manager()?;
+
+    let student_uids = write_store(|ext| {
+        ext.guardians.remove(&guardian_id).ok_or("监护人不存在")?;
+        let student_uids = ext.guardian_student_uids(guardian_id);
+        for links in ext.student_guardians.values_mut() {
+            let was_primary = links
+                .iter()
+                .any(|l| l.guardian_id == guardian_id && l.is_primary);
+            links.retain(|l| l.guardian_id != guardian_id);
+            // 删除的是主要联系人时，由剩余的第一位监护人接替
+            if was_primary {
+                if let Some(first) = links.first_mut() {
+                    first.is_primary = true;
+                }
+            }
+        }
+        ext.student_guardians.retain(|_, links| !links.is_empty());
+        Ok(student_uids)
+    })?;
+    refresh_search_index(&student_uids);
+
+    log::info!("成功删除监护人 - ID: {}", guardian_id);
+    Ok(())
+}
+
+// 监护人 - 关联监护人与学员（同一监护人可关联多个兄弟姐妹）
+#[tauri::command]
+fn link_guardian_to_student(
+    student_uid: u64,
+    guardian_id: u64,
+    is_primary: Option<bool>,
+) -> Result<Vec<StudentGuardianResponse>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+
+    let manager = get_manager()?;
+    manager
+        .get_student(student_uid)
+        .map_err(|e| format!("获取学生失败: {}", e))?
+        .ok_or("学员不存在")?;
+
+    let links = write_store(|ext| {
+        if !ext.guardians.contains_key(&guardian_id) {
+            return Err("监护人不存在".to_string());
+        }
+        let links = ext.student_guardians.entry(student_uid).or_default();
+        if !links.iter().any(|l| l.guardian_id == guardian_id) {
+            links.push(GuardianLink {
+                guardian_id,
+                is_primary: false,
+            });
+        }
+        // 第一位监护人默认为主要联系人
+        if is_primary.unwrap_or(false) || links.len() == 1 {
+            set_primary(links, guardian_id);
+        }
+
+        let links = links.clone();
+        Ok(links
+            .iter()
+            .filter_map(|link| {
+                ext.guardians
+                    .get(&link.guardian_id)
+                    .map(|g| convert_student_guardian_to_response(g, link))
+            })
+            .collect())
+    })?;
+    refresh_search_index(&[student_uid]);
+    Ok(links)
+}
+
+// 监护人 - 解除监护人与学员的关联
+#[tauri::command]
+fn unlink_guardian_from_student(student_uid: u64, guardian_id: u64) -> Result<(), String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+
+    write_store(|ext| {
+        let links = ext
+            .student_guardians
+            .get_mut(&student_uid)
+            .ok_or("该学员没有关联监护人")?;
+        let index = links
+            .iter()
+            .position(|l| l.guardian_id == guardian_id)
+            .ok_or("该监护人未关联此学员")?;
+        let removed = links.remove(index);
+        if removed.is_primary {
+            if let Some(first) = links.first_mut() {
+                first.is_primary = true;
+            }
+        }
+        if links.is_empty() {
+            ext.student_guardians.remove(&student_uid);
+        }
+        Ok(())
+    })?;
+    refresh_search_index(&[student_uid]);
+
+    log::info!(
+        "成功解除监护人关联 - 学生UID: {}, 监护人ID: {}",
+        student_uid,
+        guardian_id
+    );
+    Ok(())
+}
+
+// 监护人 - 获取全部监护人
+#[tauri::command]
+fn get_all_guardians() -> Result<Vec<GuardianResponse>, String> {
+    init_manager()?;
+
+    read_store(|ext| {
+        let mut guardians: Vec<&Guardian> = ext.guardians.values().collect();
+        guardians.sort_by_key(|g| g.id);
+        guardians
+            .into_iter()
+            .map(|g| convert_guardian_to_response(g, ext))
+            .collect()
+    })
+}
+
+// 家庭账户 - 创建家庭
+#[tauri::command]
+fn create_family(
+    name: String,
+    student_uids: Vec<u64>,
+    discount_percent: Option<u32>,
+    note: Option<String>,
+) -> Result<FamilyResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_name(&name)?;
+    if let Some(percent) = discount_percent {
+        validate_discount_percent(percent)?;
+    }
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+
+    let manager = get_manager()?;
+    let family = write_store(|ext| {
+        let mut member_uids: Vec<u64> = Vec::new();
+        for uid in student_uids {
+            validate_family_member(&manager, ext, uid, None)?;
+            if !member_uids.contains(&uid) {
+                member_uids.push(uid);
+            }
+        }
+        let family = Family {
+            id: ext.alloc_id(),
+            name: name.trim().to_string(),
+            member_uids,
+            discount_percent: discount_percent.unwrap_or(0),
+            note: note.map(|n| n.trim().to_string()),
+            created_at: Utc::now(),
+        };
+        ext.families.insert(family.id, family.clone());
+        Ok(family)
+    })?;
+
+    log::info!("成功创建家庭: {} (ID: {})", family.name, family.id);
+    Ok(convert_family_to_response(&family, &manager))
+}
+
+// 家庭账户 - 修改家庭信息
+#[tauri::command]
+fn update_family(
+    family_id: u64,
+    name: Option<String>,
+    discount_percent: Option<u32>,
+    note: Option<String>,
+) -> Result<FamilyResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    if let Some(name_str) = &name {
+        validate_student_name(name_str)?;
+    }
+    if let Some(percent) = discount_percent {
+        validate_discount_percent(percent)?;
+    }
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+
+    let manager = get_manager()?;
+    let family = write_store(|ext| {
+        let family = ext.families.get_mut(&family_id).ok_or("家庭不存在")?;
+        if let Some(name) = name {
+            family.name = name.trim().to_string();
+        }
+        if let Some(percent) = discount_percent {
+            family.discount_percent = percent;
+        }
+        if let Some(note) = note {
+            family.note = Some(note.trim().to_string()).filter(|n| !n.is_empty());
+        }
+        Ok(family.clone())
+    })?;
+
+    Ok(convert_family_to_response(&family, &manager))
+}
+
+// 家庭账户 - 添加家庭成员
+#[tauri::command]
+fn add_family_member(family_id: u64, student_uid: u64) -> Result<FamilyResponse, String> {
+    init_manager()?;
+
+    let manager = get_manager()?;
+    let family = write_store(|ext| {
+        if !ext.families.contains_key(&family_id) {
+            return Err("家庭不存在".to_string());
+        }
+        validate_family_member(&manager, ext, student_uid, Some(family_id))?;
+        let family = ext.families.get_mut(&family_id).ok_or("家庭不存在")?;
+        if !family.member_uids.contains(&student_uid) {
+            family.member_uids.push(student_uid);
+        }
+        Ok(family.clone())
+    })?;
+
+    log::info!(
+        "成功添加家庭成员 - 家庭ID: {}, 学生UID: {}",
+        family_id,
+        student_uid
+    );
+    Ok(convert_family_to_response(&family, &manager))
+}
+
+// 家庭账户 - 移除家庭成员（历史付款记录保持不变）
+#[tauri::command]
+fn remove_family_member(family_id: u64, student_uid: u64) -> Result<FamilyResponse, String> {
+    init_manager()?;
+
+    let manager = get_manager()?;
+    let family = write_store(|ext| {
+        let family = ext.families.get_mut(&family_id).ok_or("家庭不存在")?;
+        let before = family.member_uids.len();
+        family.member_uids.retain(|uid| *uid != student_uid);
+        if family.member_uids.len() == before {
+            return Err("该学员不是此家庭成员".to_string());
+        }
+        Ok(family.clone())
+    })?;
+
+    Ok(convert_family_to_response(&family, &manager))
+}
+
+// 家庭账户 - 删除家庭（不删除学员和现金记录）
+//
+// 有付款记录的家庭不能删除，付款记录用于保护关联的现金记录和家庭对账
+#[tauri::command]
+fn delete_family(family_id: u64) -> Result<(), String> {
+    init_manager()?;
+
+    write_store(|ext| {
+        if !ext.families.contains_key(&family_id) {
+            return Err("家庭不存在".to_string());
+        }
+        if ext.family_payments.iter().any(|p| p.family_id == family_id) {
+            return Err("该家庭已有付款记录，不能删除".to_string());
+        }
+        ext.families.remove(&family_id);
+        Ok(())
+    })?;
+
+    log::info!("成功删除家庭 - ID: {}", family_id);
+    Ok(())
+}
+
+// 家庭账户 - 获取全部家庭
+#[tauri::command]
+fn get_all_families() -> Result<Vec<FamilyResponse>, String> {
+    init_manager()?;
+
+    let manager = get_manager()?;
+    let mut families: Vec<Family> = read_store(|ext| ext.families.values().cloned().collect())?;
+    families.sort_by_key(|f| f.id);
+
+    Ok(families
+        .iter()
+        .map(|f| convert_family_to_response(f, &manager))
+        .collect())
+}
+
+// 家庭账户 - 家庭合并付款，按成员拆分为多条现金记录
+//
+// total_amount 为标价合计；apply_discount 为 true 时按家庭折扣比例减免，默认不减免
+#[tauri::command]
+fn record_family_payment(
+    family_id: u64,
+    total_amount: i64,
+    allocations: Option<Vec<FamilyAllocation>>,
+    apply_discount: Option<bool>,
+    note: Option<String>,
+) -> Result<FamilyPaymentResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_amount(total_amount)?;
+    if total_amount <= 0 {
+        return Err("家庭付款金额必须大于0".to_string());
+    }
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+
+    let family = read_store(|ext| ext.families.get(&family_id).cloned())?.ok_or("家庭不存在")?;
+    if family.member_uids.is_empty() {
+        return Err("家庭没有成员，无法拆分付款".to_string());
+    }
+
+    // 确定每个成员的标价金额
+    let list_amounts: Vec<(u64, i64)> = match allocations {
+        Some(allocations) => {
+            if allocations.is_empty() {
+                return Err("拆分明细不能为空".to_string());
+            }
+            for allocation in &allocations {
+                if !family.member_uids.contains(&allocation.student_uid) {
+                    return Err(format!(
+                        "学员不是此家庭成员 - UID: {}",
+                        allocation.student_uid
+                    ));
+                }
+                if allocation.amount <= 0 {
+                    return Err("拆分金额必须大于0".to_string());
+                }
+            }
+            let allocated: i64 = allocations.iter().map(|a| a.amount).sum();
+            if allocated != total_amount {
+                return Err(format!(
+                    "拆分金额合计({})与付款总额({})不一致",
+                    allocated, total_amount
+                ));
+            }
+            allocations
+                .into_iter()
+                .map(|a| (a.student_uid, a.amount))
+                .collect()
+        }
+        None => family
+            .member_uids
+            .iter()
+            .copied()
+            .zip(split_evenly(total_amount, family.member_uids.len()))
+            .collect(),
+    };
+
+    let discount_percent = if apply_discount.unwrap_or(false) {
+        family.discount_percent
+    } else {
+        0
+    };
+
+    let manager = get_manager()?;
+    let note_text = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    let mut cash_uids = Vec::new();
+    let mut discounts = Vec::new();
+    let mut total_discount = 0;
+    for (student_uid, list_amount) in &list_amounts {
+        let discount = discount_amount(*list_amount, discount_percent);
+        total_discount += discount;
+        discounts.push((*list_amount, discount));
+        let cash_note = match &note_text {
+            Some(text) => format!("家庭付款({}): {}", family.name, text),
+            None => format!("家庭付款({})", family.name),
+        };
+        let result = manager.record_cash(
+            CashBuilder::new(list_amount - discount)
+                .student_id(*student_uid)
+                .note(cash_note),
+        );
+        match result {
+            Ok(uid) => cash_uids.push(uid),
+            Err(e) => {
+                // 回滚已创建的记录，避免家庭付款只入账一部分
+                for uid in &cash_uids {
+                    let _ = manager.delete_cash(*uid);
+                }
+                log::error!("家庭付款入账失败 - 家庭ID: {}, 错误: {}", family_id, e);
+                return Err(format!("家庭付款入账失败: {}", e));
+            }
+        }
+    }
+
+    let result = write_store(|ext| {
+        for (uid, (list_amount, discount)) in cash_uids.iter().zip(&discounts) {
+            ext.record_cash_meta(*uid, None, None);
+            // 家庭折扣按 Sibling 类别计入折扣报表
+            if *discount > 0 {
+                ext.cash_pricing.insert(
+                    *uid,
+                    CashPricing {
+                        list_price: *list_amount,
+                        discount_amount: *discount,
+                        rule_id: None,
+                        coupon_code: None,
+                        category: Some(DiscountCategory::Sibling),
+                        created_at: Utc::now(),
+                    },
+                );
+            }
+        }
+        let payment = FamilyPayment {
+            id: ext.alloc_id(),
+            family_id,
+            list_amount: total_amount,
+            discount_amount: total_discount,
+            cash_uids: cash_uids.clone(),
+            note: note_text,
+            created_at: Utc::now(),
+        };
+        ext.family_payments.push(payment.clone());
+        Ok(payment)
+    });
+    let payment = match result {
+        Ok(payment) => payment,
+        Err(e) => {
+            for uid in &cash_uids {
+                let _ = manager.delete_cash(*uid);
+            }
+            log::error!("家庭付款保存失败 - 家庭ID: {}, 错误: {}", family_id, e);
+            return Err(e);
+        }
+    };
+
+    log::info!(
+        "成功记录家庭付款 - 家庭ID: {}, 总额: {}, 折扣: {}",
+        family_id,
+        total_amount,
+        total_discount
+    );
+    Ok(convert_family_payment_to_response(&payment))
+}
+
+// 家庭账户 - 家庭合并对账单（所有成员的现金记录）
+#[tauri::command]
+fn get_family_statement(family_id: u64) -> Result<FamilyStatementResponse, String> {
+    init_manager()?;
+
+    let (family, payments) = read_store(|ext| {
+        let family = ext.families.get(&family_id).cloned();
+        let payments: Vec<FamilyPaymentResponse> = ext
+            .family_payments
+            .iter()
+            .filter(|p| p.family_id == family_id)
+            .map(convert_family_payment_to_response)
+            .collect();
+        (family, payments)
+    })?;
+    let family = family.ok_or("家庭不存在")?;
+
+    let manager = get_manager()?;
+    let mut cash_list = Vec::new();
+    for uid in &family.member_uids {
+        cash_list.extend(
+            manager
+                .get_student_cash(*uid)
+                .map_err(|e| format!("获取学生现金记录失败: {}", e))?,
+        );
+    }
+    let mut transactions: Vec<TransactionResponse> = read_store(|ext| {
+        cash_list
+            .iter()
+            .map(|cash| convert_cash_to_response(cash, ext))
+            .collect()
+    })?;
+    transactions.sort_by_key(|t| t.uid);
+
+    // 未付和已取消的分期只列出明细，不计入收支合计
+    let total_income = cash_list
+        .iter()
+        .filter(|cash| cash.cash > 0 && is_cash_settled(cash))
+        .map(|cash| cash.cash)
+        .sum();
+    let total_expense = cash_list
+        .iter()
+        .filter(|cash| cash.cash <= 0 && is_cash_settled(cash))
+        .map(|cash| cash.cash.abs())
+        .sum();
+
+    Ok(FamilyStatementResponse {
+        family: convert_family_to_response(&family, &manager),
+        total_income,
+        total_expense,
+        transactions,
+        payments,
+    })
+}
+
+// 储值钱包 - 充值（记录一笔收入现金记录）
+#[tauri::command]
+fn top_up_wallet(
+    student_uid: u64,
+    amount: i64,
+    note: Option<String>,
+) -> Result<WalletEntryResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+    validate_amount(amount)?;
+    if amount <= 0 {
+        return Err("充值金额必须大于0".to_string());
+    }
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+
+    let manager = get_manager()?;
+    manager
+        .get_student(student_uid)
+        .map_err(|e| format!("获取学生失败: {}", e))?
+        .ok_or("学员不存在")?;
+
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    let cash_note = match &note {
+        Some(text) => format!("钱包充值: {}", text),
+        None => "钱包充值".to_string(),
+    };
+    let cash_uid = manager
+        .record_cash(
+            CashBuilder::new(amount)
+                .student_id(student_uid)
+                .note(cash_note),
+        )
+        .map_err(|e| format!("保存充值记录失败: {}", e))?;
+
+    let entry = append_wallet_entry(
+        &manager,
+        student_uid,
+        WalletEntryKind::TopUp,
+        amount,
+        None,
+        cash_uid,
+        note,
+    )?;
+
+    log::info!(
+        "钱包充值成功 - 学生UID: {}, 金额: {}, 余额: {}",
+        student_uid,
+        amount,
+        entry.balance_after
+    );
+    Ok(convert_wallet_entry_to_response(&entry))
+}
+
+// 储值钱包 - 扣款（课时、场地、器材等）
+#[tauri::command]
+fn charge_wallet(
+    student_uid: u64,
+    amount: i64,
+    category: String,
+    note: Option<String>,
+) -> Result<WalletEntryResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+    validate_amount(amount)?;
+    if amount <= 0 {
+        return Err("扣款金额必须大于0".to_string());
+    }
+    validate_wallet_category(&category)?;
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+
+    let balance = read_store(|ext| {
+        ext.wallets
+            .get(&student_uid)
+            .map(|entries| wallet_balance(entries))
+            .unwrap_or(0)
+    })?;
+    if balance < amount {
+        return Err(format!("钱包余额不足，当前余额: {}", balance));
+    }
+
+    let manager = get_manager()?;
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    // 充值时已计入收入，扣款记录金额为0，仅用于在账目中留下消费凭证
+    let cash_note = match &note {
+        Some(text) => format!(
+            "钱包扣款({}) {}: {}",
+            charge_category_label(&category),
+            amount,
+            text
+        ),
+        None => format!("钱包扣款({}) {}", charge_category_label(&category), amount),
+    };
+    let cash_uid = manager
+        .record_cash(CashBuilder::new(0).student_id(student_uid).note(cash_note))
+        .map_err(|e| format!("保存扣款记录失败: {}", e))?;
+
+    let entry = append_wallet_entry(
+        &manager,
+        student_uid,
+        WalletEntryKind::Charge,
+        -amount,
+        Some(category),
+        cash_uid,
+        note,
+    )?;
+
+    log::info!(
+        "钱包扣款成功 - 学生UID: {}, 金额: {}, 余额: {}",
+        student_uid,
+        amount,
+        entry.balance_after
+    );
+    Ok(convert_wallet_entry_to_response(&entry))
+}
+
+// 储值钱包 - 获取余额及流水（最近的在前）
+#[tauri::command]
+fn get_wallet_ledger(student_uid: u64) -> Result<WalletLedgerResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+
+    read_store(|ext| {
+        let entries: &[WalletEntry] = ext
+            .wallets
+            .get(&student_uid)
+            .map(|entries| entries.as_slice())
+            .unwrap_or_default();
+        WalletLedgerResponse {
+            student_uid,
+            balance: wallet_balance(entries),
+            entries: entries
+                .iter()
+                .rev()
+                .map(convert_wallet_entry_to_response)
+                .collect(),
+        }
+    })
+}
+
+// 折扣规则 - 新增规则
+#[tauri::command]
+fn create_discount_rule(
+    name: String,
+    kind: String,
+    value: i64,
+    category: Option<String>,
+) -> Result<DiscountRuleResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_name(&name)?;
+    let kind_enum = parse_discount_kind(&kind)?;
+    validate_discount_value(&kind, value)?;
+    let category_enum = parse_discount_category(category.as_deref().unwrap_or("General"))?;
+
+    let rule = write_store(|ext| {
+        let rule = DiscountRule {
+            id: ext.alloc_id(),
+            name: name.trim().to_string(),
+            kind: kind_enum,
+            value,
+            category: category_enum,
+            active: true,
+            created_at: Utc::now(),
+        };
+        ext.discount_rules.insert(rule.id, rule.clone());
+        Ok(rule)
+    })?;
+
+    log::info!("成功新增折扣规则: {} (ID: {})", rule.name, rule.id);
+    Ok(convert_discount_rule_to_response(&rule))
+}
+
+// 折扣规则 - 修改规则（已入账交易的折扣金额不受影响）
+#[tauri::command]
+fn update_discount_rule(
+    rule_id: u64,
+    name: Option<String>,
+    value: Option<i64>,
+    active: Option<bool>,
+) -> Result<DiscountRuleResponse, String> {
+    init_manager()?;
+
+    if let Some(name_str) = &name {
+        validate_student_name(name_str)?;
+    }
+
+    let rule = write_store(|ext| {
+        let rule = ext
+            .discount_rules
+            .get_mut(&rule_id)
+            .ok_or("折扣规则不存在")?;
+        if let Some(value) = value {
+            validate_discount_value(&format!("{:?}", rule.kind), value)?;
+            rule.value = value;
+        }
+        if let Some(name) = name {
+            rule.name = name.trim().to_string();
+        }
+        if let Some(active) = active {
+            rule.active = active;
+        }
+        Ok(rule.clone())
+    })?;
+
+    Ok(convert_discount_rule_to_response(&rule))
+}
+
+// 折扣规则 - 获取全部规则
+#[tauri::command]
+fn get_discount_rules() -> Result<Vec<DiscountRuleResponse>, String> {
+    init_manager()?;
+
+    read_store(|ext| {
+        let mut rules: Vec<&DiscountRule> = ext.discount_rules.values().collect();
+        rules.sort_by_key(|r| r.id);
+        rules
+            .into_iter()
+            .map(convert_discount_rule_to_response)
+            .collect()
+    })
+}
+
+// 优惠券 - 新增优惠券
+#[tauri::command]
+fn create_coupon(
+    code: String,
+    rule_id: u64,
+    max_uses: Option<u32>,
+    expires_at: Option<String>,
+) -> Result<CouponResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_coupon_code(&code)?;
+    let code = normalize_coupon_code(&code);
+    if max_uses == Some(0) {
+        return Err("优惠券使用次数上限必须大于0".to_string());
+    }
+    // 纯日期表示该日结束时过期
+    let expires_at = match expires_at {
+        Some(date_str) => Some(local_day_end(to_local_date(&parse_date_input(&date_str)?))),
+        None => None,
+    };
+
+    let coupon = write_store(|ext| {
+        if !ext.discount_rules.contains_key(&rule_id) {
+            return Err("折扣规则不存在".to_string());
+        }
+        if ext.coupons.values().any(|c| c.code == code) {
+            return Err(format!("优惠券码已存在: {}", code));
+        }
+        let coupon = Coupon {
+            id: ext.alloc_id(),
+            code,
+            rule_id,
+            max_uses,
+            used_count: 0,
+            expires_at,
+            active: true,
+            created_at: Utc::now(),
+        };
+        ext.coupons.insert(coupon.id, coupon.clone());
+        Ok(coupon)
+    })?;
+
+    log::info!("成功新增优惠券: {} (ID: {})", coupon.code, coupon.id);
+    Ok(convert_coupon_to_response(&coupon))
+}
+
+// 优惠券 - 启用/停用优惠券
+#[tauri::command]
+fn set_coupon_active(coupon_id: u64, active: bool) -> Result<CouponResponse, String> {
+    init_manager()?;
+
+    let coupon = write_store(|ext| {
+        let coupon = ext.coupons.get_mut(&coupon_id).ok_or("优惠券不存在")?;
+        coupon.active = active;
+        Ok(coupon.clone())
+    })?;
+
+    Ok(convert_coupon_to_response(&coupon))
+}
+
+// 优惠券 - 获取全部优惠券
+#[tauri::command]
+fn get_coupons() -> Result<Vec<CouponResponse>, String> {
+    init_manager()?;
+
+    read_store(|ext| {
+        let mut coupons: Vec<&Coupon> = ext.coupons.values().collect();
+        coupons.sort_by_key(|c| c.id);
+        coupons
+            .into_iter()
+            .map(convert_coupon_to_response)
+            .collect()
+    })
+}
+
+// 折扣 - 预览折扣结果（不入账、不占用优惠券次数）
+#[tauri::command]
+fn preview_discount(
+    list_price: i64,
+    discount_rule_id: Option<u64>,
+    coupon_code: Option<String>,
+) -> Result<DiscountPreviewResponse, String> {
+    init_manager()?;
+
+    validate_amount(list_price)?;
+    if let Some(code) = &coupon_code {
+        validate_coupon_code(code)?;
+    }
+
+    let pricing = resolve_cash_pricing(list_price, None, discount_rule_id, coupon_code)?
+        .ok_or("请指定折扣规则或优惠券")?;
+
+    Ok(DiscountPreviewResponse {
+        list_price: pricing.list_price,
+        discount_amount: pricing.discount_amount,
+        final_amount: pricing.list_price - pricing.discount_amount,
+        discount_rule_id: pricing.rule_id,
+    })
+}
+
+// 折扣报表 - 统计区间内给出的折扣总额（按规则和类别汇总）
+#[tauri::command]
+fn get_discount_report(
+    period: Option<String>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<DiscountReportResponse, String> {
+    init_manager()?;
+
+    let (start_day, end_day) =
+        report_local_range(period.as_deref(), date_from.as_deref(), date_to.as_deref())?;
+
+    read_store(|ext| {
+        let mut by_rule: BTreeMap<(Option<u64>, String), DiscountReportRow> = BTreeMap::new();
+        let mut by_category: BTreeMap<String, i64> = BTreeMap::new();
+        let mut total_list_price = 0;
+        let mut total_discount = 0;
+        let mut transaction_count = 0;
+
+        // 按交易的业务日期统计，没有业务日期的旧记录按折扣记录时间
+        for (_, pricing) in ext.cash_pricing.iter().filter(|(uid, p)| {
+            let date = ext
+                .cash_meta
+                .get(uid)
+                .map(|m| m.business_date)
+                .unwrap_or_else(|| to_local_date(&p.created_at));
+            date >= start_day && date <= end_day
+        }) {
+            let rule = pricing.rule_id.and_then(|id| ext.discount_rules.get(&id));
+            let category = rule
+                .map(|r| r.category)
+                .or(pricing.category)
+                .map(|c| format!("{:?}", c))
+                .unwrap_or_else(|| "Manual".to_string());
+
+            total_list_price += pricing.list_price;
+            total_discount += pricing.discount_amount;
+            transaction_count += 1;
+            *by_category.entry(category.clone()).or_default() += pricing.discount_amount;
+
+            let row = by_rule
+                .entry((pricing.rule_id, category))
+                .or_insert_with(|| DiscountReportRow {
+                    rule_id: pricing.rule_id,
+                    rule_name: match (rule, pricing.category) {
+                        (Some(r), _) => r.name.clone(),
+                        (None, Some(DiscountCategory::Sibling)) => "家庭折扣".to_string(),
+                        _ => "手工折扣".to_string(),
+                    },
+                    transaction_count: 0,
+                    total_discount: 0,
+                });
+            row.transaction_count += 1;
+            row.total_discount += pricing.discount_amount;
+        }
+
+        DiscountReportResponse {
+            period_start_date: start_day.format("%Y-%m-%d").to_string(),
+            period_end_date: end_day.format("%Y-%m-%d").to_string(),
+            transaction_count,
+            total_list_price,
+            total_discount,
+            by_rule: by_rule.into_values().collect(),
+            by_category,
+        }
+    })
+}
+
+// 转介绍 - 获取推荐奖励设置
+#[tauri::command]
+fn get_referral_reward() -> Result<ReferralRewardResponse, String> {
+    init_manager()?;
+
+    read_store(|ext| ReferralRewardResponse {
+        extra_lessons: ext.referral_reward.extra_lessons,
+        membership_days: ext.referral_reward.membership_days,
+    })
+}
+
+// 转介绍 - 修改推荐奖励设置（仅对之后的推荐生效）
+#[tauri::command]
+fn set_referral_reward(
+    extra_lessons: u32,
+    membership_days: u32,
+) -> Result<ReferralRewardResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    if extra_lessons > 9999 {
+        return Err("赠送课时不能超过9999".to_string());
+    }
+    if membership_days > 0 {
+        validate_membership_days(membership_days)?;
+    }
+
+    write_store(|ext| {
+        ext.referral_reward = ReferralReward {
+            extra_lessons,
+            membership_days,
+        };
+        Ok(())
+    })?;
+
+    log::info!(
+        "成功修改推荐奖励 - 赠送课时: {}, 赠送天数: {}",
+        extra_lessons,
+        membership_days
+    );
+    Ok(ReferralRewardResponse {
+        extra_lessons,
+        membership_days,
+    })
+}
+
+// 转介绍报表 - 谁推荐了谁，以及被推荐学员带来的收入
+#[tauri::command]
+fn get_referral_report() -> Result<Vec<ReferralReportRow>, String> {
+    init_manager()?;
+
+    let manager = get_manager()?;
+    let referrals: Vec<(u64, Referral)> =
+        read_store(|ext| ext.referrals.iter().map(|(k, v)| (*k, v.clone())).collect())?;
+
+    let student_name = |uid: u64| {
+        manager
+            .get_student(uid)
+            .ok()
+            .flatten()
+            .map(|s| s.name().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut rows: BTreeMap<u64, ReferralReportRow> = BTreeMap::new();
+    for (referee_uid, referral) in referrals {
+        let revenue: i64 = manager
+            .get_student_cash(referee_uid)
+            .map_err(|e| format!("获取学生现金记录失败: {}", e))?
+            .iter()
+            .filter(|c| c.cash > 0 && is_cash_settled(c))
+            .map(|c| c.cash)
+            .sum();
+
+        let row = rows
+            .entry(referral.referrer_uid)
+            .or_insert_with(|| ReferralReportRow {
+                referrer_uid: referral.referrer_uid,
+                referrer_name: student_name(referral.referrer_uid),
+                referred: Vec::new(),
+                total_revenue: 0,
+                total_rewarded_lessons: 0,
+                total_rewarded_days: 0,
+            });
+        row.total_revenue += revenue;
+        row.total_rewarded_lessons += referral.rewarded_lessons;
+        row.total_rewarded_days += referral.rewarded_days;
+        row.referred.push(ReferredStudentResponse {
+            uid: referee_uid,
+            name: student_name(referee_uid),
+            referred_at: referral.created_at.to_rfc3339(),
+            revenue,
+            rewarded_lessons: referral.rewarded_lessons,
+            rewarded_days: referral.rewarded_days,
+        });
+    }
+
+    let mut rows: Vec<ReferralReportRow> = rows.into_values().collect();
+    for row in &mut rows {
+        row.referred.sort_by_key(|r| r.uid);
+    }
+    // 按带来的收入从高到低排序
+    rows.sort_by_key(|r| std::cmp::Reverse(r.total_revenue));
+    Ok(rows)
+}
+
+// 潜在客户 - 登记体验/咨询客户
+#[tauri::command]
+fn create_lead(
+    name: String,
+    phone: String,
+    age: Option<u8>,
+    source: String,
+    referrer_uid: Option<u64>,
+    note: Option<String>,
+    operator: Option<String>,
+) -> Result<LeadResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_name(&name)?;
+    validate_phone_number(&phone)?;
+    if let Some(age_val) = age {
+        validate_age(age_val)?;
+    }
+    let source = parse_lead_source(&source)?;
+    validate_lead_referrer(source, referrer_uid)?;
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+    if let Some(operator_str) = &operator {
+        validate_operator(operator_str)?;
+    }
+
+    write_store(|ext| {
+        let now = Utc::now();
+        let mut lead = Lead {
+            id: ext.alloc_id(),
+            name: name.trim().to_string(),
+            phone: phone.trim().to_string(),
+            age,
+            source,
+            referrer_uid,
+            status: LeadStatus::New,
+            follow_ups: Vec::new(),
+            student_uid: None,
+            created_at: now,
+            converted_at: None,
+        };
+        lead.follow_up(
+            LeadStatus::New,
+            note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+            operator.map(|o| o.trim().to_string()),
+            now,
+        );
+        log::info!("成功登记潜在客户: {} (ID: {})", lead.name, lead.id);
+        let response = convert_lead_to_response(&lead);
+        ext.leads.insert(lead.id, lead);
+        Ok(response)
+    })
+}
+
+// 潜在客户 - 修改客户信息
+//
+// clear_age / clear_referrer 为 true 时清除年龄 / 推荐人，不能与 age / referrer_uid 同时传入
+#[tauri::command]
+fn update_lead(
+    lead_id: u64,
+    name: Option<String>,
+    phone: Option<String>,
+    age: Option<u8>,
+    clear_age: Option<bool>,
+    source: Option<String>,
+    referrer_uid: Option<u64>,
+    clear_referrer: Option<bool>,
+) -> Result<LeadResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    if let Some(name_str) = &name {
+        validate_student_name(name_str)?;
+    }
+    if let Some(phone_str) = &phone {
+        validate_phone_number(phone_str)?;
+    }
+    if let Some(age_val) = age {
+        validate_age(age_val)?;
+    }
+    let clear_age = clear_age.unwrap_or(false);
+    if clear_age && age.is_some() {
+        return Err("不能同时修改和清除年龄".to_string());
+    }
+    let clear_referrer = clear_referrer.unwrap_or(false);
+    if clear_referrer && referrer_uid.is_some() {
+        return Err("不能同时修改和清除推荐人".to_string());
+    }
+    let source = source.as_deref().map(parse_lead_source).transpose()?;
+    let (current_source, current_referrer) = read_store(|ext| {
+        ext.leads
+            .get(&lead_id)
+            .map(|l| (l.source, l.referrer_uid))
+            .ok_or("潜在客户不存在")
+    })??;
+    let new_source = source.unwrap_or(current_source);
+    // 未修改推荐人时保留原推荐人（来源不再是转介绍时清除）
+    let new_referrer = if clear_referrer {
+        None
+    } else {
+        referrer_uid.or(current_referrer)
+    }
+    .filter(|_| new_source == LeadSource::Referral);
+    validate_lead_referrer(new_source, referrer_uid)?;
+
+    write_store(|ext| {
+        let lead = ext.leads.get_mut(&lead_id).ok_or("潜在客户不存在")?;
+        if let Some(name) = name {
+            lead.name = name.trim().to_string();
+        }
+        if let Some(phone) = phone {
+            lead.phone = phone.trim().to_string();
+        }
+        if clear_age {
+            lead.age = None;
+        } else if age.is_some() {
+            lead.age = age;
+        }
+        lead.source = new_source;
+        lead.referrer_uid = new_referrer;
+        Ok(convert_lead_to_response(lead))
+    })
+}
+
+// 潜在客户 - 添加跟进记录，可同时修改跟进状态
+#[tauri::command]
+fn add_lead_follow_up(
+    lead_id: u64,
+    note: Option<String>,
+    status: Option<String>,
+    operator: Option<String>,
+) -> Result<LeadResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+    let status = status.as_deref().map(parse_lead_status).transpose()?;
+    if note.is_none() && status.is_none() {
+        return Err("跟进记录或状态至少需要填写一项".to_string());
+    }
+    if let Some(operator_str) = &operator {
+        validate_operator(operator_str)?;
+    }
+
+    write_store(|ext| {
+        let lead = ext.leads.get_mut(&lead_id).ok_or("潜在客户不存在")?;
+        if lead.status == LeadStatus::Converted {
+            return Err("客户已转为学员，无法修改跟进状态".to_string());
+        }
+        let status = status.unwrap_or(lead.status);
+        lead.follow_up(
+            status,
+            note,
+            operator.map(|o| o.trim().to_string()),
+            Utc::now(),
+        );
+        log::info!("成功添加跟进记录 - 客户ID: {}, 状态: {:?}", lead_id, status);
+        Ok(convert_lead_to_response(lead))
+    })
+}
+
+// 潜在客户 - 删除客户（已转为学员的客户保留历史，不能删除）
+#[tauri::command]
+fn delete_lead(lead_id: u64) -> Result<(), String> {
+    init_manager()?;
+
+    write_store(|ext| {
+        let lead = ext.leads.get(&lead_id).ok_or("潜在客户不存在")?;
+        if lead.status == LeadStatus::Converted {
+            return Err("客户已转为学员，不能删除".to_string());
+        }
+        ext.leads.remove(&lead_id);
+        Ok(())
+    })?;
+
+    log::info!("成功删除潜在客户 - ID: {}", lead_id);
+    Ok(())
+}
+
+// 潜在客户 - 获取客户列表（可按状态筛选，最新登记的在前）
+#[tauri::command]
+fn get_all_leads(status: Option<String>) -> Result<Vec<LeadResponse>, String> {
+    init_manager()?;
+
+    let status_filter = status
+        .as_deref()
+        .map(|s| match s {
+            "Converted" => Ok(LeadStatus::Converted),
+            other => parse_lead_status(other),
+        })
+        .transpose()?;
+
+    read_store(|ext| {
+        let mut leads: Vec<&Lead> = ext
+            .leads
+            .values()
+            .filter(|l| status_filter.is_none_or(|s| l.status == s))
+            .collect();
+        leads.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        leads.into_iter().map(convert_lead_to_response).collect()
+    })
+}
+
+// 潜在客户 - 转为正式学员（保留客户及跟进历史）
+#[tauri::command]
+fn convert_lead_to_student(
+    lead_id: u64,
+    class_type: String,
+    subject: String,
+    note: Option<String>,
+    operator: Option<String>,
+) -> Result<StudentResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    if let Some(operator_str) = &operator {
+        validate_operator(operator_str)?;
+    }
+    let lead = read_store(|ext| ext.leads.get(&lead_id).cloned())?.ok_or("潜在客户不存在")?;
+    if lead.status == LeadStatus::Converted {
+        return Err("客户已转为学员".to_string());
+    }
+
+    // 复用新增学员流程（含输入验证及推荐奖励）
+    let student = add_student(
+        lead.name.clone(),
+        lead.age,
+        class_type,
+        lead.phone.clone(),
+        note.unwrap_or_default(),
+        subject,
+        lead.referrer_uid,
+    )?;
+
+    write_store(|ext| {
+        let lead = ext.leads.get_mut(&lead_id).ok_or("潜在客户不存在")?;
+        let now = Utc::now();
+        lead.student_uid = Some(student.uid);
+        lead.converted_at = Some(now);
+        lead.follow_up(
+            LeadStatus::Converted,
+            Some(format!("转为学员 (UID: {})", student.uid)),
+            operator.map(|o| o.trim().to_string()),
+            now,
+        );
+        Ok(())
+    })?;
+
+    log::info!(
+        "成功将潜在客户转为学员 - 客户ID: {}, 学生UID: {}",
+        lead_id,
+        student.uid
+    );
+    Ok(student)
+}
+
+// 潜在客户 - 转化率统计（按登记时间统计区间内的客户）
+#[tauri::command]
+fn get_lead_conversion_stats(
+    period: Option<String>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<LeadConversionStatsResponse, String> {
+    init_manager()?;
+
+    let (start_day, end_day) =
+        report_local_range(period.as_deref(), date_from.as_deref(), date_to.as_deref())?;
+    let start = local_day_start(start_day);
+    let end = local_day_end(end_day);
+
+    read_store(|ext| {
+        let mut by_status: BTreeMap<String, usize> = BTreeMap::new();
+        let mut by_source: BTreeMap<String, LeadSourceStats> = BTreeMap::new();
+        let mut total_leads = 0;
+        let mut converted_leads = 0;
+        let mut total_days_to_convert = 0;
+
+        for lead in ext
+            .leads
+            .values()
+            .filter(|l| l.created_at >= start && l.created_at <= end)
+        {
+            total_leads += 1;
+            *by_status.entry(format!("{:?}", lead.status)).or_default() += 1;
+            let source = by_source
+                .entry(format!("{:?}", lead.source))
+                .or_insert_with(|| LeadSourceStats {
+                    total_leads: 0,
+                    converted_leads: 0,
+                    conversion_rate: 0.0,
+                });
+            source.total_leads += 1;
+            if let Some(converted_at) = lead.converted_at {
+                converted_leads += 1;
+                source.converted_leads += 1;
+                total_days_to_convert += local_days_between(&lead.created_at, &converted_at);
+            }
+        }
+
+        let rate = |converted: usize, total: usize| {
+            if total == 0 {
+                0.0
+            } else {
+                converted as f64 / total as f64 * 100.0
+            }
+        };
+        for source in by_source.values_mut() {
+            source.conversion_rate = rate(source.converted_leads, source.total_leads);
+        }
+
+        LeadConversionStatsResponse {
+            period_start_date: start_day.format("%Y-%m-%d").to_string(),
+            period_end_date: end_day.format("%Y-%m-%d").to_string(),
+            total_leads,
+            converted_leads,
+            conversion_rate: rate(converted_leads, total_leads),
+            average_days_to_convert: if converted_leads == 0 {
+                0.0
+            } else {
+                total_days_to_convert as f64 / converted_leads as f64
+            },
+            by_status,
+            by_source,
+        }
+    })
+}
+
+// 自定义字段 - 新增字段定义
+#[tauri::command]
+fn create_custom_field(
+    key: String,
+    label: String,
+    field_type: String, // "Text" | "Number" | "Date" | "Enum"
+    required: Option<bool>,
+    options: Option<Vec<String>>,
+    min: Option<f64>,
+    max: Option<f64>,
+    max_length: Option<usize>,
+) -> Result<CustomFieldResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_custom_field_key(&key)?;
+    validate_custom_field_label(&label)?;
+    let field_type = parse_custom_field_type(&field_type)?;
+    let options: Vec<String> = options
+        .unwrap_or_default()
+        .iter()
+        .map(|o| o.trim().to_string())
+        .collect();
+    validate_custom_field_rules(field_type, &options, min, max, max_length)?;
+
+    write_store(|ext| {
+        let key = key.trim().to_string();
+        if ext.custom_field_by_key(&key).is_some() {
+            return Err(format!("字段标识已存在: {}", key));
+        }
+        let field = CustomFieldDef {
+            id: ext.alloc_id(),
+            key,
+            label: label.trim().to_string(),
+            field_type,
+            required: required.unwrap_or(false),
+            options,
+            min,
+            max,
+            max_length,
+            created_at: Utc::now(),
+        };
+        log::info!("成功新增自定义字段: {} (ID: {})", field.key, field.id);
+        let response = convert_custom_field_to_response(&field);
+        ext.custom_fields.insert(field.id, field);
+        Ok(response)
+    })
+}
+
+// 自定义字段 - 修改字段定义（类型和标识不可修改，已保存的值不重新校验）
+//
+// clear_min / clear_max / clear_max_length 为 true 时取消对应限制，不能与新值同时传入
+#[tauri::command]
+fn update_custom_field(
+    field_id: u64,
+    label: Option<String>,
+    required: Option<bool>,
+    options: Option<Vec<String>>,
+    min: Option<f64>,
+    clear_min: Option<bool>,
+    max: Option<f64>,
+    clear_max: Option<bool>,
+    max_length: Option<usize>,
+    clear_max_length: Option<bool>,
+) -> Result<CustomFieldResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    if let Some(label_str) = &label {
+        validate_custom_field_label(label_str)?;
+    }
+    let clear_min = clear_min.unwrap_or(false);
+    let clear_max = clear_max.unwrap_or(false);
+    let clear_max_length = clear_max_length.unwrap_or(false);
+    if (clear_min && min.is_some())
+        || (clear_max && max.is_some())
+        || (clear_max_length && max_length.is_some())
+    {
+        return Err("不能同时修改和取消同一项限制".to_string());
+    }
+    let options: Option<Vec<String>> =
+        options.map(|list| list.iter().map(|o| o.trim().to_string()).collect());
+
+    write_store(|ext| {
+        let field = ext.custom_fields.get(&field_id).ok_or("自定义字段不存在")?;
+        let options = options.unwrap_or_else(|| field.options.clone());
+        let min = if clear_min { None } else { min.or(field.min) };
+        let max = if clear_max { None } else { max.or(field.max) };
+        let max_length = if clear_max_length {
+            None
+        } else {
+            max_length.or(field.max_length)
+        };
+        validate_custom_field_rules(field.field_type, &options, min, max, max_length)?;
+
+        let field = ext
+            .custom_fields
+            .get_mut(&field_id)
+            .ok_or("自定义字段不存在")?;
+        if let Some(label) = label {
+            field.label = label.trim().to_string();
+        }
+        if let Some(required) = required {
+            field.required = required;
+        }
+        field.options = options;
+        field.min = min;
+        field.max = max;
+        field.max_length = max_length;
+        Ok(convert_custom_field_to_response(field))
+    })
+}
+
+// 自定义字段 - 删除字段定义及所有学员的字段值
+#[tauri::command]
+fn delete_custom_field(field_id: u64) -> Result<(), String> {
+    init_manager()?;
+
+    write_store(|ext| {
+        ext.custom_fields
+            .remove(&field_id)
+            .ok_or("自定义字段不存在")?;
+        for values in ext.student_custom_fields.values_mut() {
+            values.remove(&field_id);
+        }
+        ext.student_custom_fields
+            .retain(|_, values| !values.is_empty());
+        Ok(())
+    })?;
+    invalidate_index();
+
+    log::info!("成功删除自定义字段 - ID: {}", field_id);
+    Ok(())
+}
+
+// 自定义字段 - 获取全部字段定义（按创建顺序）
+#[tauri::command]
+fn get_custom_fields() -> Result<Vec<CustomFieldResponse>, String> {
+    init_manager()?;
+
+    read_store(|ext| {
+        let mut fields: Vec<&CustomFieldDef> = ext.custom_fields.values().collect();
+        fields.sort_by_key(|f| f.id);
+        fields
+            .into_iter()
+            .map(convert_custom_field_to_response)
+            .collect()
+    })
+}
+
+// 标签 - 为学员添加标签
+#[tauri::command]
+fn add_tags(student_uid: u64, tags: Vec<String>) -> Result<Vec<String>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+    let tags = normalize_tags(tags);
+    if tags.is_empty() {
+        return Err("标签不能为空".to_string());
+    }
+    validate_tags(&tags)?;
+    get_manager()?
+        .get_student(student_uid)
+        .map_err(|e| format!("获取学生失败: {}", e))?
+        .ok_or("学员不存在")?;
+
+    let result = write_store(|ext| {
+        let mut student_tags = ext
+            .student_tags
+            .get(&student_uid)
+            .cloned()
+            .unwrap_or_default();
+        student_tags.extend(tags);
+        if student_tags.len() > MAX_TAGS_PER_STUDENT {
+            return Err(format!("每个学员最多{}个标签", MAX_TAGS_PER_STUDENT));
+        }
+        let result = student_tags.iter().cloned().collect();
+        ext.student_tags.insert(student_uid, student_tags);
+        Ok(result)
+    })?;
+    refresh_search_index(&[student_uid]);
+    Ok(result)
+}
+
+// 标签 - 移除学员的标签
+#[tauri::command]
+fn remove_tags(student_uid: u64, tags: Vec<String>) -> Result<Vec<String>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+    let tags = normalize_tags(tags);
+
+    let result = write_store(|ext| {
+        let Some(student_tags) = ext.student_tags.get_mut(&student_uid) else {
+            return Ok(Vec::new());
+        };
+        student_tags.retain(|t| !tags.contains(t));
+        let result = student_tags.iter().cloned().collect();
+        if student_tags.is_empty() {
+            ext.student_tags.remove(&student_uid);
+        }
+        Ok(result)
+    })?;
+    refresh_search_index(&[student_uid]);
+    Ok(result)
+}
+
+// 标签 - 获取所有标签及使用人数
+#[tauri::command]
+fn get_all_tags() -> Result<Vec<TagCountResponse>, String> {
+    init_manager()?;
+
+    read_store(|ext| {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for tags in ext.student_tags.values() {
+            for tag in tags {
+                *counts.entry(tag.as_str()).or_default() += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .map(|(tag, student_count)| TagCountResponse {
+                tag: tag.to_string(),
+                student_count,
+            })
+            .collect()
+    })
+}
+
+// 学员分组 - 保存筛选条件为分组（保存前先执行一次以校验条件）
+#[tauri::command]
+fn create_segment(
+    name: String,
+    criteria: StudentSearchCriteria,
+) -> Result<SegmentResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_segment_name(&name)?;
+    run_student_search(&criteria)?;
+
+    write_store(|ext| {
+        let name = name.trim().to_string();
+        if ext.segments.values().any(|s| s.name == name) {
+            return Err(format!("分组名称已存在: {}", name));
+        }
+        let now = Utc::now();
+        let segment = Segment {
+            id: ext.alloc_id(),
+            name,
+            criteria,
+            created_at: now,
+            updated_at: now,
+        };
+        log::info!("成功保存学员分组: {} (ID: {})", segment.name, segment.id);
+        let response = convert_segment_to_response(&segment);
+        ext.segments.insert(segment.id, segment);
+        Ok(response)
+    })
+}
+
+// 学员分组 - 修改分组名称或筛选条件
+#[tauri::command]
+fn update_segment(
+    segment_id: u64,
+    name: Option<String>,
+    criteria: Option<StudentSearchCriteria>,
+) -> Result<SegmentResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    if let Some(name_str) = &name {
+        validate_segment_name(name_str)?;
+    }
+    if let Some(criteria) = &criteria {
+        run_student_search(criteria)?;
+    }
+
+    write_store(|ext| {
+        let name = name.map(|n| n.trim().to_string());
+        if let Some(name) = &name {
+            if ext
+                .segments
+                .values()
+                .any(|s| s.id != segment_id && &s.name == name)
+            {
+                return Err(format!("分组名称已存在: {}", name));
+            }
+        }
+        let segment = ext.segments.get_mut(&segment_id).ok_or("学员分组不存在")?;
+        if let Some(name) = name {
+            segment.name = name;
+        }
+        if let Some(criteria) = criteria {
+            segment.criteria = criteria;
+        }
+        segment.updated_at = Utc::now();
+        Ok(convert_segment_to_response(segment))
+    })
+}
+
+// 学员分组 - 删除分组（不影响学员）
+#[tauri::command]
+fn delete_segment(segment_id: u64) -> Result<(), String> {
+    init_manager()?;
+
+    write_store(|ext| {
+        ext.segments.remove(&segment_id).ok_or("学员分组不存在")?;
+        Ok(())
+    })?;
+
+    log::info!("成功删除学员分组 - ID: {}", segment_id);
+    Ok(())
+}
+
+// 学员分组 - 获取全部分组
+#[tauri::command]
+fn get_segments() -> Result<Vec<SegmentResponse>, String> {
+    init_manager()?;
+
+    read_store(|ext| {
+        let mut segments: Vec<&Segment> = ext.segments.values().collect();
+        segments.sort_by(|a, b| a.name.cmp(&b.name));
+        segments
+            .into_iter()
+            .map(convert_segment_to_response)
+            .collect()
+    })
+}
+
+// 学员分组 - 按保存的条件重新查询学员
+#[tauri::command]
+fn run_segment(segment_id: u64) -> Result<Vec<StudentResponse>, String> {
+    init_manager()?;
+
+    let segment =
+        read_store(|ext| ext.segments.get(&segment_id).cloned())?.ok_or("学员分组不存在")?;
+    let students = run_student_search(&segment.criteria)?;
+
+    read_store(|ext| {
+        students
+            .iter()
+            .map(|student| convert_student_to_response(student, ext))
+            .collect()
+    })
+}
+
+// 收据 - 为一笔收入生成PDF收据
+//
+// 收据编号按业务日期所在年份连续编号，同一笔记录再次生成时为补打，沿用原编号；
+// 未指定 output_path 时保存到 ./data/receipts/<收据编号>.pdf
+#[tauri::command]
+fn generate_receipt(
+    transaction_uid: u64,
+    output_path: Option<String>,
+) -> Result<ReceiptResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_transaction_uid(transaction_uid)?;
+    if let Some(path) = &output_path {
+        validate_pdf_path(path)?;
+    }
+
+    let manager = get_manager()?;
+    let cash = manager
+        .get_cash(transaction_uid)
+        .map_err(|e| format!("获取交易记录失败: {}", e))?
+        .ok_or("交易记录不存在")?;
+    if cash.cash <= 0 {
+        return Err("只能为收入记录开具收据".to_string());
+    }
+    if !is_cash_settled(&cash) {
+        return Err("分期尚未付款，不能开具收据".to_string());
+    }
+    let student_name = match cash.student_id {
+        Some(uid) => manager
+            .get_student(uid)
+            .map_err(|e| format!("获取学生信息失败: {}", e))?
+            .map(|s| s.name().to_string()),
+        None => None,
+    };
+
+    // 收据编号在PDF写入成功后才保存，写入失败不占用编号
+    let (year, receipt, transaction, club_name, logo_path) = read_store(|ext| {
+        let year = ext
+            .cash_meta
+            .get(&transaction_uid)
+            .map(|m| m.business_date.year())
+            .unwrap_or_else(|| club_today().year());
+        (
+            year,
+            ext.preview_receipt(transaction_uid, year),
+            convert_cash_to_response(&cash, ext),
+            ext.settings.club_name.clone(),
+            ext.settings.logo_path.clone(),
+        )
+    })?;
+
+    let printed_at = to_local_datetime_string(&Utc::now());
+    let content = render_receipt(
+        load_club_profile(club_name, logo_path),
+        &receipt,
+        student_name.as_deref(),
+        &transaction,
+        &printed_at,
+    );
+    let path = match output_path {
+        Some(path) => std::path::PathBuf::from(path.trim()),
+        None => std::path::Path::new("./data/receipts").join(format!("{}.pdf", receipt.number)),
+    };
+    write_pdf_file(&path, &content)?;
+    if let Err(e) = write_store(|ext| ext.issue_receipt(transaction_uid, year, &receipt)) {
+        let _ = std::fs::remove_file(&path);
+        return Err(e);
+    }
+
+    log::info!(
+        "成功生成收据 - 交易UID: {}, 编号: {}, 补打次数: {}",
+        transaction_uid,
+        receipt.number,
+        receipt.reprint_count
+    );
+    Ok(ReceiptResponse {
+        transaction_uid,
+        receipt_number: receipt.number,
+        reprint_count: receipt.reprint_count,
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+// 收据 - 批量生成学员月度对账单（每名学员一个PDF）
+//
+// month 格式为 YYYY-MM，按业务日期筛选（本功能之前录入、没有业务日期的记录不计入），
+// 未付款或已取消的分期不计入；
+// 未指定学员时为当月有交易的全部学员；未指定 output_dir 时保存到 ./data/statements/<月份>/
+#[tauri::command]
+fn generate_monthly_statements(
+    month: String,
+    student_uids: Option<Vec<u64>>,
+    output_dir: Option<String>,
+) -> Result<Vec<MonthlyStatementResponse>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    let month = month.trim().to_string();
+    let month_start = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| format!("月份格式应为YYYY-MM: {}", month))?;
+    let month_end = month_start
+        .checked_add_months(Months::new(1))
+        .and_then(|d| d.pred_opt())
+        .ok_or("月份超出范围")?;
+    if let Some(uids) = &student_uids {
+        for uid in uids {
+            validate_student_uid(*uid)?;
+        }
+    }
+    let output_dir = match output_dir.map(|d| d.trim().to_string()) {
+        Some(dir) if dir.is_empty() => return Err("输出目录不能为空".to_string()),
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => std::path::Path::new("./data/statements").join(&month),
+    };
+
+    let manager = get_manager()?;
+    let cash_list = manager
+        .search_cash(CashQuery::new())
+        .map_err(|e| format!("获取现金记录失败: {}", e))?;
+
+    let (statements, receipt_numbers, club_name, logo_path) = read_store(|ext| {
+        let mut statements: BTreeMap<u64, Vec<TransactionResponse>> = BTreeMap::new();
+        if let Some(uids) = &student_uids {
+            for uid in uids {
+                statements.entry(*uid).or_default();
+            }
+        }
+        for cash in &cash_list {
+            let Some(student_uid) = cash.student_id else {
+                continue;
+            };
+            if !is_cash_settled(cash) {
+                continue;
+            }
+            let in_month = ext
+                .cash_meta
+                .get(&cash.uid)
+                .is_some_and(|m| m.business_date >= month_start && m.business_date <= month_end);
+            if !in_month {
+                continue;
+            }
+            if student_uids.is_none() || statements.contains_key(&student_uid) {
+                statements
+                    .entry(student_uid)
+                    .or_default()
+                    .push(convert_cash_to_response(cash, ext));
+            }
+        }
+        let receipt_numbers: HashMap<u64, String> = ext
+            .receipts
+            .iter()
+            .map(|(uid, receipt)| (*uid, receipt.number.clone()))
+            .collect();
+        (
+            statements,
+            receipt_numbers,
+            ext.settings.club_name.clone(),
+            ext.settings.logo_path.clone(),
+        )
+    })?;
+
+    let printed_at = to_local_datetime_string(&Utc::now());
+    let mut results = Vec::new();
+    for (student_uid, mut transactions) in statements {
+        let student_name = match manager
+            .get_student(student_uid)
+            .map_err(|e| format!("获取学生信息失败: {}", e))?
+        {
+            Some(student) => student.name().to_string(),
+            None if student_uids.is_some() => {
+                return Err(format!("学生不存在: {}", student_uid));
+            }
+            None => "已删除学员".to_string(),
+        };
+        transactions.sort_by(|a, b| (&a.business_date, a.uid).cmp(&(&b.business_date, b.uid)));
+        let lines: Vec<StatementLine> = transactions
+            .iter()
+            .map(|transaction| StatementLine {
+                transaction,
+                receipt_number: receipt_numbers.get(&transaction.uid).cloned(),
+            })
+            .collect();
+
+        let content = render_statement(
+            load_club_profile(club_name.clone(), logo_path.clone()),
+            &month,
+            &student_name,
+            student_uid,
+            &lines,
+            &printed_at,
+        );
+        let path = output_dir.join(format!("{}-{}.pdf", month, student_uid));
+        write_pdf_file(&path, &content)?;
+
+        results.push(MonthlyStatementResponse {
+            student_uid,
+            student_name,
+            path: path.to_string_lossy().to_string(),
+            transaction_count: transactions.len(),
+            total: transactions.iter().map(|t| t.amount).sum(),
+        });
+    }
+
+    log::info!("成功生成{}的月度对账单{}份", month, results.len());
+    Ok(results)
+}
+
+// 财务报表 - 生成月度/年度财务报表并导出PDF和XLSX
+//
+// period 为 YYYY-MM（月报）或 YYYY（年报）；formats 可选 pdf、xlsx，默认两种都导出；
+// 未指定 output_dir 时保存到 ./data/reports/
+#[tauri::command]
+fn generate_financial_report(
+    period: String,
+    formats: Option<Vec<String>>,
+    output_dir: Option<String>,
+) -> Result<FinancialReportResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    let report_period = ReportPeriod::parse(&period)?;
+    let formats = formats.unwrap_or_else(|| vec!["pdf".to_string(), "xlsx".to_string()]);
+    for format in &formats {
+        if !matches!(format.as_str(), "pdf" | "xlsx") {
+            return Err(format!("无效的导出格式: {}", format));
+        }
+    }
+    let output_dir = match output_dir.map(|d| d.trim().to_string()) {
+        Some(dir) if dir.is_empty() => return Err("输出目录不能为空".to_string()),
+        Some(dir) => std::path::PathBuf::from(dir),
+        None => std::path::PathBuf::from("./data/reports"),
+    };
+
+    let (start_day, end_day) = report_period.range()?;
+    let (previous_start, previous_end) = report_period.previous()?.range()?;
+    let current_cash = cash_in_local_range(start_day, end_day)?;
+    let previous_cash = cash_in_local_range(previous_start, previous_end)?;
+
+    let manager = get_manager()?;
+    let students: HashMap<u64, (String, String)> = manager
+        .list_students()
+        .map_err(|e| format!("获取学生列表失败: {}", e))?
+        .iter()
+        .map(|s| {
+            (
+                s.uid(),
+                (format!("{:?}", s.class()), format!("{:?}", s.subject())),
+            )
+        })
+        .collect();
+
+    let to_entries = |cash_list: &[qmx_backend_lib::cash::Cash], ext: &ExtStore| {
+        cash_list
+            .iter()
+            .filter(|cash| is_cash_settled(cash))
+            .map(|cash| {
+                let meta = ext.cash_meta.get(&cash.uid);
+                let student = cash.student_id.and_then(|uid| students.get(&uid));
+                ReportEntry {
+                    amount: cash.cash,
+                    has_student: cash.student_id.is_some(),
+                    category: meta.and_then(|m| m.category.clone()),
+                    payment_method: meta.and_then(|m| m.payment_method.clone()),
+                    class: student.map(|(class, _)| class.clone()),
+                    subject: student.map(|(_, subject)| subject.clone()),
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    let (entries, previous_entries, club_name, logo_path) = read_store(|ext| {
+        (
+            to_entries(&current_cash, ext),
+            to_entries(&previous_cash, ext),
+            ext.settings.club_name.clone(),
+            ext.settings.logo_path.clone(),
+        )
+    })?;
+
+    let today = club_today();
+    let mut receivables = ReceivableSummary::default();
+    let installments = manager
+        .search_cash(CashQuery::new().has_installment(true))
+        .map_err(|e| format!("获取分期记录失败: {}", e))?;
+    for cash in &installments {
+        let Some(installment) = &cash.installment else {
+            continue;
+        };
+        if !matches!(
+            installment.status,
+            InstallmentStatus::Pending | InstallmentStatus::Overdue
+        ) {
+            continue;
+        }
+        receivables.outstanding_amount += cash.cash;
+        receivables.outstanding_count += 1;
+        if is_installment_overdue(installment, today) {
+            receivables.overdue_amount += cash.cash;
+            receivables.overdue_count += 1;
+        }
+        let due_day = to_local_date(&installment.due_date);
+        if due_day >= start_day && due_day <= end_day {
+            receivables.due_in_period_amount += cash.cash;
+            receivables.due_in_period_count += 1;
+        }
+    }
+
+    let report = build_report(report_period, &entries, &previous_entries, receivables)?;
+    let file_stem = format!("financial-report-{}", report.period);
+    let mut pdf_path = None;
+    let mut xlsx_path = None;
+    if formats.iter().any(|f| f == "pdf") {
+        let content = render_pdf(
+            &report,
+            load_club_profile(club_name, logo_path),
+            &to_local_datetime_string(&Utc::now()),
+        );
+        let path = output_dir.join(format!("{}.pdf", file_stem));
+        write_pdf_file(&path, &content)?;
+        pdf_path = Some(path.to_string_lossy().to_string());
+    }
+    if formats.iter().any(|f| f == "xlsx") {
+        let content = render_xlsx(&report)?;
+        let path = output_dir.join(format!("{}.xlsx", file_stem));
+        std::fs::create_dir_all(&output_dir).map_err(|e| format!("创建输出目录失败: {}", e))?;
+        std::fs::write(&path, content).map_err(|e| format!("写入XLSX文件失败: {}", e))?;
+        xlsx_path = Some(path.to_string_lossy().to_string());
+    }
+
+    log::info!("成功生成财务报表 - 期间: {}", report.period);
+    Ok(FinancialReportResponse {
+        report,
+        pdf_path,
+        xlsx_path,
+        timezone: club_timezone().name().to_string(),
+    })
+}
+
+// 收支趋势 - 按日/周/月汇总收入、支出和净收入，没有交易的时间段补零
+//
+// granularity 可选 Day、Week（周一开始）、Month；split_by 可选 category（收支类别）或
+// subject（学员科目）。区间规则同其他报表，默认本月；未付和已取消的分期不计入
+#[tauri::command]
+fn get_revenue_timeseries(
+    granularity: String,
+    period: Option<String>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+    split_by: Option<String>,
+) -> Result<RevenueTimeseriesResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    let granularity = parse_granularity(&granularity)?;
+    if let Some(split) = &split_by {
+        if !matches!(split.as_str(), "category" | "subject") {
+            return Err(format!("无效的拆分维度: {}", split));
+        }
+    }
+    let (start_day, end_day) =
+        report_local_range(period.as_deref(), date_from.as_deref(), date_to.as_deref())?;
+    let buckets = granularity.buckets(start_day, end_day)?;
+
+    let manager = get_manager()?;
+    let cash_list: Vec<_> = cash_in_local_range(start_day, end_day)?
+        .into_iter()
+        .filter(is_cash_settled)
+        .collect();
+    let subjects: HashMap<u64, String> = if split_by.as_deref() == Some("subject") {
+        manager
+            .list_students()
+            .map_err(|e| format!("获取学生列表失败: {}", e))?
+            .iter()
+            .map(|s| (s.uid(), format!("{:?}", s.subject())))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let dates = cash_local_dates(&cash_list, &buckets)?;
+    let categories: HashMap<u64, String> = read_store(|ext| {
+        cash_list
+            .iter()
+            .filter_map(|cash| {
+                let category = ext.cash_meta.get(&cash.uid)?.category.clone()?;
+                Some((cash.uid, category))
+            })
+            .collect()
+    })?;
+
+    let entries: Vec<SeriesEntry> = cash_list
+        .iter()
+        .filter_map(|cash| {
+            Some(SeriesEntry {
+                date: *dates.get(&cash.uid)?,
+                amount: cash.cash,
+                split_key: match split_by.as_deref() {
+                    Some("category") => categories.get(&cash.uid).cloned(),
+                    Some("subject") => cash.student_id.and_then(|uid| subjects.get(&uid).cloned()),
+                    _ => None,
+                },
+            })
+        })
+        .collect();
+    let split = match split_by.as_deref() {
+        Some("category") => Some(SplitLabels {
+            label_of: cash_category_label,
+            missing: "未分类",
+        }),
+        Some("subject") => Some(SplitLabels {
+            label_of: subject_label,
+            missing: "无学员",
+        }),
+        _ => None,
+    };
+    let (series, totals) = build_series(&buckets, &entries, split.as_ref());
+
+    Ok(RevenueTimeseriesResponse {
+        granularity: format!("{:?}", granularity),
+        split_by,
+        buckets: series,
+        totals,
+        period_start_date: start_day.format("%Y-%m-%d").to_string(),
+        period_end_date: end_day.format("%Y-%m-%d").to_string(),
+        timezone: club_timezone().name().to_string(),
+    })
+}
+
+// 收入确认 - 按月统计确认收入与待确认收入（权责发生制）
+//
+// month_from / month_to 格式为 YYYY-MM，默认为今年1月至本月，最多120个月
+#[tauri::command]
+fn get_revenue_recognition(
+    month_from: Option<String>,
+    month_to: Option<String>,
+) -> Result<RevenueRecognitionResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    let parse_month = |month: &str| {
+        NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+            .map_err(|_| format!("月份格式应为YYYY-MM: {}", month))
+    };
+    let today = club_today();
+    let first_month = match &month_from {
+        Some(month) => parse_month(month)?,
+        None => NaiveDate::from_ymd_opt(today.year(), 1, 1).ok_or("日期超出范围")?,
+    };
+    let last_month = match &month_to {
+        Some(month) => parse_month(month)?,
+        None => today.with_day(1).ok_or("日期超出范围")?,
+    };
+    if first_month > last_month {
+        return Err("开始月份不能晚于结束月份".to_string());
+    }
+    let last_day = last_month
+        .checked_add_months(Months::new(1))
+        .and_then(|d| d.pred_opt())
+        .ok_or("日期超出范围")?;
+    let months = Granularity::Month.buckets(first_month, last_day)?;
+    if months.len() > 120 {
+        return Err("统计区间不能超过120个月".to_string());
+    }
+
+    let income: Vec<qmx_backend_lib::cash::Cash> = get_manager()?
+        .search_cash(CashQuery::new())
+        .map_err(|e| format!("获取现金记录失败: {}", e))?
+        .into_iter()
+        .filter(|cash| cash.cash > 0 && is_cash_settled(cash))
+        .collect();
+    let dates = cash_local_dates(&income, &months)?;
+    let payments = collect_deferred_payments(&income, &dates)?;
+    let deferred_uids: HashSet<u64> = payments.iter().map(|p| p.cash_uid).collect();
+    // 普通课时费没有与付款对应的课时消耗记录，单独列出，不混入其他收入
+    let lesson_pack_uids: HashSet<u64> = read_store(|ext| {
+        income
+            .iter()
+            .filter(|cash| {
+                ext.cash_meta
+                    .get(&cash.uid)
+                    .is_some_and(|m| m.category.as_deref() == Some("tuition"))
+            })
+            .map(|cash| cash.uid)
+            .collect()
+    })?;
+    let mut lesson_packs_on_receipt: Vec<(NaiveDate, i64)> = Vec::new();
+    let mut other_income: Vec<(NaiveDate, i64)> = Vec::new();
+    for cash in income
+        .iter()
+        .filter(|cash| !deferred_uids.contains(&cash.uid))
+    {
+        let Some(date) = dates.get(&cash.uid) else {
+            continue;
+        };
+        if lesson_pack_uids.contains(&cash.uid) {
+            lesson_packs_on_receipt.push((*date, cash.cash));
+        } else {
+            other_income.push((*date, cash.cash));
+        }
+    }
+
+    Ok(RevenueRecognitionResponse {
+        months: monthly_recognition(&months, &payments, &lesson_packs_on_receipt, &other_income),
+        unscheduled_lesson_pack_count: lesson_packs_on_receipt
+            .iter()
+            .filter(|(date, _)| *date >= first_month && *date <= last_day)
+            .count(),
+        membership_payment_count: payments
+            .iter()
+            .filter(|p| p.kind == DeferredKind::Membership)
+            .count(),
+        lesson_pack_payment_count: payments
+            .iter()
+            .filter(|p| p.kind == DeferredKind::LessonPack)
+            .count(),
+        timezone: club_timezone().name().to_string(),
+    })
+}
+
+// 应收账龄 - 未付分期按逾期天数分段（未到期、1-30、31-60、61-90、90天以上），按学员和合计汇总
+//
+// as_of 为统计日期（YYYY-MM-DD或RFC3339），默认今天；每个学员附带联系电话和监护人，便于催收；
+// 待付款的滞纳金单独列出（不计入账龄分段）
+#[tauri::command]
+fn get_receivables_aging(as_of: Option<String>) -> Result<ReceivablesAgingResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    let as_of_day = match &as_of {
+        Some(input) => to_local_date(&parse_date_input(input)?),
+        None => club_today(),
+    };
+
+    let manager = get_manager()?;
+    let installments = manager
+        .search_cash(CashQuery::new().has_installment(true))
+        .map_err(|e| format!("获取分期记录失败: {}", e))?;
+
+    let mut totals = AgingBuckets::default();
+    let mut by_student: BTreeMap<
+        Option<u64>,
+        (
+            AgingBuckets,
+            Vec<AgingInstallmentResponse>,
+            Vec<LateFeeResponse>,
+        ),
+    > = BTreeMap::new();
+    for cash in &installments {
+        let Some(installment) = &cash.installment else {
+            continue;
+        };
+        if !matches!(
+            installment.status,
+            InstallmentStatus::Pending | InstallmentStatus::Overdue
+        ) {
+            continue;
+        }
+        let due_day = to_local_date(&installment.due_date);
+        let days_past_due = (as_of_day - due_day).num_days();
+        totals.add(days_past_due, cash.cash);
+        let (buckets, items, _) = by_student.entry(cash.student_id).or_default();
+        buckets.add(days_past_due, cash.cash);
+        items.push(AgingInstallmentResponse {
+            transaction_uid: cash.uid,
+            plan_id: installment.plan_id,
+            installment_current: installment.current_installment,
+            installment_total: installment.total_installments,
+            amount: cash.cash,
+            due_local_date: due_day.format("%Y-%m-%d").to_string(),
+            days_past_due: days_past_due.max(0),
+            bucket: aging_bucket(days_past_due).to_string(),
+            status: format!("{:?}", installment.status),
+        });
+    }
+
+    // 待付款的滞纳金是分期的关联应收
+    let mut late_fee_total = 0;
+    let pending_fees: Vec<LateFee> = read_store(|ext| {
+        ext.late_fees
+            .values()
+            .filter(|fee| {
+                fee.status == LateFeeStatus::Pending && to_local_date(&fee.created_at) <= as_of_day
+            })
+            .cloned()
+            .collect()
+    })?;
+    for fee in &pending_fees {
+        late_fee_total += fee.amount;
+        let (_, _, fees) = by_student.entry(fee.student_uid).or_default();
+        fees.push(convert_late_fee_to_response(fee));
+    }
+
+    let mut students = Vec::new();
+    let mut unassigned = None;
+    for (student_uid, (buckets, mut items, mut late_fees)) in by_student {
+        items.sort_by(|a, b| {
+            (&a.due_local_date, a.transaction_uid).cmp(&(&b.due_local_date, b.transaction_uid))
+        });
+        late_fees.sort_by_key(|fee| fee.id);
+        let late_fee_total = late_fees.iter().map(|fee| fee.amount).sum();
+        let Some(student_uid) = student_uid else {
+            unassigned = Some(UnassignedAgingResponse {
+                buckets,
+                installments: items,
+                late_fee_total,
+                late_fees,
+            });
+            continue;
+        };
+        let student = manager
+            .get_student(student_uid)
+            .map_err(|e| format!("获取学生信息失败: {}", e))?;
+        let guardians = read_store(|ext| {
+            ext.student_guardians
+                .get(&student_uid)
+                .map(|links| {
+                    links
+                        .iter()
+                        .filter_map(|link| {
+                            ext.guardians
+                                .get(&link.guardian_id)
+                                .map(|g| convert_student_guardian_to_response(g, link))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        })?;
+        students.push(StudentAgingResponse {
+            student_uid,
+            name: student.as_ref().map(|s| s.name().to_string()),
+            phone: student.as_ref().map(|s| s.phone().to_string()),
+            guardians,
+            buckets,
+            installments: items,
+            late_fee_total,
+            late_fees,
+        });
+    }
+    // 逾期金额大的排在前面
+    students.sort_by(|a, b| {
+        b.buckets
+            .overdue
+            .cmp(&a.buckets.overdue)
+            .then(b.buckets.total.cmp(&a.buckets.total))
+            .then(a.student_uid.cmp(&b.student_uid))
+    });
+
+    Ok(ReceivablesAgingResponse {
+        as_of_date: as_of_day.format("%Y-%m-%d").to_string(),
+        totals,
+        late_fee_total,
+        students,
+        unassigned,
+        timezone: club_timezone().name().to_string(),
+    })
+}
+
+// 滞纳金 - 设置分期计划的滞纳金规则（已生成的滞纳金不受影响）
+#[tauri::command]
+fn set_late_fee_policy(
+    plan_id: u64,
+    kind: String,
+    value: i64,
+    grace_days: Option<u32>,
+    cap: Option<i64>,
+) -> Result<LateFeePolicyResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    let kind_enum = parse_late_fee_kind(&kind)?;
+    validate_late_fee_value(&kind, value)?;
+    let grace_days = grace_days.unwrap_or(0);
+    validate_grace_days(grace_days)?;
+    if let Some(cap) = cap {
+        validate_amount(cap)?;
+        if cap <= 0 {
+            return Err("滞纳金上限必须大于0".to_string());
+        }
+    }
+
+    let manager = get_manager()?;
+    let installments = manager
+        .search_cash(CashQuery::new().has_installment(true))
+        .map_err(|e| format!("获取分期记录失败: {}", e))?;
+    if !installments
+        .iter()
+        .any(|c| c.installment.as_ref().is_some_and(|i| i.plan_id == plan_id))
+    {
+        return Err("分期计划不存在".to_string());
+    }
+
+    let policy = write_store(|ext| {
+        let now = Utc::now();
+        let created_at = ext
+            .late_fee_policies
+            .get(&plan_id)
+            .map_or(now, |p| p.created_at);
+        let policy = LateFeePolicy {
+            kind: kind_enum,
+            value,
+            grace_days,
+            cap,
+            created_at,
+            updated_at: now,
+        };
+        ext.late_fee_policies.insert(plan_id, policy.clone());
+        Ok(policy)
+    })?;
+
+    // 设置后立即为已逾期的分期补生成滞纳金
+    generate_late_fees(&installments, club_today())?;
+
+    log::info!("成功设置滞纳金规则 - 分期计划: {}", plan_id);
+    Ok(convert_late_fee_policy_to_response(plan_id, &policy))
+}
+
+// 滞纳金 - 删除分期计划的滞纳金规则（已生成的滞纳金不受影响）
+#[tauri::command]
+fn remove_late_fee_policy(plan_id: u64) -> Result<(), String> {
+    init_manager()?;
+
+    write_store(|ext| {
+        ext.late_fee_policies
+            .remove(&plan_id)
+            .ok_or("该分期计划没有设置滞纳金规则")?;
+        Ok(())
+    })?;
+
+    log::info!("成功删除滞纳金规则 - 分期计划: {}", plan_id);
+    Ok(())
+}
+
+// 滞纳金 - 获取所有滞纳金规则
+#[tauri::command]
+fn get_late_fee_policies() -> Result<Vec<LateFeePolicyResponse>, String> {
+    init_manager()?;
+
+    read_store(|ext| {
+        let mut policies: Vec<LateFeePolicyResponse> = ext
+            .late_fee_policies
+            .iter()
+            .map(|(plan_id, policy)| convert_late_fee_policy_to_response(*plan_id, policy))
+            .collect();
+        policies.sort_by_key(|p| p.plan_id);
+        policies
+    })
+}
+
+// 滞纳金 - 检查逾期分期并生成滞纳金，返回本次新生成的滞纳金
+// 程序启动和分期被标记为逾期时会自动执行，前端可每天调用一次以补充生成
+#[tauri::command]
+fn apply_late_fees(as_of: Option<String>) -> Result<Vec<LateFeeResponse>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    let today = club_today();
+    let as_of_day = match &as_of {
+        Some(input) => to_local_date(&parse_date_input(input)?),
+        None => today,
+    };
+    if as_of_day > today {
+        return Err("计算日期不能晚于今天".to_string());
+    }
+
+    let created = sweep_late_fees(as_of_day)?;
+    Ok(created.iter().map(convert_late_fee_to_response).collect())
+}
+
+// 滞纳金 - 查询滞纳金，可按学员、分期计划和状态筛选
+#[tauri::command]
+fn get_late_fees(
+    student_uid: Option<u64>,
+    plan_id: Option<u64>,
+    status: Option<String>,
+) -> Result<Vec<LateFeeResponse>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    if let Some(uid) = student_uid {
+        validate_student_uid(uid)?;
+    }
+    let status_enum = status.as_deref().map(parse_late_fee_status).transpose()?;
+
+    read_store(|ext| {
+        let mut fees: Vec<LateFeeResponse> = ext
+            .late_fees
+            .values()
+            .filter(|fee| student_uid.is_none_or(|uid| fee.student_uid == Some(uid)))
+            .filter(|fee| plan_id.is_none_or(|id| fee.plan_id == id))
+            .filter(|fee| status_enum.is_none_or(|s| fee.status == s))
+            .map(convert_late_fee_to_response)
+            .collect();
+        fees.sort_by_key(|fee| fee.id);
+        fees
+    })
+}
+
+// 滞纳金 - 减免待付款的滞纳金，必须填写减免原因
+#[tauri::command]
+fn waive_late_fee(
+    fee_id: u64,
+    reason: String,
+    operator: Option<String>,
+) -> Result<LateFeeResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_waiver_reason(&reason)?;
+    if let Some(operator_str) = &operator {
+        validate_operator(operator_str)?;
+    }
+
+    let fee = write_store(|ext| {
+        let fee = ext.late_fees.get_mut(&fee_id).ok_or("滞纳金不存在")?;
+        if fee.status != LateFeeStatus::Pending {
+            return Err("只能减免待付款的滞纳金".to_string());
+        }
+        fee.status = LateFeeStatus::Waived;
+        fee.waiver = Some(LateFeeWaiver {
+            reason: reason.trim().to_string(),
+            operator: operator.map(|o| o.trim().to_string()),
+            waived_at: Utc::now(),
+        });
+        Ok(fee.clone())
+    })?;
+
+    log::info!("成功减免滞纳金 - ID: {}, 原因: {}", fee.id, reason.trim());
+    Ok(convert_late_fee_to_response(&fee))
+}
+
+// 滞纳金 - 收取滞纳金，记为类别为 late_fee 的收入
+#[tauri::command]
+fn pay_late_fee(
+    fee_id: u64,
+    payment_method: Option<String>,
+    business_date: Option<String>,
+    operator: Option<String>,
+) -> Result<LateFeeResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    if let Some(method) = &payment_method {
+        validate_payment_method(method)?;
+    }
+    let business_date = business_date
+        .as_deref()
+        .map(parse_business_date)
+        .transpose()?;
+    if let Some(operator_str) = &operator {
+        validate_operator(operator_str)?;
+    }
+
+    let fee = read_store(|ext| ext.late_fees.get(&fee_id).cloned())?.ok_or("滞纳金不存在")?;
+    if fee.status != LateFeeStatus::Pending {
+        return Err("只能收取待付款的滞纳金".to_string());
+    }
+
+    let manager = get_manager()?;
+    let mut builder = CashBuilder::new(fee.amount).note(format!(
+        "滞纳金（分期计划 {}，分期记录 {}）",
+        fee.plan_id, fee.installment_uid
+    ));
+    if let Some(student_id) = fee.student_uid {
+        builder = builder.student_id(student_id);
+    }
+    let cash_id = manager
+        .record_cash(builder)
+        .map_err(|e| format!("保存交易记录失败: {}", e))?;
+
+    // 入账期间滞纳金可能已被收取或减免，保存时重新检查，失败则删除刚入账的记录
+    let result = write_store(|ext| {
+        if ext.late_fees.get(&fee_id).map(|f| f.status) != Some(LateFeeStatus::Pending) {
+            return Err("只能收取待付款的滞纳金".to_string());
+        }
+        let meta = ext.record_cash_meta(
+            cash_id,
+            business_date,
+            operator.map(|o| o.trim().to_string()),
+        );
+        meta.category = Some("late_fee".to_string());
+        meta.payment_method = payment_method;
+        let fee = ext.late_fees.get_mut(&fee_id).ok_or("滞纳金不存在")?;
+        fee.status = LateFeeStatus::Paid;
+        fee.payment_uid = Some(cash_id);
+        fee.paid_at = Some(Utc::now());
+        Ok(fee.clone())
+    });
+    let fee = match result {
+        Ok(fee) => fee,
+        Err(e) => {
+            let _ = manager.delete_cash(cash_id);
+            return Err(e);
+        }
+    };
+
+    log::info!(
+        "成功收取滞纳金 - ID: {}, 金额: {}, 交易UID: {}",
+        fee.id,
+        fee.amount,
+        cash_id
+    );
+    Ok(convert_late_fee_to_response(&fee))
+}
+
+// 分期 - 调整分期计划：修改付款频率、顺延未付分期的到期日，或重新设定剩余期数
+// 修改频率时从第一期未付分期起按新频率重新排列到期日；重新设定剩余期数时，
+// 未付余额（总金额减去已付金额和已给予的减免）平均分配到各期，余数计入最后一期，多出的未付分期取消
+#[tauri::command]
+fn reschedule_installment_plan(
+    plan_id: u64,
+    frequency: Option<String>,
+    shift_days: Option<i64>,
+    remaining_installments: Option<u32>,
+    operator: Option<String>,
+    reason: Option<String>,
+) -> Result<Vec<TransactionResponse>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_plan_id(plan_id)?;
+    let frequency = frequency
+        .as_deref()
+        .map(parse_payment_frequency)
+        .transpose()?;
+    if let Some(days) = shift_days {
+        validate_shift_days(days)?;
+    }
+    if let Some(count) = remaining_installments {
+        validate_installment_count(count)?;
+    }
+    if let Some(operator_str) = &operator {
+        validate_operator(operator_str)?;
+    }
+    if let Some(reason_str) = &reason {
+        validate_note(reason_str)?;
+    }
+    if frequency.is_none() && shift_days.is_none() && remaining_installments.is_none() {
+        return Err("请至少指定一项调整内容".to_string());
+    }
+
+    let manager = get_manager()?;
+    let plan = find_plan_installments(&manager, plan_id)?;
+    let active: Vec<_> = plan
+        .iter()
+        .filter(|(_, i)| i.status != InstallmentStatus::Cancelled)
+        .collect();
+    let (first_cash, last) = match (active.first(), active.last()) {
+        (Some((cash, _)), Some((_, installment))) => (cash, installment),
+        _ => return Err("分期计划已取消".to_string()),
+    };
+    let unpaid: Vec<_> = active
+        .iter()
+        .filter(|(_, i)| {
+            matches!(
+                i.status,
+                InstallmentStatus::Pending | InstallmentStatus::Overdue
+            )
+        })
+        .collect();
+    if unpaid.is_empty() && remaining_installments.is_none() {
+        return Err("分期计划没有待付款的分期".to_string());
+    }
+    let frequency_enum = frequency.unwrap_or(last.frequency);
+
+    // 未付分期的新到期日
+    let mut due_dates: Vec<DateTime<Utc>> = Vec::new();
+    for (index, (_, installment)) in unpaid.iter().enumerate() {
+        let due = match (frequency, due_dates.first()) {
+            (Some(_), Some(first)) => advance_due_date(*first, frequency_enum, index as u32)?,
+            _ => installment.due_date + Duration::days(shift_days.unwrap_or(0)),
+        };
+        due_dates.push(due);
+    }
+
+    // 调整后的未付分期：(原记录, 期数, 金额, 到期日)
+    let mut slots: Vec<(
+        Option<&qmx_backend_lib::cash::Cash>,
+        u32,
+        i64,
+        DateTime<Utc>,
+    )> = Vec::new();
+    let mut cancelled: Vec<&qmx_backend_lib::cash::Cash> = Vec::new();
+    let total_installments = match remaining_installments {
+        Some(count) => {
+            let paid: Vec<_> = active
+                .iter()
+                .filter(|(_, i)| i.status == InstallmentStatus::Paid)
+                .collect();
+            let balance = plan_balance(&active, last.total_amount)?;
+            if balance <= 0 {
+                return Err("分期计划已全部付清".to_string());
+            }
+            if balance < i64::from(count) {
+                return Err(format!("未付余额{}不足以分为{}期", balance, count));
+            }
+            let paid_numbers: HashSet<u32> =
+                paid.iter().map(|(_, i)| i.current_installment).collect();
+            let numbers = (1..)
+                .filter(|n| !paid_numbers.contains(n))
+                .take(count as usize);
+            let per_installment = balance / i64::from(count);
+            let mut anchor = due_dates
+                .last()
+                .copied()
+                .unwrap_or(last.due_date + Duration::days(shift_days.unwrap_or(0)));
+            for (index, number) in numbers.enumerate() {
+                let amount = if index + 1 == count as usize {
+                    balance - per_installment * (i64::from(count) - 1)
+                } else {
+                    per_installment
+                };
+                match unpaid.get(index) {
+                    Some((cash, _)) => slots.push((Some(cash), number, amount, due_dates[index])),
+                    None => {
+                        anchor = advance_due_date(anchor, frequency_enum, 1)?;
+                        slots.push((None, number, amount, anchor));
+                    }
+                }
+            }
+            cancelled.extend(unpaid.iter().skip(count as usize).map(|(cash, _)| cash));
+            paid.len() as u32 + count
+        }
+        None => {
+            for ((cash, installment), due) in unpaid.iter().zip(&due_dates) {
+                slots.push((Some(cash), installment.current_installment, cash.cash, *due));
+            }
+            last.total_installments
+        }
+    };
+
+    let today = club_today();
+    let mut updated_uids = Vec::new();
+    let mut audits: Vec<(u64, Vec<FieldChange>)> = Vec::new();
+    let mut created_uids = Vec::new();
+    // 不再逾期（到期日顺延到今天及以后）或被取消的分期，其待付款滞纳金一并减免
+    let mut waive_fee_uids: Vec<u64> = cancelled.iter().map(|cash| cash.uid).collect();
+
+    // 任一步失败时恢复已修改的分期并删除新增的分期，避免计划半新半旧
+    // 已付分期只同步计划层面的付款频率和总期数
+    for (cash, installment) in &active {
+        if unpaid.iter().any(|(c, _)| c.uid == cash.uid) {
+            continue;
+        }
+        let mut installment = installment.clone();
+        installment.frequency = frequency_enum;
+        installment.total_installments = total_installments;
+        if let Err(e) =
+            manager.update_cash(cash.uid, CashUpdater::new().installment(Some(installment)))
+        {
+            rollback_plan_changes(&manager, &plan, &updated_uids, &created_uids);
+            return Err(format!("调整分期计划失败: {}", e));
+        }
+        updated_uids.push(cash.uid);
+    }
+
+    for (cash, number, amount, due_date) in &slots {
+        let mut installment = Installment {
+            plan_id,
+            total_amount: last.total_amount,
+            total_installments,
+            current_installment: *number,
+            frequency: frequency_enum,
+            due_date: *due_date,
+            status: InstallmentStatus::Pending,
+        };
+        let Some(cash) = cash else {
+            let mut builder = CashBuilder::new(*amount)
+                .installment(installment)
+                .note(format!("分期付款第{}期", number));
+            if let Some(student_id) = first_cash.student_id {
+                builder = builder.student_id(student_id);
+            }
+            match manager.record_cash(builder) {
+                Ok(cash_id) => created_uids.push(cash_id),
+                Err(e) => {
+                    rollback_plan_changes(&manager, &plan, &updated_uids, &created_uids);
+                    return Err(format!("生成分期失败: {}", e));
+                }
+            }
+            continue;
+        };
+        let Some(old) = cash.installment.as_ref() else {
+            rollback_plan_changes(&manager, &plan, &updated_uids, &created_uids);
+            return Err("分期计划数据异常".to_string());
+        };
+        // 到期日仍早于今天的逾期分期保持逾期状态
+        if old.status == InstallmentStatus::Overdue && to_local_date(due_date) < today {
+            installment.status = InstallmentStatus::Overdue;
+        } else {
+            waive_fee_uids.push(cash.uid);
+        }
+        let mut changes = Vec::new();
+        let mut updater = CashUpdater::new();
+        if *amount != cash.cash {
+            changes.push(FieldChange {
+                field: "amount".to_string(),
+                old_value: Some(cash.cash.to_string()),
+                new_value: Some(amount.to_string()),
+            });
+            updater = updater.cash(*amount);
+        }
+        if to_local_date(&old.due_date) != to_local_date(due_date) {
+            changes.push(FieldChange {
+                field: "due_date".to_string(),
+                old_value: Some(to_local_date(&old.due_date).format("%Y-%m-%d").to_string()),
+                new_value: Some(to_local_date(due_date).format("%Y-%m-%d").to_string()),
+            });
+        }
+        if let Err(e) = manager.update_cash(cash.uid, updater.installment(Some(installment))) {
+            rollback_plan_changes(&manager, &plan, &updated_uids, &created_uids);
+            return Err(format!("调整分期失败: {}", e));
+        }
+        updated_uids.push(cash.uid);
+        if !changes.is_empty() {
+            audits.push((cash.uid, changes));
+        }
+    }
+
+    for cash in &cancelled {
+        let Some(mut installment) = cash.installment.clone() else {
+            rollback_plan_changes(&manager, &plan, &updated_uids, &created_uids);
+            return Err("分期计划数据异常".to_string());
+        };
+        installment.status = InstallmentStatus::Cancelled;
+        installment.total_installments = total_installments;
+        if let Err(e) =
+            manager.update_cash(cash.uid, CashUpdater::new().installment(Some(installment)))
+        {
+            rollback_plan_changes(&manager, &plan, &updated_uids, &created_uids);
+            return Err(format!("取消分期付款失败: {}", e));
+        }
+        updated_uids.push(cash.uid);
+        audits.push((
+            cash.uid,
+            vec![FieldChange {
+                field: "status".to_string(),
+                old_value: cash.installment.as_ref().map(|i| format!("{:?}", i.status)),
+                new_value: Some("Cancelled".to_string()),
+            }],
+        ));
+    }
+
+    let operator = operator.map(|o| o.trim().to_string());
+    let reason = reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    let result = write_store(|ext| {
+        let category = ext
+            .cash_meta
+            .get(&first_cash.uid)
+            .and_then(|m| m.category.clone());
+        for uid in &created_uids {
+            ext.record_cash_meta(*uid, None, operator.clone()).category = category.clone();
+        }
+        let waiver_reason = match &reason {
+            Some(reason) => format!("分期计划调整：{}", reason),
+            None => "分期计划调整".to_string(),
+        };
+        let waived_fees =
+            ext.waive_pending_late_fees(&waive_fee_uids, &waiver_reason, operator.clone());
+        for uid in &updated_uids {
+            ext.touch_cash(*uid);
+        }
+        for (cash_uid, changes) in audits {
+            let entry = CashAuditEntry {
+                id: ext.alloc_id(),
+                cash_uid,
+                changes,
+                operator: operator.clone(),
+                reason: reason.clone(),
+                created_at: Utc::now(),
+            };
+            ext.cash_audit.push(entry);
+        }
+        Ok(waived_fees)
+    });
+    let waived_fees = match result {
+        Ok(waived_fees) => waived_fees,
+        Err(e) => {
+            rollback_plan_changes(&manager, &plan, &updated_uids, &created_uids);
+            return Err(e);
+        }
+    };
+
+    log::info!(
+        "成功调整分期计划 - 计划: {}, 总期数: {}, 新增: {}, 取消: {}, 减免滞纳金: {}",
+        plan_id,
+        total_installments,
+        created_uids.len(),
+        cancelled.len(),
+        waived_fees
+    );
+
+    let plan = find_plan_installments(&manager, plan_id)?;
+    read_store(|ext| {
+        plan.iter()
+            .map(|(cash, _)| convert_cash_to_response(cash, ext))
+            .collect()
+    })
+}
+
+// 分期 - 提前结清分期计划：一次付清未付余额（总金额减去已付金额和已给予的减免），可手动减免或使用折扣规则/优惠券
+// 结清款记为该计划新的一期（已付款），原有未付分期标记为已付款并将金额调整为0，避免重复计入收入
+#[tauri::command]
+fn pay_off_plan_early(
+    plan_id: u64,
+    discount_amount: Option<i64>,
+    discount_rule_id: Option<u64>,
+    coupon_code: Option<String>,
+    payment_method: Option<String>,
+    business_date: Option<String>,
+    operator: Option<String>,
+) -> Result<PlanPayoffResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_plan_id(plan_id)?;
+    if let Some(discount) = discount_amount {
+        validate_amount(discount)?;
+        if discount < 0 {
+            return Err("减免金额不能为负数".to_string());
+        }
+        if discount_rule_id.is_some() || coupon_code.is_some() {
+            return Err("手动减免金额不能与折扣规则或优惠券同时使用".to_string());
+        }
+    }
+    if let Some(code) = &coupon_code {
+        validate_coupon_code(code)?;
+    }
+    if let Some(method) = &payment_method {
+        validate_payment_method(method)?;
+    }
+    let business_date = business_date
+        .as_deref()
+        .map(parse_business_date)
+        .transpose()?;
+    if let Some(operator_str) = &operator {
+        validate_operator(operator_str)?;
+    }
+
+    let manager = get_manager()?;
+    let plan = find_plan_installments(&manager, plan_id)?;
+    let active: Vec<_> = plan
+        .iter()
+        .filter(|(_, i)| i.status != InstallmentStatus::Cancelled)
+        .collect();
+    let (first_cash, last) = match (active.first(), active.last()) {
+        (Some((cash, _)), Some((_, installment))) => (cash, installment),
+        _ => return Err("分期计划已取消".to_string()),
+    };
+    let balance = plan_balance(&active, last.total_amount)?;
+    if balance <= 0 {
+        return Err("分期计划已全部付清".to_string());
+    }
+
+    let pricing = match discount_amount {
+        Some(discount) => {
+            if discount > balance {
+                return Err(format!("减免金额不能超过未付余额{}", balance));
+            }
+            resolve_cash_pricing(balance - discount, Some(balance), None, None)?
+        }
+        None => resolve_cash_pricing(balance, Some(balance), discount_rule_id, coupon_code)?,
+    };
+    let amount = pricing
+        .as_ref()
+        .map(|p| p.list_price - p.discount_amount)
+        .unwrap_or(balance);
+
+    // 结清款作为计划的最后一期，之后不能再生成下一期
+    let number = plan
+        .iter()
+        .map(|(_, i)| i.current_installment)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let mut builder = CashBuilder::new(amount)
+        .installment(Installment {
+            plan_id,
+            total_amount: last.total_amount,
+            total_installments: number,
+            current_installment: number,
+            frequency: last.frequency,
+            due_date: local_day_start(business_date.unwrap_or_else(club_today)),
+            status: InstallmentStatus::Paid,
+        })
+        .note(format!("提前结清分期计划，未付余额{}", balance));
+    if let Some(student_id) = first_cash.student_id {
+        builder = builder.student_id(student_id);
+    }
+    let cash_id = manager
+        .record_cash(builder)
+        .map_err(|e| format!("保存结清款失败: {}", e))?;
+
+    // 任一步失败时恢复已修改的分期并删除结清款，避免余额既算已付又算应收
+    let mut updated_uids = Vec::new();
+    let mut audits: Vec<(u64, Vec<FieldChange>)> = Vec::new();
+    for (cash, installment) in &active {
+        let mut installment = installment.clone();
+        installment.total_installments = number;
+        let mut updater = CashUpdater::new();
+        if matches!(
+            installment.status,
+            InstallmentStatus::Pending | InstallmentStatus::Overdue
+        ) {
+            audits.push((
+                cash.uid,
+                vec![
+                    FieldChange {
+                        field: "amount".to_string(),
+                        old_value: Some(cash.cash.to_string()),
+                        new_value: Some("0".to_string()),
+                    },
+                    FieldChange {
+                        field: "status".to_string(),
+                        old_value: Some(format!("{:?}", installment.status)),
+                        new_value: Some("Paid".to_string()),
+                    },
+                ],
+            ));
+            installment.status = InstallmentStatus::Paid;
+            updater = updater.cash(0);
+        }
+        if let Err(e) = manager.update_cash(cash.uid, updater.installment(Some(installment))) {
+            rollback_plan_changes(&manager, &plan, &updated_uids, &[cash_id]);
+            return Err(format!("结清分期失败: {}", e));
+        }
+        updated_uids.push(cash.uid);
+    }
+
+    let operator = operator.map(|o| o.trim().to_string());
+    let settled_uids: Vec<u64> = audits.iter().map(|(uid, _)| *uid).collect();
+    let result = write_store(|ext| {
+        let category = ext
+            .cash_meta
+            .get(&first_cash.uid)
+            .and_then(|m| m.category.clone());
+        let meta = ext.record_cash_meta(cash_id, business_date, operator.clone());
+        meta.category = category;
+        meta.payment_method = payment_method;
+        if let Some(pricing) = pricing {
+            // 优惠券在交易成功入账后才计入使用次数
+            if let Some(code) = &pricing.coupon_code {
+                if let Some(coupon) = ext.coupon_by_code_mut(code) {
+                    coupon.used_count += 1;
+                }
+            }
+            ext.cash_pricing.insert(cash_id, pricing);
+        }
+        for uid in &updated_uids {
+            ext.touch_cash(*uid);
+        }
+        for (cash_uid, changes) in audits {
+            let entry = CashAuditEntry {
+                id: ext.alloc_id(),
+                cash_uid,
+                changes,
+                operator: operator.clone(),
+                reason: Some(format!("提前结清，并入交易记录 {}", cash_id)),
+                created_at: Utc::now(),
+            };
+            ext.cash_audit.push(entry);
+        }
+        // 结清后逾期分期不再存在，未收取的滞纳金一并减免
+        Ok(ext.waive_pending_late_fees(
+            &settled_uids,
+            &format!("提前结清分期计划，结清款交易记录 {}", cash_id),
+            operator.clone(),
+        ))
+    });
+    let waived_fees = match result {
+        Ok(waived_fees) => waived_fees,
+        Err(e) => {
+            rollback_plan_changes(&manager, &plan, &updated_uids, &[cash_id]);
+            return Err(e);
+        }
+    };
+
+    let cash = manager
+        .get_cash(cash_id)
+        .map_err(|e| format!("获取现金记录失败: {}", e))?
+        .ok_or("现金记录创建后未找到")?;
+    let payment = read_store(|ext| convert_cash_to_response(&cash, ext))?;
+
+    log::info!(
+        "成功提前结清分期计划 - 计划: {}, 未付余额: {}, 实收: {}, 交易UID: {}, 减免滞纳金: {}",
+        plan_id,
+        balance,
+        amount,
+        cash_id,
+        waived_fees
+    );
+    Ok(PlanPayoffResponse {
+        plan_id,
+        remaining_balance: balance,
+        discount_amount: balance - amount,
+        amount_paid: amount,
+        payment,
+        settled_uids,
+    })
+}
+
+// 签到 - 记录学员到场
+#[tauri::command]
+fn check_in_student(
+    student_uid: u64,
+    checked_in_at: Option<String>,
+    note: Option<String>,
+) -> Result<CheckInResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+    if let Some(note_str) = &note {
+        validate_note(note_str)?;
+    }
+    let checked_in_at = match checked_in_at {
+        Some(time_str) => parse_date_input(&time_str)?,
+        None => Utc::now(),
+    };
+
+    let manager = get_manager()?;
+    let student = manager
+        .get_student(student_uid)
+        .map_err(|e| format!("获取学生失败: {}", e))?
+        .ok_or("学员不存在")?;
+    let is_trial = format!("{:?}", student.class()) == "TenTry";
+
+    let record = write_store(|ext| {
+        // 体验课学员每次签到扣除一次体验次数（早于本功能的学员在首次签到时开始计算）
+        // 次数已用完或已过期时仍然记录签到但不扣次数，由体验课提醒列出待转化的学员
+        let mut trial_credit = false;
+        if is_trial {
+            let mut package = ext
+                .trials
+                .get(&student_uid)
+                .cloned()
+                .unwrap_or_else(|| TrialPackage::new(checked_in_at));
+            if package.conversion.is_none() {
+                match package.consume(&checked_in_at) {
+                    Ok(()) => trial_credit = true,
+                    Err(e) => log::warn!("体验课签到未扣次数 - UID: {}, 原因: {}", student_uid, e),
+                }
+                ext.trials.insert(student_uid, package);
+            }
+        }
+        let record = CheckIn {
+            id: ext.alloc_id(),
+            checked_in_at,
+            note: note.map(|n| n.trim().to_string()),
+            trial_credit,
+        };
+        ext.attendance
+            .entry(student_uid)
+            .or_default()
+            .push(record.clone());
+        Ok(record)
+    })?;
+
+    log::info!("学员签到成功 - UID: {}, 签到ID: {}", student_uid, record.id);
+    Ok(convert_check_in_to_response(&record))
+}
+
+// 签到 - 获取学员签到记录（最近的在前）
+#[tauri::command]
+fn get_student_check_ins(student_uid: u64) -> Result<Vec<CheckInResponse>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+
+    read_store(|ext| {
+        let mut records: Vec<&CheckIn> = ext
+            .attendance
+            .get(&student_uid)
+            .map(|records| records.iter().collect())
+            .unwrap_or_default();
+        records.sort_by_key(|r| std::cmp::Reverse(r.checked_in_at));
+        records
+            .into_iter()
+            .map(convert_check_in_to_response)
+            .collect()
+    })
+}
+
+// 体验课提醒 - 即将到期、次数即将用完或已用完/已过期但未转化的体验课学员
+#[tauri::command]
+fn get_trial_ending_soon(
+    days: i64,
+    credits_threshold: Option<u32>,
+) -> Result<Vec<StudentResponse>, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_days(days)?;
+    let credits_threshold = credits_threshold.unwrap_or(2);
+
+    let manager = get_manager()?;
+    let now = Utc::now();
+    // 截止到俱乐部本地日期第N天结束
+    let cutoff_day = club_today()
+        .checked_add_days(Days::new(days as u64))
+        .ok_or("天数超出范围")?;
+    let cutoff_date = local_day_end(cutoff_day);
+
+    let students = manager
+        .list_students()
+        .map_err(|e| format!("获取学生列表失败: {}", e))?;
+
+    read_store(|ext| {
+        let mut ending: Vec<(DateTime<Utc>, StudentResponse)> = Vec::new();
+        for student in &students {
+            if format!("{:?}", student.class()) != "TenTry" {
+                continue;
+            }
+            let Some(package) = ext.trials.get(&student.uid()) else {
+                continue;
+            };
+            let needs_attention = match package.status(&now) {
+                TrialStatus::Active => {
+                    package.expires_at <= cutoff_date || package.credits_left() <= credits_threshold
+                }
+                TrialStatus::Exhausted | TrialStatus::Expired => true,
+                TrialStatus::Converted => false,
+            };
+            if needs_attention {
+                ending.push((
+                    package.expires_at,
+                    convert_student_to_response(student, ext),
+                ));
+            }
+        }
+        ending.sort_by_key(|(expires_at, _)| *expires_at);
+        ending.into_iter().map(|(_, student)| student).collect()
+    })
+}
+
+// 体验课转化 - 升级为月卡/年卡会员，并关联付款记录
+#[tauri::command]
+fn convert_trial_student(
+    student_uid: u64,
+    class_type: String, // "Month" | "Year"
+    payment_uid: u64,
+    start_date: Option<String>,
+    operator: Option<String>,
+) -> Result<StudentResponse, String> {
+    init_manager()?;
+
+    // 输入验证（完整的后端验证）
+    validate_student_uid(student_uid)?;
+    let membership_type = match class_type.as_str() {
+        "Month" => "month",
+        "Year" => "year",
+        _ => return Err("体验课只能转为月卡(Month)或年卡(Year)".to_string()),
+    };
+
+    let manager = get_manager()?;
+    validate_membership_link(&manager, Some(payment_uid), operator.as_deref())?;
+    let student = manager
+        .get_student(student_uid)
+        .map_err(|e| format!("获取学生失败: {}", e))?
+        .ok_or("学员不存在")?;
+    if format!("{:?}", student.class()) != "TenTry" {
+        return Err("该学员不是体验课学员".to_string());
+    }
+    let payment = manager
+        .get_cash(payment_uid)
+        .map_err(|e| format!("获取付款记录失败: {}", e))?
+        .ok_or("关联的付款记录不存在")?;
+    if payment.student_id != Some(student_uid) || payment.cash <= 0 {
+        return Err("关联的付款记录必须是该学员的收入记录".to_string());
+    }
+    let already_converted = read_store(|ext| {
+        ext.trials
+            .get(&student_uid)
+            .is_some_and(|p| p.conversion.is_some())
+    })?;
+    if already_converted {
+        return Err("学员已转为正式会员".to_string());
+    }
+
+    manager
+        .update_student(
+            student_uid,
+            StudentUpdater::new().class(parse_class_type(&class_type)?),
+        )
+        .map_err(|e| {
+            log::error!("体验课转化失败 - UID: {}, 错误: {}", student_uid, e);
+            format!("更新学员班级失败: {}", e)
+        })?;
+    // 设置会员失败时恢复为体验课班级
+    if let Err(e) = set_membership_by_type(
+        student_uid,
+        membership_type.to_string(),
+        Some(true),
+        None,
+        None,
+        start_date,
+        Some(payment_uid),
+        operator.clone(),
+    ) {
+        if let Err(rollback_err) =
+            manager.update_student(student_uid, StudentUpdater::new().class(Class::TenTry))
+        {
+            log::error!(
+                "恢复体验课班级失败 - UID: {}, 错误: {}",
+                student_uid,
+                rollback_err
+            );
+        }
+        return Err(e);
+    }
+
+    write_store(|ext| {
+        // 没有体验课记录的旧学员，以转化时间作为体验开始时间
+        let now = Utc::now();
+        let package = ext
+            .trials
+            .entry(student_uid)
+            .or_insert_with(|| TrialPackage::new(now));
+        package.conversion = Some(TrialConversion {
+            class_type: class_type.clone(),
+            payment_uid,
+            operator: operator.map(|o| o.trim().to_string()),
+            converted_at: now,
+        });
+        Ok(())
+    })?;
+
+    log::info!(
+        "体验课学员转化成功 - UID: {}, 班级: {}, 付款记录: {}",
+        student_uid,
+        class_type,
+        payment_uid
+    );
+
+    let student = manager
+        .get_student(student_uid)
+        .map_err(|e| format!("获取学生失败: {}", e))?
+        .ok_or("学员不存在")?;
+    read_store(|ext| convert_student_to_response(&student, ext))
+}
+
+// 体验课转化率统计（按体验开始时间统计区间内的体验课）
+#[tauri::command]
+fn get_trial_conversion_stats(
+    period: Option<String>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<TrialConversionStatsResponse, String> {
+    init_manager()?;
+
+    let (start_day, end_day) =
+        report_local_range(period.as_deref(), date_from.as_deref(), date_to.as_deref())?;
+    let start = local_day_start(start_day);
+    let end = local_day_end(end_day);
+    let now = Utc::now();
+
+    let manager = get_manager()?;
+    let packages: Vec<(u64, TrialPackage)> = read_store(|ext| {
+        ext.trials
+            .iter()
+            .filter(|(_, p)| p.started_at >= start && p.started_at <= end)
+            .map(|(uid, p)| (*uid, p.clone()))
+            .collect()
+    })?;
+
+    let mut by_status: BTreeMap<String, usize> = BTreeMap::new();
+    let mut by_class: BTreeMap<String, usize> = BTreeMap::new();
+    let mut by_subject: BTreeMap<String, TrialSubjectStats> = BTreeMap::new();
+    let mut converted_trials = 0;
+    let mut total_credits_used = 0;
+    let mut total_days_to_convert = 0;
+
+    for (student_uid, package) in &packages {
+        *by_status
+            .entry(format!("{:?}", package.status(&now)))
+            .or_default() += 1;
+        let subject = manager
+            .get_student(*student_uid)
+            .ok()
+            .flatten()
+            .map(|s| format!("{:?}", s.subject()))
+            .unwrap_or_else(|| "Others".to_string());
+        let subject_stats = by_subject
+            .entry(subject)
+            .or_insert_with(|| TrialSubjectStats {
+                total_trials: 0,
+                converted_trials: 0,
+                conversion_rate: 0.0,
+            });
+        subject_stats.total_trials += 1;
+        if let Some(conversion) = &package.conversion {
+            converted_trials += 1;
+            subject_stats.converted_trials += 1;
+            total_credits_used += package.credits_used;
+            total_days_to_convert +=
+                local_days_between(&package.started_at, &conversion.converted_at);
+            *by_class.entry(conversion.class_type.clone()).or_default() += 1;
+        }
+    }
+
+    let rate = |converted: usize, total: usize| {
+        if total == 0 {
+            0.0
+        } else {
+            converted as f64 / total as f64 * 100.0
         }
+    };
+    for stats in by_subject.values_mut() {
+        stats.conversion_rate = rate(stats.converted_trials, stats.total_trials);
     }
+    let average = |total: f64| {
+        if converted_trials == 0 {
+            0.0
+        } else {
+            total / converted_trials as f64
+        }
+    };
 
-    Ok(success_count)
+    Ok(TrialConversionStatsResponse {
+        period_start_date: start_day.format("%Y-%m-%d").to_string(),
+        period_end_date: end_day.format("%Y-%m-%d").to_string(),
+        total_trials: packages.len(),
+        converted_trials,
+        conversion_rate: rate(converted_trials, packages.len()),
+        average_credits_used_before_conversion: average(f64::from(total_credits_used)),
+        average_days_to_convert: average(total_days_to_convert as f64),
+        by_status,
+        by_class,
+        by_subject,
+    })
 }
 
-// v2 API功能 - 获取会员到期提醒
+// 流失会员报表 - 最近N天内到期且未续费的会员，按班级和科目分组
 #[tauri::command]
-fn get_membership_expiring_soon(days: i64) -> Result<Vec<StudentResponse>, String> {
+fn get_lapsed_members(days: i64) -> Result<LapsedMembersReportResponse, String> {
     init_manager()?;
 
     // 输入验证（完整的后端验证）
     validate_days(days)?;
 
     let manager = get_manager()?;
-    let cutoff_date = Utc::now() + Duration::days(days);
+    let now = Utc::now();
+    let today = club_today();
+    let since_day = today
+        .checked_sub_days(Days::new(days as u64))
+        .ok_or("天数超出范围")?;
+    let since = local_day_start(since_day);
 
     let students = manager
-        .search_students(StudentQuery::new().has_membership(true))
-        .map_err(|e| format!("搜索会员学生失败: {}", e))?;
+        .list_students()
+        .map_err(|e| format!("获取学生列表失败: {}", e))?;
+
+    // 每个学员最近一笔收款（UID递增，取最大者）
+    let cash_list = manager
+        .search_cash(CashQuery::new())
+        .map_err(|e| format!("获取交易记录失败: {}", e))?;
+    let mut last_payments: HashMap<u64, &qmx_backend_lib::cash::Cash> = HashMap::new();
+    for cash in cash_list.iter().filter(|c| c.cash > 0) {
+        if let Some(student_id) = cash.student_id {
+            let entry = last_payments.entry(student_id).or_insert(cash);
+            if cash.uid > entry.uid {
+                *entry = cash;
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<(String, String), Vec<LapsedMemberResponse>> = BTreeMap::new();
+    read_store(|ext| {
+        for student in &students {
+            let Some(end_date) = student.membership_end_date() else {
+                continue;
+            };
+            // 已到期（未续费）且到期时间在统计窗口内
+            if end_date >= now || end_date < since {
+                continue;
+            }
+            // 冻结中的会员不算流失
+            let is_frozen = ext
+                .membership_freezes
+                .get(&student.uid())
+                .is_some_and(|f| active_freeze(f).is_some());
+            if is_frozen {
+                continue;
+            }
 
-    let mut expiring_students = Vec::new();
-    for student in students {
-        if let Some(end_date) = student.membership_end_date() {
-            if end_date <= cutoff_date && student.is_membership_active() {
-                expiring_students.push(StudentResponse {
+            let last_attendance = ext
+                .attendance
+                .get(&student.uid())
+                .and_then(|records| last_check_in(records))
+                .map(|r| r.checked_in_at.to_rfc3339());
+            let last_payment = last_payments.get(&student.uid());
+            let class = format!("{:?}", student.class());
+            let subject = format!("{:?}", student.subject());
+
+            groups
+                .entry((class.clone(), subject.clone()))
+                .or_default()
+                .push(LapsedMemberResponse {
                     uid: student.uid(),
                     name: student.name().to_string(),
-                    age: student.age(),
-                    class: format!("{:?}", student.class()),
                     phone: student.phone().to_string(),
-                    note: student.note().to_string(),
-                    subject: format!("{:?}", student.subject()),
-                    lesson_left: student.lesson_left(),
-                    membership_start_date: student.membership_start_date().map(|d| d.to_rfc3339()),
-                    membership_end_date: student.membership_end_date().map(|d| d.to_rfc3339()),
-                    is_membership_active: student.is_membership_active(),
-                    membership_days_remaining: student.membership_days_remaining(),
+                    class,
+                    subject,
+                    membership_end_date: end_date.to_rfc3339(),
+                    membership_end_local_date: to_local_date_string(&end_date),
+                    days_since_expiry: (today - to_local_date(&end_date)).num_days(),
+                    last_attendance,
+                    last_payment_uid: last_payment.map(|c| c.uid),
+                    last_payment_amount: last_payment.map(|c| c.cash),
                 });
-            }
         }
-    }
+    })?;
 
-    Ok(expiring_students)
+    let groups: Vec<LapsedGroupResponse> = groups
+        .into_iter()
+        .map(|((class, subject), mut members)| {
+            members.sort_by_key(|m| m.days_since_expiry);
+            LapsedGroupResponse {
+                class,
+                subject,
+                count: members.len(),
+                members,
+            }
+        })
+        .collect();
+    let total = groups.iter().map(|g| g.count).sum();
+
+    log::info!("成功生成流失会员报表 - 最近{}天, 共{}人", days, total);
+    Ok(LapsedMembersReportResponse {
+        days,
+        since_local_date: since_day.format("%Y-%m-%d").to_string(),
+        total,
+        groups,
+    })
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -1461,6 +7425,8 @@ pub fn run() {
             delete_student,
             add_cash_transaction,
             get_all_transactions,
+            update_cash_transaction,
+            get_cash_audit_log,
             delete_cash_transaction,
             get_dashboard_stats,
             // 分期付款相关命令
@@ -1468,18 +7434,110 @@ pub fn run() {
             generate_next_installment,
             cancel_installment_plan,
             get_installments_by_plan,
+            reschedule_installment_plan,
+            pay_off_plan_early,
             // 会员管理相关命令
             set_student_membership,
             clear_student_membership,
             set_membership_by_type,
+            freeze_membership,
+            unfreeze_membership,
+            get_membership_history,
             // v2 API命令
             get_student_stats,
             get_financial_stats,
+            // 俱乐部设置相关命令
+            get_club_settings,
+            set_club_timezone_setting,
+            update_club_profile,
             search_students,
+            quick_search_students,
             get_student_cash,
             search_cash,
+            // 分页列表相关命令
+            list_students_paged,
+            list_transactions_paged,
             update_multiple_students,
-            get_membership_expiring_soon
+            get_membership_expiring_soon,
+            // 监护人相关命令
+            add_guardian,
+            update_guardian,
+            delete_guardian,
+            link_guardian_to_student,
+            unlink_guardian_from_student,
+            get_all_guardians,
+            // 家庭账户相关命令
+            create_family,
+            update_family,
+            add_family_member,
+            remove_family_member,
+            delete_family,
+            get_all_families,
+            record_family_payment,
+            get_family_statement,
+            // 储值钱包相关命令
+            top_up_wallet,
+            charge_wallet,
+            get_wallet_ledger,
+            // 折扣与优惠券相关命令
+            create_discount_rule,
+            update_discount_rule,
+            get_discount_rules,
+            create_coupon,
+            set_coupon_active,
+            get_coupons,
+            preview_discount,
+            get_discount_report,
+            // 转介绍相关命令
+            get_referral_reward,
+            set_referral_reward,
+            get_referral_report,
+            // 潜在客户相关命令
+            create_lead,
+            update_lead,
+            add_lead_follow_up,
+            delete_lead,
+            get_all_leads,
+            convert_lead_to_student,
+            get_lead_conversion_stats,
+            // 体验课相关命令
+            get_trial_ending_soon,
+            convert_trial_student,
+            get_trial_conversion_stats,
+            // 自定义字段相关命令
+            create_custom_field,
+            update_custom_field,
+            delete_custom_field,
+            get_custom_fields,
+            // 标签与学员分组相关命令
+            add_tags,
+            remove_tags,
+            get_all_tags,
+            create_segment,
+            update_segment,
+            delete_segment,
+            get_segments,
+            run_segment,
+            // 收据与对账单相关命令
+            generate_receipt,
+            generate_monthly_statements,
+            // 财务报表相关命令
+            generate_financial_report,
+            get_revenue_timeseries,
+            get_revenue_recognition,
+            get_receivables_aging,
+            // 滞纳金相关命令
+            set_late_fee_policy,
+            remove_late_fee_policy,
+            get_late_fee_policies,
+            apply_late_fees,
+            get_late_fees,
+            waive_late_fee,
+            pay_late_fee,
+            // 签到与会员留存相关命令
+            check_in_student,
+            get_student_check_ins,
+            get_lapsed_members
         ])
         .run(tauri::generate_context!())
         .expect("Error running app");
@@ -1497,8 +7555,64 @@ pub struct StudentResponse {
     pub lesson_left: Option<u32>,
     pub membership_start_date: Option<String>,
     pub membership_end_date: Option<String>,
+    pub membership_start_local_date: Option<String>,
+    pub membership_end_local_date: Option<String>,
     pub is_membership_active: bool,
     pub membership_days_remaining: Option<i64>,
+    pub is_membership_frozen: bool,
+    pub freeze_history: Vec<MembershipFreezeResponse>,
+    pub referred_by: Option<u64>,
+    pub trial: Option<TrialPackageResponse>,
+    pub tags: Vec<String>,
+    pub custom_fields: BTreeMap<String, String>,
+    pub wallet_balance: i64,
+    pub guardians: Vec<StudentGuardianResponse>,
+}
+
+#[derive(Serialize)]
+pub struct StudentGuardianResponse {
+    pub guardian_id: u64,
+    pub name: String,
+    pub relationship: String,
+    pub phones: Vec<String>,
+    pub is_primary: bool,
+}
+
+#[derive(Serialize)]
+pub struct GuardianResponse {
+    pub id: u64,
+    pub name: String,
+    pub relationship: String,
+    pub phones: Vec<String>,
+    pub note: Option<String>,
+    pub created_at: String,
+    pub student_uids: Vec<u64>,
+}
+
+#[derive(Serialize)]
+pub struct MembershipFreezeResponse {
+    pub start_date: String,
+    pub end_date: Option<String>,
+    pub frozen_days: i64,
+    pub reason: Option<String>,
+    pub is_active: bool,
+}
+
+#[derive(Serialize)]
+pub struct MembershipPeriodResponse {
+    pub id: u64,
+    pub membership_type: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub start_local_date: String,
+    pub end_local_date: String,
+    pub payment_uid: Option<u64>,
+    pub operator: Option<String>,
+    pub created_at: String,
+    pub frozen_days: i64,
+    pub cancelled_at: Option<String>,
+    pub cancel_reason: Option<String>,
+    pub status: String,
 }
 
 #[derive(Serialize)]
@@ -1518,7 +7632,20 @@ pub struct TransactionResponse {
     pub installment_current: Option<u32>,
     pub installment_total: Option<u32>,
     pub installment_due_date: Option<String>,
+    pub installment_due_local_date: Option<String>,
     pub installment_status: Option<String>,
+    pub list_price: Option<i64>,
+    pub discount_amount: Option<i64>,
+    pub discount_rule_id: Option<u64>,
+    pub coupon_code: Option<String>,
+    pub business_date: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub operator: Option<String>,
+    pub category: Option<String>,
+    pub category_label: Option<String>,
+    pub payment_method: Option<String>,
+    pub payment_method_label: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -1545,9 +7672,34 @@ pub struct FinancialStatsResponse {
     pub total_income: i64,
     pub total_expense: i64,
     pub net_income: i64,
+    /// Cash / Accrual
+    pub basis: String,
+    /// 权责发生制下期末尚未确认的收入
+    pub deferred_balance: Option<i64>,
+    /// 区间内的分期记录数（不含已取消的分期，此前为区间内全部交易笔数）
     pub installment_total: i64,
+    /// 其中已付款的分期记录数（此前为区间内全部分期记录数）
     pub installment_paid: i64,
+    /// 其中待付款或已逾期的分期记录数
     pub installment_pending: i64,
+    pub period_start_date: String,
+    pub period_end_date: String,
+    pub timezone: String,
+}
+
+#[derive(Serialize)]
+pub struct ClubSettingsResponse {
+    pub timezone: String,
+    pub today: String,
+    pub club_name: String,
+    pub logo_path: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BatchUpdateResponse {
+    pub updated_count: usize,
+    /// 新增标签后超出标签数量上限而跳过的学员
+    pub skipped_uids: Vec<u64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1557,4 +7709,498 @@ pub struct StudentUpdateBatch {
     pub class_type: Option<String>,
     pub subject: Option<String>,
     pub note: Option<String>,
+    pub add_tags: Option<Vec<String>>,
+    pub remove_tags: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct CheckInResponse {
+    pub id: u64,
+    pub checked_in_at: String,
+    pub local_date: String,
+    pub note: Option<String>,
+    pub trial_credit: bool,
+}
+
+#[derive(Serialize)]
+pub struct LapsedMemberResponse {
+    pub uid: u64,
+    pub name: String,
+    pub phone: String,
+    pub class: String,
+    pub subject: String,
+    pub membership_end_date: String,
+    pub membership_end_local_date: String,
+    pub days_since_expiry: i64,
+    pub last_attendance: Option<String>,
+    pub last_payment_uid: Option<u64>,
+    pub last_payment_amount: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct LapsedGroupResponse {
+    pub class: String,
+    pub subject: String,
+    pub count: usize,
+    pub members: Vec<LapsedMemberResponse>,
+}
+
+#[derive(Serialize)]
+pub struct LapsedMembersReportResponse {
+    pub days: i64,
+    pub since_local_date: String,
+    pub total: usize,
+    pub groups: Vec<LapsedGroupResponse>,
+}
+
+#[derive(Serialize)]
+pub struct FamilyMemberResponse {
+    pub uid: u64,
+    pub name: String,
+}
+
+#[derive(Serialize)]
+pub struct FamilyResponse {
+    pub id: u64,
+    pub name: String,
+    pub members: Vec<FamilyMemberResponse>,
+    pub discount_percent: u32,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct FamilyPaymentResponse {
+    pub id: u64,
+    pub family_id: u64,
+    pub list_amount: i64,
+    pub discount_amount: i64,
+    pub paid_amount: i64,
+    pub cash_uids: Vec<u64>,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct FamilyStatementResponse {
+    pub family: FamilyResponse,
+    pub total_income: i64,
+    pub total_expense: i64,
+    pub transactions: Vec<TransactionResponse>,
+    pub payments: Vec<FamilyPaymentResponse>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FamilyAllocation {
+    pub student_uid: u64,
+    pub amount: i64,
+}
+
+#[derive(Serialize)]
+pub struct WalletEntryResponse {
+    pub id: u64,
+    pub kind: String,
+    pub amount: i64,
+    pub balance_after: i64,
+    pub category: Option<String>,
+    pub cash_uid: u64,
+    pub note: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct WalletLedgerResponse {
+    pub student_uid: u64,
+    pub balance: i64,
+    pub entries: Vec<WalletEntryResponse>,
+}
+
+#[derive(Serialize)]
+pub struct DiscountRuleResponse {
+    pub id: u64,
+    pub name: String,
+    pub kind: String,
+    pub value: i64,
+    pub category: String,
+    pub active: bool,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct CouponResponse {
+    pub id: u64,
+    pub code: String,
+    pub rule_id: u64,
+    pub max_uses: Option<u32>,
+    pub used_count: u32,
+    pub expires_at: Option<String>,
+    pub active: bool,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct DiscountPreviewResponse {
+    pub list_price: i64,
+    pub discount_amount: i64,
+    pub final_amount: i64,
+    pub discount_rule_id: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct DiscountReportRow {
+    pub rule_id: Option<u64>,
+    pub rule_name: String,
+    pub transaction_count: usize,
+    pub total_discount: i64,
+}
+
+#[derive(Serialize)]
+pub struct DiscountReportResponse {
+    pub period_start_date: String,
+    pub period_end_date: String,
+    pub transaction_count: usize,
+    pub total_list_price: i64,
+    pub total_discount: i64,
+    pub by_rule: Vec<DiscountReportRow>,
+    pub by_category: BTreeMap<String, i64>,
+}
+
+#[derive(Serialize)]
+pub struct ReferralRewardResponse {
+    pub extra_lessons: u32,
+    pub membership_days: u32,
+}
+
+#[derive(Serialize)]
+pub struct ReferredStudentResponse {
+    pub uid: u64,
+    pub name: String,
+    pub referred_at: String,
+    pub revenue: i64,
+    pub rewarded_lessons: u32,
+    pub rewarded_days: u32,
+}
+
+#[derive(Serialize)]
+pub struct ReferralReportRow {
+    pub referrer_uid: u64,
+    pub referrer_name: String,
+    pub referred: Vec<ReferredStudentResponse>,
+    pub total_revenue: i64,
+    pub total_rewarded_lessons: u32,
+    pub total_rewarded_days: u32,
+}
+
+#[derive(Serialize)]
+pub struct LeadFollowUpResponse {
+    pub status: String,
+    pub note: Option<String>,
+    pub operator: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct LeadResponse {
+    pub id: u64,
+    pub name: String,
+    pub phone: String,
+    pub age: Option<u8>,
+    pub source: String,
+    pub referrer_uid: Option<u64>,
+    pub status: String,
+    pub follow_ups: Vec<LeadFollowUpResponse>,
+    pub student_uid: Option<u64>,
+    pub created_at: String,
+    pub converted_at: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct LeadSourceStats {
+    pub total_leads: usize,
+    pub converted_leads: usize,
+    pub conversion_rate: f64,
+}
+
+#[derive(Serialize)]
+pub struct LeadConversionStatsResponse {
+    pub period_start_date: String,
+    pub period_end_date: String,
+    pub total_leads: usize,
+    pub converted_leads: usize,
+    pub conversion_rate: f64,
+    pub average_days_to_convert: f64,
+    pub by_status: BTreeMap<String, usize>,
+    pub by_source: BTreeMap<String, LeadSourceStats>,
+}
+
+#[derive(Serialize)]
+pub struct TrialPackageResponse {
+    pub credits_total: u32,
+    pub credits_used: u32,
+    pub credits_left: u32,
+    pub started_at: String,
+    pub expires_at: String,
+    pub expires_local_date: String,
+    pub status: String,
+    pub converted_class: Option<String>,
+    pub payment_uid: Option<u64>,
+    pub converted_at: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TrialSubjectStats {
+    pub total_trials: usize,
+    pub converted_trials: usize,
+    pub conversion_rate: f64,
+}
+
+#[derive(Serialize)]
+pub struct TrialConversionStatsResponse {
+    pub period_start_date: String,
+    pub period_end_date: String,
+    pub total_trials: usize,
+    pub converted_trials: usize,
+    pub conversion_rate: f64,
+    pub average_credits_used_before_conversion: f64,
+    pub average_days_to_convert: f64,
+    pub by_status: BTreeMap<String, usize>,
+    pub by_class: BTreeMap<String, usize>,
+    pub by_subject: BTreeMap<String, TrialSubjectStats>,
+}
+
+#[derive(Serialize)]
+pub struct CustomFieldResponse {
+    pub id: u64,
+    pub key: String,
+    pub label: String,
+    pub field_type: String,
+    pub required: bool,
+    pub options: Vec<String>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub max_length: Option<usize>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct TagCountResponse {
+    pub tag: String,
+    pub student_count: usize,
+}
+
+#[derive(Serialize)]
+pub struct SegmentResponse {
+    pub id: u64,
+    pub name: String,
+    pub criteria: StudentSearchCriteria,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Serialize)]
+pub struct StudentSearchHitResponse {
+    pub score: u32,
+    pub matched_field: String,
+    pub student: StudentResponse,
+}
+
+#[derive(Default, Deserialize)]
+pub struct CashSearchFilter {
+    pub student_id: Option<u64>,
+    pub min_amount: Option<i64>,
+    pub max_amount: Option<i64>,
+    pub has_installment: Option<bool>,
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct StudentSummaryResponse {
+    pub uid: u64,
+    pub name: String,
+    pub class: String,
+    pub subject: String,
+    pub phone: String,
+    pub lesson_left: Option<u32>,
+    pub membership_end_local_date: Option<String>,
+    pub is_membership_active: bool,
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TransactionSummaryResponse {
+    pub uid: u64,
+    pub business_date: Option<String>,
+    pub student_id: Option<u64>,
+    pub amount: i64,
+    pub note: Option<String>,
+    pub installment_status: Option<String>,
+    pub installment_due_local_date: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum StudentListItem {
+    Full(Box<StudentResponse>),
+    Summary(StudentSummaryResponse),
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum TransactionListItem {
+    Full(Box<TransactionResponse>),
+    Summary(TransactionSummaryResponse),
+}
+
+#[derive(Serialize)]
+pub struct FieldChangeResponse {
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CashAuditEntryResponse {
+    pub id: u64,
+    pub cash_uid: u64,
+    pub changes: Vec<FieldChangeResponse>,
+    pub operator: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+#[derive(Serialize)]
+pub struct ReceiptResponse {
+    pub transaction_uid: u64,
+    pub receipt_number: String,
+    pub reprint_count: u32,
+    pub path: String,
+}
+
+#[derive(Serialize)]
+pub struct MonthlyStatementResponse {
+    pub student_uid: u64,
+    pub student_name: String,
+    pub path: String,
+    pub transaction_count: usize,
+    pub total: i64,
+}
+
+#[derive(Serialize)]
+pub struct FinancialReportResponse {
+    pub report: FinancialReport,
+    pub pdf_path: Option<String>,
+    pub xlsx_path: Option<String>,
+    pub timezone: String,
+}
+
+#[derive(Serialize)]
+pub struct RevenueTimeseriesResponse {
+    pub granularity: String,
+    pub split_by: Option<String>,
+    pub buckets: Vec<SeriesBucket>,
+    pub totals: SeriesValues,
+    pub period_start_date: String,
+    pub period_end_date: String,
+    pub timezone: String,
+}
+
+#[derive(Serialize)]
+pub struct RevenueRecognitionResponse {
+    pub months: Vec<MonthlyRecognition>,
+    /// 按会员期确认的收款笔数
+    pub membership_payment_count: usize,
+    /// 按体验课次数确认的收款笔数
+    pub lesson_pack_payment_count: usize,
+    /// 没有课时消耗记录、只能按收款确认的课时费笔数（区间内）
+    pub unscheduled_lesson_pack_count: usize,
+    pub timezone: String,
+}
+
+#[derive(Serialize)]
+pub struct AgingInstallmentResponse {
+    pub transaction_uid: u64,
+    pub plan_id: u64,
+    pub installment_current: u32,
+    pub installment_total: u32,
+    pub amount: i64,
+    pub due_local_date: String,
+    pub days_past_due: i64,
+    /// current / 1-30 / 31-60 / 61-90 / 90+
+    pub bucket: String,
+    pub status: String,
+}
+
+#[derive(Serialize)]
+pub struct StudentAgingResponse {
+    pub student_uid: u64,
+    /// 学员已删除时为空
+    pub name: Option<String>,
+    pub phone: Option<String>,
+    pub guardians: Vec<StudentGuardianResponse>,
+    pub buckets: AgingBuckets,
+    pub installments: Vec<AgingInstallmentResponse>,
+    /// 待付款的滞纳金合计（不计入账龄分段）
+    pub late_fee_total: i64,
+    pub late_fees: Vec<LateFeeResponse>,
+}
+
+#[derive(Serialize)]
+pub struct UnassignedAgingResponse {
+    pub buckets: AgingBuckets,
+    pub installments: Vec<AgingInstallmentResponse>,
+    /// 待付款的滞纳金合计（不计入账龄分段）
+    pub late_fee_total: i64,
+    pub late_fees: Vec<LateFeeResponse>,
+}
+
+#[derive(Serialize)]
+pub struct ReceivablesAgingResponse {
+    pub as_of_date: String,
+    pub totals: AgingBuckets,
+    /// 待付款的滞纳金合计（不计入账龄分段）
+    pub late_fee_total: i64,
+    pub students: Vec<StudentAgingResponse>,
+    /// 未关联学员的分期
+    pub unassigned: Option<UnassignedAgingResponse>,
+    pub timezone: String,
+}
+
+#[derive(Serialize)]
+pub struct LateFeePolicyResponse {
+    pub plan_id: u64,
+    pub kind: String,
+    pub value: i64,
+    pub grace_days: u32,
+    pub cap: Option<i64>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Serialize)]
+pub struct LateFeeResponse {
+    pub id: u64,
+    pub plan_id: u64,
+    pub installment_uid: u64,
+    pub student_uid: Option<u64>,
+    pub amount: i64,
+    pub status: String,
+    pub created_at: String,
+    pub payment_uid: Option<u64>,
+    pub paid_at: Option<String>,
+    pub waiver_reason: Option<String>,
+    pub waived_by: Option<String>,
+    pub waived_at: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PlanPayoffResponse {
+    pub plan_id: u64,
+    /// 结清前的未付余额
+    pub remaining_balance: i64,
+    pub discount_amount: i64,
+    pub amount_paid: i64,
+    pub payment: TransactionResponse,
+    /// 随结清标记为已付款的原未付分期
+    pub settled_uids: Vec<u64>,
 }
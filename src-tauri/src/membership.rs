@@ -0,0 +1,131 @@
+//! 会员模块 - 会员冻结、会员期限计算等扩展逻辑
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::timezone::local_days_between;
+
+/// 单次会员冻结记录，`end_date` 为空表示冻结仍在进行中
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MembershipFreeze {
+    pub start_date: DateTime<Utc>,
+    pub end_date: Option<DateTime<Utc>>,
+    pub frozen_days: i64,
+    pub reason: Option<String>,
+}
+
+impl MembershipFreeze {
+    pub fn new(start_date: DateTime<Utc>, reason: Option<String>) -> Self {
+        Self {
+            start_date,
+            end_date: None,
+            frozen_days: 0,
+            reason,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.end_date.is_none()
+    }
+}
+
+/// 计算冻结天数（按俱乐部本地自然日计算，不足一天不计）
+pub fn frozen_days_between(start: &DateTime<Utc>, end: &DateTime<Utc>) -> i64 {
+    local_days_between(start, end).max(0)
+}
+
+/// 查找当前进行中的冻结记录
+pub fn active_freeze(freezes: &[MembershipFreeze]) -> Option<&MembershipFreeze> {
+    freezes.iter().find(|f| f.is_active())
+}
+
+/// 一段会员期（每次开通/续费会员都会新增一条），用于会员历史与留存统计
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MembershipPeriod {
+    pub id: u64,
+    pub membership_type: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub payment_uid: Option<u64>,
+    pub operator: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// 本期内累计冻结天数（已计入 end_date 顺延）
+    #[serde(default)]
+    pub frozen_days: i64,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub cancel_reason: Option<String>,
+}
+
+impl MembershipPeriod {
+    /// 会员期状态：cancelled / upcoming / active / expired
+    pub fn status(&self, now: &DateTime<Utc>) -> &'static str {
+        if self.cancelled_at.is_some() {
+            "cancelled"
+        } else if self.start_date > *now {
+            "upcoming"
+        } else if self.end_date >= *now {
+            "active"
+        } else {
+            "expired"
+        }
+    }
+}
+
+/// 最近一条未取消的会员期
+pub fn current_period_mut(periods: &mut [MembershipPeriod]) -> Option<&mut MembershipPeriod> {
+    periods
+        .iter_mut()
+        .filter(|p| p.cancelled_at.is_none())
+        .max_by_key(|p| p.created_at)
+}
+
+/// 会员期限（按自然月或按天）
+pub enum MembershipPlan {
+    Months(u32),
+    Days(u32),
+}
+
+/// 根据会员类型解析会员期限
+pub fn parse_membership_plan(
+    membership_type: &str,
+    custom_days: Option<u32>,
+    custom_months: Option<u32>,
+) -> Result<MembershipPlan, String> {
+    match membership_type {
+        "month" => Ok(MembershipPlan::Months(1)),
+        "quarter" => Ok(MembershipPlan::Months(3)),
+        "half_year" => Ok(MembershipPlan::Months(6)),
+        "year" => Ok(MembershipPlan::Months(12)),
+        "days" => custom_days
+            .map(MembershipPlan::Days)
+            .ok_or_else(|| "自定义天数会员需要指定天数".to_string()),
+        "months" => custom_months
+            .map(MembershipPlan::Months)
+            .ok_or_else(|| "自定义月数会员需要指定月数".to_string()),
+        _ => Err(format!("无效的会员类型: {}", membership_type)),
+    }
+}
+
+/// 计算会员的最后一天（含当天）
+///
+/// 按自然月计算：1月15日开始的月卡到2月14日结束；若目标月份没有对应日期
+/// （如1月31日开始），则截止到目标月份的最后一天。
+pub fn membership_last_day(start: NaiveDate, plan: &MembershipPlan) -> Result<NaiveDate, String> {
+    let last_day = match plan {
+        MembershipPlan::Months(months) => {
+            let anniversary = start
+                .checked_add_months(Months::new(*months))
+                .ok_or("会员结束日期超出范围")?;
+            if anniversary.day() < start.day() {
+                // 目标月份天数不足，已被截断到月末
+                Some(anniversary)
+            } else {
+                anniversary.checked_sub_days(Days::new(1))
+            }
+        }
+        MembershipPlan::Days(days) => {
+            start.checked_add_days(Days::new(u64::from(days.saturating_sub(1))))
+        }
+    };
+    last_day.ok_or_else(|| "会员结束日期超出范围".to_string())
+}
@@ -0,0 +1,71 @@
+//! 分页模块 - 列表命令的分页、排序与精简字段参数
+
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// 默认每页条数
+pub const DEFAULT_PAGE_SIZE: usize = 50;
+/// 每页最大条数
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// 分页请求
+#[derive(Clone, Default, Deserialize)]
+pub struct PageRequest {
+    /// 跳过的条数，默认0
+    pub offset: Option<usize>,
+    /// 每页条数，默认50，最大500
+    pub limit: Option<usize>,
+    /// 排序字段，可选值由各命令定义
+    pub sort_by: Option<String>,
+    /// 是否倒序
+    pub descending: Option<bool>,
+    /// 只返回列表视图需要的精简字段
+    pub summary: Option<bool>,
+}
+
+/// 分页结果
+#[derive(Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// 符合条件的总条数
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    /// 下一页的 offset，没有下一页时为 None
+    pub next_offset: Option<usize>,
+}
+
+impl PageRequest {
+    pub fn limit(&self) -> usize {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
+    }
+
+    pub fn is_summary(&self) -> bool {
+        self.summary.unwrap_or(false)
+    }
+
+    pub fn is_descending(&self) -> bool {
+        self.descending.unwrap_or(false)
+    }
+
+    /// 当前页在已排序结果中的下标范围
+    pub fn window(&self, total: usize) -> Range<usize> {
+        let start = self.offset.unwrap_or(0).min(total);
+        let end = start.saturating_add(self.limit()).min(total);
+        start..end
+    }
+
+    /// 由当前页的数据组装分页结果
+    pub fn page<T>(&self, items: Vec<T>, total: usize) -> Page<T> {
+        let window = self.window(total);
+        Page {
+            items,
+            total,
+            offset: window.start,
+            limit: self.limit(),
+            next_offset: (window.end < total).then_some(window.end),
+        }
+    }
+}
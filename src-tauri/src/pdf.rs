@@ -0,0 +1,243 @@
+//! PDF模块 - 收据、对账单等打印文档的简单排版
+//!
+//! 完全离线生成：中文使用PDF阅读器内置的 STSong-Light 字体（Adobe-GB1），
+//! 不需要嵌入字体文件；Logo 仅支持 JPEG，原样写入无需解码。
+
+use pdf_writer::types::{CidFontType, FontFlags, SystemInfo};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+/// A4 纸张尺寸（单位：点）
+pub const PAGE_WIDTH: f32 = 595.0;
+pub const PAGE_HEIGHT: f32 = 842.0;
+/// 页边距
+pub const MARGIN: f32 = 50.0;
+
+const FONT_NAME: Name = Name(b"F1");
+const LOGO_NAME: Name = Name(b"Im1");
+
+/// 文字对齐方式（x 为对齐点）
+#[derive(Clone, Copy)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+/// JPEG 格式的 Logo 图片
+pub struct Logo {
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    components: u8,
+}
+
+impl Logo {
+    /// 读取 JPEG 文件头中的尺寸和颜色通道数
+    pub fn from_jpeg(data: Vec<u8>) -> Result<Self, String> {
+        if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+            return Err("Logo 必须是 JPEG 格式图片".to_string());
+        }
+        let mut pos = 2;
+        while pos + 9 < data.len() {
+            if data[pos] != 0xFF {
+                pos += 1;
+                continue;
+            }
+            let marker = data[pos + 1];
+            let length = usize::from(u16::from_be_bytes([data[pos + 2], data[pos + 3]]));
+            // SOF0-SOF15（不含 DHT/JPG/DAC）记录了图片尺寸
+            if (0xC0..=0xCF).contains(&marker) && !matches!(marker, 0xC4 | 0xC8 | 0xCC) {
+                let height = u32::from(u16::from_be_bytes([data[pos + 5], data[pos + 6]]));
+                let width = u32::from(u16::from_be_bytes([data[pos + 7], data[pos + 8]]));
+                let components = data[pos + 9];
+                if width == 0 || height == 0 || !matches!(components, 1 | 3 | 4) {
+                    break;
+                }
+                return Ok(Self {
+                    data,
+                    width,
+                    height,
+                    components,
+                });
+            }
+            pos += 2 + length;
+        }
+        Err("无法读取 Logo 图片尺寸".to_string())
+    }
+
+    /// 按指定高度等比缩放后的宽度
+    pub fn width_for_height(&self, height: f32) -> f32 {
+        height * self.width as f32 / self.height as f32
+    }
+}
+
+/// 多页PDF文档
+pub struct PdfDocument {
+    title: String,
+    logo: Option<Logo>,
+    pages: Vec<Content>,
+    current: Content,
+}
+
+impl PdfDocument {
+    pub fn new(title: &str, logo: Option<Logo>) -> Self {
+        Self {
+            title: title.to_string(),
+            logo,
+            pages: Vec::new(),
+            current: Content::new(),
+        }
+    }
+
+    pub fn logo(&self) -> Option<&Logo> {
+        self.logo.as_ref()
+    }
+
+    /// 开始新的一页
+    pub fn new_page(&mut self) {
+        let page = std::mem::replace(&mut self.current, Content::new());
+        self.pages.push(page);
+    }
+
+    /// 在 (x, y) 处写一行文字，y 为基线位置
+    pub fn text(&mut self, x: f32, y: f32, size: f32, text: &str, align: Align) {
+        let x = match align {
+            Align::Left => x,
+            Align::Center => x - text_width(text, size) / 2.0,
+            Align::Right => x - text_width(text, size),
+        };
+        self.current
+            .begin_text()
+            .set_font(FONT_NAME, size)
+            .next_line(x, y)
+            .show(Str(&encode_text(text)))
+            .end_text();
+    }
+
+    /// 画一条直线
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, width: f32) {
+        self.current
+            .set_line_width(width)
+            .move_to(x1, y1)
+            .line_to(x2, y2)
+            .stroke();
+    }
+
+    /// 在指定区域绘制 Logo（没有 Logo 时不处理）
+    pub fn draw_logo(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        if self.logo.is_none() {
+            return;
+        }
+        self.current
+            .save_state()
+            .transform([width, 0.0, 0.0, height, x, y])
+            .x_object(LOGO_NAME)
+            .restore_state();
+    }
+
+    /// 生成PDF文件内容
+    pub fn finish(mut self) -> Vec<u8> {
+        self.new_page();
+
+        let catalog_id = Ref::new(1);
+        let page_tree_id = Ref::new(2);
+        let font_id = Ref::new(3);
+        let cid_font_id = Ref::new(4);
+        let descriptor_id = Ref::new(5);
+        let logo_id = Ref::new(6);
+        let info_id = Ref::new(7);
+        let page_ids: Vec<Ref> = (0..self.pages.len())
+            .map(|i| Ref::new(8 + 2 * i as i32))
+            .collect();
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(page_tree_id);
+        pdf.pages(page_tree_id)
+            .kids(page_ids.iter().copied())
+            .count(page_ids.len() as i32);
+        pdf.document_info(info_id)
+            .title(TextStr(&self.title))
+            .producer(TextStr("QMX"));
+
+        // 内置中文字体：STSong-Light + UniGB-UCS2-H 编码
+        pdf.type0_font(font_id)
+            .base_font(Name(b"STSong-Light"))
+            .encoding_predefined(Name(b"UniGB-UCS2-H"))
+            .descendant_font(cid_font_id);
+        let mut cid_font = pdf.cid_font(cid_font_id);
+        cid_font
+            .subtype(CidFontType::Type0)
+            .base_font(Name(b"STSong-Light"))
+            .system_info(SystemInfo {
+                registry: Str(b"Adobe"),
+                ordering: Str(b"GB1"),
+                supplement: 2,
+            })
+            .font_descriptor(descriptor_id)
+            .default_width(1000.0);
+        // Adobe-GB1 的 CID 1-95 为半角西文字符
+        cid_font.widths().same(1, 95, 500.0);
+        cid_font.finish();
+        pdf.font_descriptor(descriptor_id)
+            .name(Name(b"STSong-Light"))
+            .flags(FontFlags::SYMBOLIC)
+            .bbox(Rect::new(-25.0, -254.0, 1000.0, 880.0))
+            .italic_angle(0.0)
+            .ascent(880.0)
+            .descent(-120.0)
+            .cap_height(880.0)
+            .stem_v(93.0);
+
+        if let Some(logo) = &self.logo {
+            let mut image = pdf.image_xobject(logo_id, &logo.data);
+            image.filter(Filter::DctDecode);
+            image
+                .width(logo.width as i32)
+                .height(logo.height as i32)
+                .bits_per_component(8);
+            match logo.components {
+                1 => image.color_space().device_gray(),
+                4 => image.color_space().device_cmyk(),
+                _ => image.color_space().device_rgb(),
+            }
+            image.finish();
+        }
+
+        for (content, page_id) in self.pages.into_iter().zip(&page_ids) {
+            let content_id = Ref::new(page_id.get() + 1);
+            let mut page = pdf.page(*page_id);
+            page.parent(page_tree_id)
+                .media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+                .contents(content_id);
+            let mut resources = page.resources();
+            resources.fonts().pair(FONT_NAME, font_id);
+            if self.logo.is_some() {
+                resources.x_objects().pair(LOGO_NAME, logo_id);
+            }
+            resources.finish();
+            page.finish();
+            pdf.stream(content_id, &content.finish());
+        }
+
+        pdf.finish()
+    }
+}
+
+/// 估算文字宽度：西文字符按半角、其他字符按全角计算
+pub fn text_width(text: &str, size: f32) -> f32 {
+    text.chars()
+        .map(|c| if (' '..='~').contains(&c) { 0.5 } else { 1.0 })
+        .sum::<f32>()
+        * size
+}
+
+/// 按 UCS-2 大端编码文字，基本平面以外的字符替换为 '?'
+fn encode_text(text: &str) -> Vec<u8> {
+    text.chars()
+        .filter(|c| !c.is_control())
+        .flat_map(|c| {
+            let code = u16::try_from(u32::from(c)).unwrap_or(u16::from(b'?'));
+            code.to_be_bytes()
+        })
+        .collect()
+}
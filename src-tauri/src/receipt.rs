@@ -0,0 +1,388 @@
+//! 收据模块 - 收据编号、大写金额及收据/月度对账单的PDF排版
+//!
+//! 收据编号按年份连续编号（如 R2026-000001），同一笔记录补打时沿用原编号。
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::pdf::{Align, Logo, PdfDocument, MARGIN, PAGE_HEIGHT, PAGE_WIDTH};
+use crate::TransactionResponse;
+
+/// 未设置俱乐部名称时使用的默认名称
+pub const DEFAULT_CLUB_NAME: &str = "启明星";
+
+/// 已开具的收据，按现金记录UID保存
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReceiptRecord {
+    pub number: String,
+    pub issued_at: DateTime<Utc>,
+    /// 补打次数（首次开具为0）
+    pub reprint_count: u32,
+}
+
+/// 收据编号：R + 年份 + 当年序号
+pub fn format_receipt_number(year: i32, seq: u64) -> String {
+    format!("R{}-{:06}", year, seq)
+}
+
+/// 打印抬头使用的俱乐部信息
+pub struct ClubProfile {
+    pub name: String,
+    pub logo: Option<Logo>,
+}
+
+/// 对账单中的一行
+pub struct StatementLine<'a> {
+    pub transaction: &'a TransactionResponse,
+    pub receipt_number: Option<String>,
+}
+
+const DIGITS: [char; 10] = ['零', '壹', '贰', '叁', '肆', '伍', '陆', '柒', '捌', '玖'];
+const SECTION_UNITS: [&str; 5] = ["", "万", "亿", "万亿", "亿亿"];
+
+/// 四位以内的数字转大写（不含万、亿等节单位）
+fn section_to_upper(section: u64) -> String {
+    let mut result = String::new();
+    let mut pending_zero = false;
+    for (place, unit) in [(1000, "仟"), (100, "佰"), (10, "拾"), (1, "")] {
+        let digit = (section / place % 10) as usize;
+        if digit == 0 {
+            pending_zero = !result.is_empty();
+            continue;
+        }
+        if pending_zero {
+            result.push('零');
+            pending_zero = false;
+        }
+        result.push(DIGITS[digit]);
+        result.push_str(unit);
+    }
+    result
+}
+
+/// 金额（元）转中文大写，如 1205 → 壹仟贰佰零伍元整
+pub fn amount_in_chinese_upper(amount: i64) -> String {
+    let mut value = amount.unsigned_abs();
+    if value == 0 {
+        return "零元整".to_string();
+    }
+    let mut sections = Vec::new();
+    while value > 0 {
+        sections.push(value % 10_000);
+        value /= 10_000;
+    }
+
+    let mut result = String::new();
+    let mut pending_zero = false;
+    for (index, section) in sections.iter().enumerate().rev() {
+        if *section == 0 {
+            pending_zero = !result.is_empty();
+            continue;
+        }
+        if !result.is_empty() && (pending_zero || *section < 1000) {
+            result.push('零');
+        }
+        pending_zero = false;
+        result.push_str(&section_to_upper(*section));
+        result.push_str(SECTION_UNITS[index]);
+    }
+
+    if amount < 0 {
+        format!("负{}元整", result)
+    } else {
+        format!("{}元整", result)
+    }
+}
+
+/// 金额（元）格式化为 ￥1,200.00（全角符号，内置中文字体均可显示）
+pub fn format_yuan(amount: i64) -> String {
+    let digits = amount.unsigned_abs().to_string();
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    let sign = if amount < 0 { "-" } else { "" };
+    format!("{}￥{}.00", sign, grouped)
+}
+
+/// 交易的项目名称：优先使用收支类别，其次使用付款描述
+fn item_description(transaction: &TransactionResponse) -> String {
+    transaction
+        .category_label
+        .clone()
+        .unwrap_or_else(|| transaction.description.clone())
+}
+
+/// 截断过长的文字，避免超出表格列宽
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// 页眉：Logo、俱乐部名称和标题，返回页眉下方的起始位置
+fn draw_header(doc: &mut PdfDocument, club_name: &str, title: &str) -> f32 {
+    let mut y = PAGE_HEIGHT - MARGIN;
+    let logo_height = 40.0;
+    if let Some(logo_width) = doc.logo().map(|logo| logo.width_for_height(logo_height)) {
+        doc.draw_logo(MARGIN, y - logo_height, logo_width, logo_height);
+    }
+    y -= 26.0;
+    doc.text(PAGE_WIDTH / 2.0, y, 18.0, club_name, Align::Center);
+    y -= 28.0;
+    doc.text(PAGE_WIDTH / 2.0, y, 15.0, title, Align::Center);
+    y -= 14.0;
+    doc.line(MARGIN, y, PAGE_WIDTH - MARGIN, y, 1.0);
+    y - 22.0
+}
+
+/// 单笔收入的收据
+pub fn render_receipt(
+    club: ClubProfile,
+    receipt: &ReceiptRecord,
+    student_name: Option<&str>,
+    transaction: &TransactionResponse,
+    printed_at: &str,
+) -> Vec<u8> {
+    let title = if receipt.reprint_count > 0 {
+        format!("收  据（补打第{}次）", receipt.reprint_count)
+    } else {
+        "收  据".to_string()
+    };
+    let mut doc = PdfDocument::new(&format!("收据 {}", receipt.number), club.logo);
+    let left = MARGIN;
+    let right = PAGE_WIDTH - MARGIN;
+    let mut y = draw_header(&mut doc, &club.name, &title);
+
+    doc.text(
+        left,
+        y,
+        11.0,
+        &format!("收据编号：{}", receipt.number),
+        Align::Left,
+    );
+    let date = transaction.business_date.as_deref().unwrap_or("-");
+    doc.text(right, y, 11.0, &format!("日期：{}", date), Align::Right);
+    y -= 20.0;
+    let student = match (student_name, transaction.student_id) {
+        (Some(name), Some(uid)) => format!("{}（编号 {}）", name, uid),
+        (None, Some(uid)) => format!("编号 {}", uid),
+        _ => "-".to_string(),
+    };
+    doc.text(left, y, 11.0, &format!("学员：{}", student), Align::Left);
+    y -= 26.0;
+
+    // 项目明细
+    doc.line(left, y + 14.0, right, y + 14.0, 0.5);
+    doc.text(left, y, 11.0, "项目", Align::Left);
+    doc.text(right, y, 11.0, "金额", Align::Right);
+    y -= 8.0;
+    doc.line(left, y, right, y, 0.5);
+    y -= 18.0;
+
+    let description = item_description(transaction);
+    let item_amount = transaction.list_price.unwrap_or(transaction.amount);
+    doc.text(left, y, 11.0, &truncate(&description, 30), Align::Left);
+    doc.text(right, y, 11.0, &format_yuan(item_amount), Align::Right);
+    y -= 18.0;
+    if let Some(note) = transaction.note.as_deref().filter(|n| !n.trim().is_empty()) {
+        doc.text(left + 12.0, y, 9.0, &truncate(note.trim(), 50), Align::Left);
+        y -= 16.0;
+    }
+    if let Some(discount) = transaction.discount_amount.filter(|d| *d != 0) {
+        let label = match &transaction.coupon_code {
+            Some(code) => format!("优惠（优惠券 {}）", code),
+            None => "优惠".to_string(),
+        };
+        doc.text(left, y, 11.0, &label, Align::Left);
+        doc.text(right, y, 11.0, &format_yuan(-discount), Align::Right);
+        y -= 18.0;
+    }
+    doc.line(left, y + 8.0, right, y + 8.0, 0.5);
+    y -= 10.0;
+
+    doc.text(
+        left,
+        y,
+        12.0,
+        &format!("合计（小写）：{}", format_yuan(transaction.amount)),
+        Align::Left,
+    );
+    y -= 20.0;
+    doc.text(
+        left,
+        y,
+        12.0,
+        &format!(
+            "合计（大写）：{}",
+            amount_in_chinese_upper(transaction.amount)
+        ),
+        Align::Left,
+    );
+    y -= 26.0;
+
+    let payment_method = transaction.payment_method_label.as_deref().unwrap_or("-");
+    let operator = transaction.operator.as_deref().unwrap_or("-");
+    doc.text(
+        left,
+        y,
+        11.0,
+        &format!("付款方式：{}", payment_method),
+        Align::Left,
+    );
+    doc.text(
+        right,
+        y,
+        11.0,
+        &format!("经办人：{}", operator),
+        Align::Right,
+    );
+    y -= 30.0;
+    doc.text(
+        left,
+        y,
+        9.0,
+        &format!("打印时间：{}", printed_at),
+        Align::Left,
+    );
+
+    doc.finish()
+}
+
+/// 学员的月度对账单，明细过多时自动分页
+pub fn render_statement(
+    club: ClubProfile,
+    month: &str,
+    student_name: &str,
+    student_uid: u64,
+    lines: &[StatementLine],
+    printed_at: &str,
+) -> Vec<u8> {
+    let mut doc = PdfDocument::new(&format!("{} 月度对账单 {}", student_name, month), club.logo);
+    let left = MARGIN;
+    let right = PAGE_WIDTH - MARGIN;
+    let columns = [left, left + 80.0, left + 200.0, left + 300.0];
+    let mut y = draw_header(&mut doc, &club.name, &format!("月度对账单（{}）", month));
+
+    doc.text(
+        left,
+        y,
+        11.0,
+        &format!("学员：{}（编号 {}）", student_name, student_uid),
+        Align::Left,
+    );
+    doc.text(
+        right,
+        y,
+        11.0,
+        &format!("打印时间：{}", printed_at),
+        Align::Right,
+    );
+    y -= 26.0;
+
+    let draw_table_header = |doc: &mut PdfDocument, y: f32| -> f32 {
+        doc.line(left, y + 14.0, right, y + 14.0, 0.5);
+        for (x, label) in columns.iter().zip(["日期", "收据编号", "付款方式", "项目"]) {
+            doc.text(*x, y, 10.0, label, Align::Left);
+        }
+        doc.text(right, y, 10.0, "金额", Align::Right);
+        doc.line(left, y - 8.0, right, y - 8.0, 0.5);
+        y - 26.0
+    };
+    y = draw_table_header(&mut doc, y);
+
+    let mut total = 0;
+    for line in lines {
+        if y < MARGIN + 60.0 {
+            doc.new_page();
+            y = draw_table_header(&mut doc, PAGE_HEIGHT - MARGIN - 14.0);
+        }
+        let transaction = line.transaction;
+        total += transaction.amount;
+        let cells = [
+            transaction.business_date.clone().unwrap_or_default(),
+            line.receipt_number
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+            transaction
+                .payment_method_label
+                .clone()
+                .unwrap_or_else(|| "-".to_string()),
+            truncate(&item_description(transaction), 12),
+        ];
+        for (x, cell) in columns.iter().zip(&cells) {
+            doc.text(*x, y, 10.0, cell, Align::Left);
+        }
+        doc.text(
+            right,
+            y,
+            10.0,
+            &format_yuan(transaction.amount),
+            Align::Right,
+        );
+        y -= 18.0;
+    }
+
+    if y < MARGIN + 50.0 {
+        doc.new_page();
+        y = PAGE_HEIGHT - MARGIN - 14.0;
+    }
+    doc.line(left, y + 8.0, right, y + 8.0, 0.5);
+    y -= 10.0;
+    doc.text(
+        left,
+        y,
+        12.0,
+        &format!("本月合计：{}（{}笔）", format_yuan(total), lines.len()),
+        Align::Left,
+    );
+    y -= 20.0;
+    doc.text(
+        left,
+        y,
+        12.0,
+        &format!("大写：{}", amount_in_chinese_upper(total)),
+        Align::Left,
+    );
+
+    doc.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chinese_upper_zero() {
+        assert_eq!(amount_in_chinese_upper(0), "零元整");
+    }
+
+    #[test]
+    fn chinese_upper_fills_zero_inside_section() {
+        assert_eq!(amount_in_chinese_upper(1005), "壹仟零伍元整");
+        assert_eq!(amount_in_chinese_upper(1205), "壹仟贰佰零伍元整");
+        assert_eq!(amount_in_chinese_upper(1050), "壹仟零伍拾元整");
+        assert_eq!(amount_in_chinese_upper(10), "壹拾元整");
+    }
+
+    #[test]
+    fn chinese_upper_fills_zero_across_sections() {
+        assert_eq!(amount_in_chinese_upper(10_000_005), "壹仟万零伍元整");
+        assert_eq!(amount_in_chinese_upper(100_001_000), "壹亿零壹仟元整");
+        assert_eq!(amount_in_chinese_upper(10_500), "壹万零伍佰元整");
+        assert_eq!(amount_in_chinese_upper(10_000), "壹万元整");
+        assert_eq!(amount_in_chinese_upper(100_000_000), "壹亿元整");
+    }
+
+    #[test]
+    fn chinese_upper_negative() {
+        assert_eq!(amount_in_chinese_upper(-1005), "负壹仟零伍元整");
+        assert_eq!(amount_in_chinese_upper(-10_000_005), "负壹仟万零伍元整");
+    }
+}
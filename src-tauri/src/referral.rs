@@ -0,0 +1,27 @@
+//! 转介绍模块 - 推荐关系与推荐奖励
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 推荐奖励设置，新学员入学时自动发放给推荐人
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ReferralReward {
+    /// 赠送课时
+    pub extra_lessons: u32,
+    /// 赠送会员天数（推荐人有会员时顺延结束日期，会员已过期时从当天起计算）
+    pub membership_days: u32,
+}
+
+/// 推荐关系，按被推荐学员UID保存
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Referral {
+    pub referrer_uid: u64,
+    pub created_at: DateTime<Utc>,
+    /// 实际发放的赠送课时
+    pub rewarded_lessons: u32,
+    /// 实际发放的赠送会员天数
+    pub rewarded_days: u32,
+    /// 使用了推荐折扣的交易（被推荐学员仅首笔付款自动享受推荐折扣）
+    #[serde(default)]
+    pub discount_cash_uid: Option<u64>,
+}
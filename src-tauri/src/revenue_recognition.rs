@@ -0,0 +1,164 @@
+//! 收入确认模块 - 权责发生制下会员费和课时包收入的分期确认
+//!
+//! - 会员费：关联到会员期（`MembershipPeriod::payment_uid`）的付款，按会员期天数逐日平均确认；
+//!   会员期被取消时，在取消当天确认剩余部分
+//! - 课时包：体验课包的付款（类别为 trial），按签到扣除的次数确认；转为正式会员或到期时，
+//!   未使用的次数一次性确认
+//! - 普通课时费（类别为 tuition）：剩余课时只有数量、没有与付款对应的消耗记录，无法按消耗确认，
+//!   在收款的业务日期确认，并单独列为 `recognized_lesson_packs_on_receipt` 提示会计人工复核
+//! - 其他收入在收款的业务日期确认
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// 延期确认的收入类型
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeferredKind {
+    Membership,
+    LessonPack,
+}
+
+/// 收入确认计划
+pub enum RevenueSchedule {
+    /// 在起止日期（含）之间逐日平均确认
+    Straight { start: NaiveDate, end: NaiveDate },
+    /// 按使用次数确认，break_date 当天起未使用部分全部确认
+    Usage {
+        total_units: u32,
+        usage_dates: Vec<NaiveDate>,
+        break_date: NaiveDate,
+    },
+}
+
+/// 需要延期确认的一笔收款
+pub struct DeferredPayment {
+    pub cash_uid: u64,
+    pub kind: DeferredKind,
+    pub amount: i64,
+    /// 收款的业务日期
+    pub received_on: NaiveDate,
+    pub schedule: RevenueSchedule,
+}
+
+impl DeferredPayment {
+    /// 截至指定日期（含）累计确认的金额，按累计值取整，各月之和等于总额
+    pub fn recognized_through(&self, date: NaiveDate) -> i64 {
+        let (done, total) = match &self.schedule {
+            RevenueSchedule::Straight { start, end } => {
+                if date < *start {
+                    return 0;
+                }
+                if date >= *end {
+                    return self.amount;
+                }
+                (
+                    (date - *start).num_days() + 1,
+                    (*end - *start).num_days() + 1,
+                )
+            }
+            RevenueSchedule::Usage {
+                total_units,
+                usage_dates,
+                break_date,
+            } => {
+                if *break_date <= date || *total_units == 0 {
+                    return self.amount;
+                }
+                let used = usage_dates.iter().filter(|d| **d <= date).count() as i64;
+                (used.min(i64::from(*total_units)), i64::from(*total_units))
+            }
+        };
+        (i128::from(self.amount) * i128::from(done) / i128::from(total)) as i64
+    }
+
+    /// 区间（含首尾）内确认的金额
+    pub fn recognized_between(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        let before = start
+            .pred_opt()
+            .map_or(0, |day| self.recognized_through(day));
+        self.recognized_through(end) - before
+    }
+}
+
+/// 一个月的收入确认情况
+#[derive(Clone, Serialize)]
+pub struct MonthlyRecognition {
+    /// YYYY-MM
+    pub month: String,
+    /// 当月实际收款（收付实现制收入）
+    pub cash_received: i64,
+    /// 当月确认的收入合计
+    pub recognized_revenue: i64,
+    pub recognized_membership: i64,
+    pub recognized_lesson_packs: i64,
+    /// 没有消耗记录、在收款当月确认的课时费（未按权责发生制处理，需人工复核）
+    pub recognized_lesson_packs_on_receipt: i64,
+    /// 不需要延期确认、在收款当月确认的收入
+    pub recognized_other: i64,
+    /// 当月新增的待确认收入（会员费、课时包收款）
+    pub deferred_added: i64,
+    /// 月末尚未确认的收入余额
+    pub deferred_balance: i64,
+}
+
+/// 按月汇总确认收入和待确认余额
+///
+/// lesson_packs_on_receipt 为无法按消耗确认的课时费，other_income 为不需要延期确认的收入，
+/// 均为（业务日期, 金额）
+pub fn monthly_recognition(
+    months: &[(NaiveDate, NaiveDate)],
+    payments: &[DeferredPayment],
+    lesson_packs_on_receipt: &[(NaiveDate, i64)],
+    other_income: &[(NaiveDate, i64)],
+) -> Vec<MonthlyRecognition> {
+    months
+        .iter()
+        .map(|(start, end)| {
+            let in_month = |date: &NaiveDate| date >= start && date <= end;
+            let recognized_of = |kind: DeferredKind| -> i64 {
+                payments
+                    .iter()
+                    .filter(|p| p.kind == kind)
+                    .map(|p| p.recognized_between(*start, *end))
+                    .sum()
+            };
+            let recognized_membership = recognized_of(DeferredKind::Membership);
+            let recognized_lesson_packs = recognized_of(DeferredKind::LessonPack);
+            let received_in_month = |income: &[(NaiveDate, i64)]| -> i64 {
+                income
+                    .iter()
+                    .filter(|(date, _)| in_month(date))
+                    .map(|(_, amount)| amount)
+                    .sum()
+            };
+            let recognized_lesson_packs_on_receipt = received_in_month(lesson_packs_on_receipt);
+            let recognized_other = received_in_month(other_income);
+            let deferred_added: i64 = payments
+                .iter()
+                .filter(|p| in_month(&p.received_on))
+                .map(|p| p.amount)
+                .sum();
+            let deferred_balance = payments
+                .iter()
+                .filter(|p| p.received_on <= *end)
+                .map(|p| p.amount - p.recognized_through(*end))
+                .sum();
+            MonthlyRecognition {
+                month: start.format("%Y-%m").to_string(),
+                cash_received: recognized_lesson_packs_on_receipt
+                    + recognized_other
+                    + deferred_added,
+                recognized_revenue: recognized_membership
+                    + recognized_lesson_packs
+                    + recognized_lesson_packs_on_receipt
+                    + recognized_other,
+                recognized_membership,
+                recognized_lesson_packs,
+                recognized_lesson_packs_on_receipt,
+                recognized_other,
+                deferred_added,
+                deferred_balance,
+            }
+        })
+        .collect()
+}
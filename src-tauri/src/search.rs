@@ -0,0 +1,310 @@
+//! 快速搜索模块 - 前台统一搜索框，支持拼音全拼/首字母、电话片段、备注及自定义字段
+//!
+//! 每名学员的搜索内容（小写文本、姓名各读音的拼音、电话数字）预先整理为索引，首次搜索时建立，
+//! 之后在学员、监护人、标签和自定义字段修改时按学员刷新，搜索时只需遍历索引。
+
+use pinyin::{ToPinyin, ToPinyinMulti};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+/// 电话号码片段至少需要的数字位数
+const MIN_PHONE_DIGITS: usize = 3;
+
+/// 单个姓名最多展开的读音组合数量（多音字较多时只保留前面的组合）
+const MAX_NAME_READINGS: usize = 16;
+
+/// 姓名的拼音全拼与首字母（均为小写、无空格）
+#[derive(Clone)]
+pub struct NamePinyin {
+    pub full: String,
+    pub initials: String,
+}
+
+static PINYIN_CACHE: OnceLock<Mutex<HashMap<String, Vec<NamePinyin>>>> = OnceLock::new();
+
+/// 单个汉字的全部读音（常用读音在前），非汉字返回空
+fn char_readings(c: char) -> Vec<(&'static str, &'static str)> {
+    let mut readings: Vec<(&'static str, &'static str)> = c
+        .to_pinyin()
+        .map(|p| (p.plain(), p.first_letter()))
+        .into_iter()
+        .collect();
+    for pinyin in c.to_pinyin_multi().into_iter().flatten() {
+        if !readings.iter().any(|(plain, _)| *plain == pinyin.plain()) {
+            readings.push((pinyin.plain(), pinyin.first_letter()));
+        }
+    }
+    readings
+}
+
+/// 姓名转拼音：汉字按每个读音展开（如曾可读 zeng / ceng），字母数字原样保留（每个单词取首字母）
+///
+/// 第一项为各字常用读音的组合
+fn to_name_readings(name: &str) -> Vec<NamePinyin> {
+    let mut readings = vec![NamePinyin {
+        full: String::new(),
+        initials: String::new(),
+    }];
+    let mut in_word = false;
+    for c in name.chars() {
+        let options = char_readings(c);
+        if !options.is_empty() {
+            readings = readings
+                .iter()
+                .flat_map(|reading| {
+                    options.iter().map(move |(plain, first)| NamePinyin {
+                        full: format!("{}{}", reading.full, plain),
+                        initials: format!("{}{}", reading.initials, first),
+                    })
+                })
+                .take(MAX_NAME_READINGS)
+                .collect();
+            in_word = false;
+        } else if c.is_ascii_alphanumeric() {
+            let lower = c.to_ascii_lowercase();
+            for reading in &mut readings {
+                reading.full.push(lower);
+                if !in_word {
+                    reading.initials.push(lower);
+                }
+            }
+            in_word = true;
+        } else {
+            in_word = false;
+        }
+    }
+    readings
+}
+
+/// 带缓存的姓名全部读音
+fn name_readings(name: &str) -> Vec<NamePinyin> {
+    let cache = PINYIN_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    match cache.lock() {
+        Ok(mut cache) => cache
+            .entry(name.to_string())
+            .or_insert_with(|| to_name_readings(name))
+            .clone(),
+        Err(_) => to_name_readings(name),
+    }
+}
+
+/// 姓名按常用读音的拼音，用于排序
+pub fn name_pinyin(name: &str) -> NamePinyin {
+    name_readings(name).swap_remove(0)
+}
+
+/// 规范化后的搜索词
+pub struct SearchTerm {
+    /// 小写原文
+    text: String,
+    /// 去除空格的小写字母数字，用于拼音匹配
+    compact: String,
+    /// 仅数字，用于电话匹配
+    digits: String,
+}
+
+impl SearchTerm {
+    pub fn new(query: &str) -> Option<Self> {
+        let text = query.trim().to_lowercase();
+        if text.is_empty() {
+            return None;
+        }
+        let compact = text.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+        let digits = text.chars().filter(|c| c.is_ascii_digit()).collect();
+        Some(Self {
+            text,
+            compact,
+            digits,
+        })
+    }
+
+    /// 搜索词是否为纯数字（电话号码片段）
+    fn is_phone_like(&self) -> bool {
+        self.digits.len() >= MIN_PHONE_DIGITS
+            && self
+                .text
+                .chars()
+                .all(|c| c.is_ascii_digit() || c == ' ' || c == '-' || c == '+')
+    }
+}
+
+/// 参与搜索的学员信息
+pub struct SearchDoc<'a> {
+    pub name: &'a str,
+    pub phones: Vec<&'a str>,
+    pub guardian_phones: Vec<&'a str>,
+    pub tags: Vec<&'a str>,
+    pub custom_values: Vec<&'a str>,
+    pub note: &'a str,
+}
+
+/// 索引中的一名学员：搜索时比较的内容均已预先规范化
+pub struct IndexedStudent {
+    pub uid: u64,
+    pub name: String,
+    name_lower: String,
+    readings: Vec<NamePinyin>,
+    phone_digits: Vec<String>,
+    guardian_phone_digits: Vec<String>,
+    tags: Vec<String>,
+    custom_values: Vec<String>,
+    note: String,
+}
+
+impl IndexedStudent {
+    pub fn new(uid: u64, doc: &SearchDoc) -> Self {
+        let digits = |phones: &[&str]| -> Vec<String> {
+            phones
+                .iter()
+                .map(|phone| phone.chars().filter(|c| c.is_ascii_digit()).collect())
+                .collect()
+        };
+        let lower = |values: &[&str]| -> Vec<String> {
+            values.iter().map(|value| value.to_lowercase()).collect()
+        };
+        Self {
+            uid,
+            name: doc.name.to_string(),
+            name_lower: doc.name.to_lowercase(),
+            readings: name_readings(doc.name),
+            phone_digits: digits(&doc.phones),
+            guardian_phone_digits: digits(&doc.guardian_phones),
+            tags: lower(&doc.tags),
+            custom_values: lower(&doc.custom_values),
+            note: doc.note.to_lowercase(),
+        }
+    }
+
+    /// 计算匹配得分，返回得分和命中的字段；未命中返回 None
+    fn score(&self, term: &SearchTerm) -> Option<(u32, &'static str)> {
+        let name = &self.name_lower;
+        if *name == term.text {
+            return Some((100, "name"));
+        }
+        if name.starts_with(&term.text) {
+            return Some((90, "name"));
+        }
+        if name.contains(&term.text) {
+            return Some((80, "name"));
+        }
+
+        // 多音字姓名取得分最高的读音
+        if !term.compact.is_empty() && !term.is_phone_like() {
+            let best = self
+                .readings
+                .iter()
+                .filter_map(|pinyin| {
+                    if pinyin.initials == term.compact {
+                        Some((75, "pinyin_initials"))
+                    } else if pinyin.full.starts_with(&term.compact) {
+                        Some((72, "pinyin"))
+                    } else if pinyin.initials.starts_with(&term.compact) {
+                        Some((70, "pinyin_initials"))
+                    } else if pinyin.full.contains(&term.compact) {
+                        Some((60, "pinyin"))
+                    } else {
+                        None
+                    }
+                })
+                .max_by_key(|(score, _)| *score);
+            if best.is_some() {
+                return best;
+            }
+        }
+
+        if term.is_phone_like() {
+            let matches_phone = |digits: &String| digits.contains(&term.digits);
+            if self.phone_digits.iter().any(matches_phone) {
+                return Some((50, "phone"));
+            }
+            if self.guardian_phone_digits.iter().any(matches_phone) {
+                return Some((45, "guardian_phone"));
+            }
+        }
+
+        let contains = |value: &String| value.contains(&term.text);
+        if self.tags.contains(&term.text) {
+            return Some((42, "tag"));
+        }
+        if self.tags.iter().any(contains) {
+            return Some((40, "tag"));
+        }
+        if self.custom_values.iter().any(contains) {
+            return Some((30, "custom_field"));
+        }
+        if contains(&self.note) {
+            return Some((20, "note"));
+        }
+        None
+    }
+}
+
+/// 学员搜索索引，None 表示尚未建立或已失效
+static SEARCH_INDEX: OnceLock<Mutex<Option<HashMap<u64, IndexedStudent>>>> = OnceLock::new();
+
+fn search_index() -> &'static Mutex<Option<HashMap<u64, IndexedStudent>>> {
+    SEARCH_INDEX.get_or_init(|| Mutex::new(None))
+}
+
+/// 更新索引中的学员（索引尚未建立时不处理，下次搜索时整体建立）
+pub fn upsert_indexed(students: Vec<IndexedStudent>) {
+    if let Ok(mut index) = search_index().lock() {
+        if let Some(index) = index.as_mut() {
+            for student in students {
+                index.insert(student.uid, student);
+            }
+        }
+    }
+}
+
+/// 从索引中移除学员
+pub fn remove_indexed(uid: u64) {
+    if let Ok(mut index) = search_index().lock() {
+        if let Some(index) = index.as_mut() {
+            index.remove(&uid);
+        }
+    }
+}
+
+/// 使索引失效，下次搜索时重新建立
+pub fn invalidate_index() {
+    if let Ok(mut index) = search_index().lock() {
+        *index = None;
+    }
+}
+
+/// 在索引中搜索，返回按得分、姓名、UID排序的前 limit 个结果：(学员UID, 得分, 命中字段)
+///
+/// 索引尚未建立时先调用 build 建立
+pub fn search(
+    term: &SearchTerm,
+    limit: usize,
+    build: impl FnOnce() -> Result<Vec<IndexedStudent>, String>,
+) -> Result<Vec<(u64, u32, &'static str)>, String> {
+    let mut index = search_index()
+        .lock()
+        .map_err(|_| "搜索索引锁已损坏".to_string())?;
+    if index.is_none() {
+        let students = build()?;
+        *index = Some(students.into_iter().map(|s| (s.uid, s)).collect());
+    }
+    let mut hits: Vec<(u32, &'static str, &IndexedStudent)> = index
+        .iter()
+        .flat_map(|index| index.values())
+        .filter_map(|student| {
+            student
+                .score(term)
+                .map(|(score, field)| (score, field, student))
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| a.2.name.cmp(&b.2.name))
+            .then_with(|| a.2.uid.cmp(&b.2.uid))
+    });
+    Ok(hits
+        .into_iter()
+        .take(limit)
+        .map(|(score, field, student)| (student.uid, score, field))
+        .collect())
+}
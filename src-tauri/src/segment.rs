@@ -0,0 +1,33 @@
+//! 学员分组模块 - 保存的学员筛选条件，可随时重新执行
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::custom_field::CustomFieldFilter;
+
+/// 学员筛选条件，与 `search_students` 的参数一一对应
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct StudentSearchCriteria {
+    pub name_contains: Option<String>,
+    pub min_age: Option<u8>,
+    pub max_age: Option<u8>,
+    pub min_score: Option<f64>,
+    pub max_score: Option<f64>,
+    pub class_type: Option<String>,
+    pub subject: Option<String>,
+    pub has_membership: Option<bool>,
+    pub phone_contains: Option<String>,
+    pub custom_field_filters: Option<Vec<CustomFieldFilter>>,
+    /// 需要同时拥有的标签
+    pub tags: Option<Vec<String>>,
+}
+
+/// 保存的学员分组
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub id: u64,
+    pub name: String,
+    pub criteria: StudentSearchCriteria,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
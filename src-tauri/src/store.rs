@@ -0,0 +1,375 @@
+//! 扩展数据存储 - 保存 qmx_backend_lib 尚未覆盖的业务数据
+//!
+//! 数据以单个 JSON 文件保存在 `data/` 目录下，每次写操作成功后立即落盘。
+
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use crate::attendance::CheckIn;
+use crate::cash_meta::{CashAuditEntry, CashMeta};
+use crate::custom_field::CustomFieldDef;
+use crate::discount::{CashPricing, Coupon, DiscountRule};
+use crate::family::{Family, FamilyPayment};
+use crate::guardian::{Guardian, GuardianLink};
+use crate::late_fee::{LateFee, LateFeePolicy, LateFeeStatus, LateFeeWaiver};
+use crate::lead::Lead;
+use crate::membership::{MembershipFreeze, MembershipPeriod};
+use crate::receipt::{format_receipt_number, ReceiptRecord};
+use crate::referral::{Referral, ReferralReward};
+use crate::segment::Segment;
+use crate::timezone::{club_today, parse_timezone, set_club_timezone, DEFAULT_TIMEZONE};
+use crate::trial::TrialPackage;
+use crate::wallet::WalletEntry;
+
+const STORE_DIR: &str = "./data";
+const STORE_FILE: &str = "./data/qmx_extensions.json";
+
+// 全局扩展数据实例，与 MANAGER 一样延迟初始化
+static STORE: OnceLock<Mutex<ExtStore>> = OnceLock::new();
+
+/// 俱乐部设置
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ClubSettings {
+    /// IANA时区名称，用于所有按日期判断的业务逻辑
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// 收据、对账单抬头显示的俱乐部名称
+    #[serde(default)]
+    pub club_name: Option<String>,
+    /// 收据、对账单抬头的 Logo（JPEG 文件路径）
+    #[serde(default)]
+    pub logo_path: Option<String>,
+}
+
+fn default_timezone() -> String {
+    DEFAULT_TIMEZONE.to_string()
+}
+
+impl Default for ClubSettings {
+    fn default() -> Self {
+        Self {
+            timezone: default_timezone(),
+            club_name: None,
+            logo_path: None,
+        }
+    }
+}
+
+/// 扩展数据集合，新增字段必须带 `#[serde(default)]` 以兼容旧文件
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ExtStore {
+    /// 扩展数据的自增ID
+    #[serde(default)]
+    pub next_id: u64,
+    #[serde(default)]
+    pub settings: ClubSettings,
+    /// 会员冻结记录，按学生UID索引
+    #[serde(default)]
+    pub membership_freezes: HashMap<u64, Vec<MembershipFreeze>>,
+    /// 会员历史，按学生UID索引
+    #[serde(default)]
+    pub membership_history: HashMap<u64, Vec<MembershipPeriod>>,
+    /// 签到记录，按学生UID索引
+    #[serde(default)]
+    pub attendance: HashMap<u64, Vec<CheckIn>>,
+    /// 监护人，按监护人ID索引
+    #[serde(default)]
+    pub guardians: HashMap<u64, Guardian>,
+    /// 学员关联的监护人，按学生UID索引
+    #[serde(default)]
+    pub student_guardians: HashMap<u64, Vec<GuardianLink>>,
+    /// 家庭账户，按家庭ID索引
+    #[serde(default)]
+    pub families: HashMap<u64, Family>,
+    /// 家庭付款记录
+    #[serde(default)]
+    pub family_payments: Vec<FamilyPayment>,
+    /// 储值钱包流水，按学生UID索引
+    #[serde(default)]
+    pub wallets: HashMap<u64, Vec<WalletEntry>>,
+    /// 折扣规则，按规则ID索引
+    #[serde(default)]
+    pub discount_rules: HashMap<u64, DiscountRule>,
+    /// 优惠券，按优惠券ID索引
+    #[serde(default)]
+    pub coupons: HashMap<u64, Coupon>,
+    /// 交易的标价与折扣，按现金记录UID索引
+    #[serde(default)]
+    pub cash_pricing: HashMap<u64, CashPricing>,
+    /// 推荐奖励设置
+    #[serde(default)]
+    pub referral_reward: ReferralReward,
+    /// 推荐关系，按被推荐学员UID索引
+    #[serde(default)]
+    pub referrals: HashMap<u64, Referral>,
+    /// 潜在客户，按客户ID索引
+    #[serde(default)]
+    pub leads: HashMap<u64, Lead>,
+    /// 体验课包，按学生UID索引
+    #[serde(default)]
+    pub trials: HashMap<u64, TrialPackage>,
+    /// 自定义字段定义，按字段ID索引
+    #[serde(default)]
+    pub custom_fields: HashMap<u64, CustomFieldDef>,
+    /// 学员的自定义字段值，按学生UID、字段ID索引
+    #[serde(default)]
+    pub student_custom_fields: HashMap<u64, BTreeMap<u64, String>>,
+    /// 学员标签，按学生UID索引
+    #[serde(default)]
+    pub student_tags: HashMap<u64, BTreeSet<String>>,
+    /// 保存的学员分组，按分组ID索引
+    #[serde(default)]
+    pub segments: HashMap<u64, Segment>,
+    /// 现金记录的业务日期、录入时间及操作员，按现金记录UID索引
+    #[serde(default)]
+    pub cash_meta: HashMap<u64, CashMeta>,
+    /// 现金记录修改审计
+    #[serde(default)]
+    pub cash_audit: Vec<CashAuditEntry>,
+    /// 已开具的收据，按现金记录UID索引
+    #[serde(default)]
+    pub receipts: HashMap<u64, ReceiptRecord>,
+    /// 各年份已使用的最大收据序号
+    #[serde(default)]
+    pub receipt_counters: BTreeMap<i32, u64>,
+    /// 滞纳金规则，按分期计划ID索引
+    #[serde(default)]
+    pub late_fee_policies: HashMap<u64, LateFeePolicy>,
+    /// 滞纳金，按滞纳金ID索引
+    #[serde(default)]
+    pub late_fees: HashMap<u64, LateFee>,
+}
+
+impl ExtStore {
+    fn load() -> Result<Self, String> {
+        if !Path::new(STORE_FILE).exists() {
+            return Ok(Self::default());
+        }
+        let content =
+            fs::read_to_string(STORE_FILE).map_err(|e| format!("读取扩展数据失败: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("解析扩展数据失败: {}", e))
+    }
+
+    fn save(&self) -> Result<(), String> {
+        fs::create_dir_all(STORE_DIR).map_err(|e| format!("创建数据目录失败: {}", e))?;
+        let content =
+            serde_json::to_string_pretty(self).map_err(|e| format!("序列化扩展数据失败: {}", e))?;
+        // 先写临时文件再替换，避免写入中断导致数据损坏
+        let tmp_file = format!("{}.tmp", STORE_FILE);
+        fs::write(&tmp_file, content).map_err(|e| format!("写入扩展数据失败: {}", e))?;
+        fs::rename(&tmp_file, STORE_FILE).map_err(|e| format!("保存扩展数据失败: {}", e))
+    }
+
+    /// 分配一个新的扩展数据ID（从1开始）
+    pub fn alloc_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// 关联了指定监护人的学员UID
+    pub fn guardian_student_uids(&self, guardian_id: u64) -> Vec<u64> {
+        let mut uids: Vec<u64> = self
+            .student_guardians
+            .iter()
+            .filter(|(_, links)| links.iter().any(|l| l.guardian_id == guardian_id))
+            .map(|(uid, _)| *uid)
+            .collect();
+        uids.sort_unstable();
+        uids
+    }
+
+    /// 学员所属的家庭ID
+    pub fn family_of(&self, student_uid: u64) -> Option<u64> {
+        self.families
+            .values()
+            .find(|f| f.member_uids.contains(&student_uid))
+            .map(|f| f.id)
+    }
+
+    /// 按优惠券码查找优惠券
+    pub fn coupon_by_code_mut(&mut self, code: &str) -> Option<&mut Coupon> {
+        self.coupons.values_mut().find(|c| c.code == code)
+    }
+
+    /// 按字段标识查找自定义字段
+    pub fn custom_field_by_key(&self, key: &str) -> Option<&CustomFieldDef> {
+        self.custom_fields.values().find(|f| f.key == key)
+    }
+
+    /// 记录新建现金记录的元数据，未指定业务日期时取俱乐部本地今天
+    pub fn record_cash_meta(
+        &mut self,
+        cash_uid: u64,
+        business_date: Option<NaiveDate>,
+        operator: Option<String>,
+    ) -> &mut CashMeta {
+        let meta = CashMeta::new(
+            business_date.unwrap_or_else(club_today),
+            operator,
+            Utc::now(),
+        );
+        self.cash_meta.entry(cash_uid).insert_entry(meta).into_mut()
+    }
+
+    /// 预览本次开具的收据（不保存）：首次开具时为当年下一个编号，补打时沿用原编号并累计补打次数
+    pub fn preview_receipt(&self, cash_uid: u64, year: i32) -> ReceiptRecord {
+        if let Some(receipt) = self.receipts.get(&cash_uid) {
+            return ReceiptRecord {
+                reprint_count: receipt.reprint_count + 1,
+                ..receipt.clone()
+            };
+        }
+        let seq = self.receipt_counters.get(&year).copied().unwrap_or(0) + 1;
+        ReceiptRecord {
+            number: format_receipt_number(year, seq),
+            issued_at: Utc::now(),
+            reprint_count: 0,
+        }
+    }
+
+    /// 收据打印成功后保存预览的收据；期间已有其他收据开具（编号已变化）时返回错误
+    pub fn issue_receipt(
+        &mut self,
+        cash_uid: u64,
+        year: i32,
+        receipt: &ReceiptRecord,
+    ) -> Result<(), String> {
+        let current = self.preview_receipt(cash_uid, year);
+        if current.number != receipt.number || current.reprint_count != receipt.reprint_count {
+            return Err("收据编号已被占用，请重新生成".to_string());
+        }
+        if receipt.reprint_count == 0 {
+            self.receipt_counters
+                .entry(year)
+                .and_modify(|seq| *seq += 1)
+                .or_insert(1);
+        }
+        self.receipts.insert(cash_uid, receipt.clone());
+        Ok(())
+    }
+
+    /// 现金记录是否关联了钱包流水、家庭付款或滞纳金（金额和学员由关联记录决定）
+    pub fn is_cash_linked(&self, cash_uid: u64) -> bool {
+        self.wallets
+            .values()
+            .flatten()
+            .any(|entry| entry.cash_uid == cash_uid)
+            || self
+                .family_payments
+                .iter()
+                .any(|payment| payment.cash_uids.contains(&cash_uid))
+            || self
+                .late_fees
+                .values()
+                .any(|fee| fee.payment_uid == Some(cash_uid))
+    }
+
+    /// 现金记录被修改时更新修改时间（旧记录没有元数据时不处理）
+    pub fn touch_cash(&mut self, cash_uid: u64) {
+        if let Some(meta) = self.cash_meta.get_mut(&cash_uid) {
+            meta.updated_at = Utc::now();
+        }
+    }
+
+    /// 减免指定分期记录上待付款的滞纳金（分期调整或结清后不再收取），返回减免的笔数
+    pub fn waive_pending_late_fees(
+        &mut self,
+        installment_uids: &[u64],
+        reason: &str,
+        operator: Option<String>,
+    ) -> usize {
+        let mut waived = 0;
+        for fee in self.late_fees.values_mut() {
+            if fee.status == LateFeeStatus::Pending
+                && installment_uids.contains(&fee.installment_uid)
+            {
+                fee.status = LateFeeStatus::Waived;
+                fee.waiver = Some(LateFeeWaiver {
+                    reason: reason.to_string(),
+                    operator: operator.clone(),
+                    waived_at: Utc::now(),
+                });
+                waived += 1;
+            }
+        }
+        waived
+    }
+
+    /// 删除现金记录时清理其关联的扩展数据
+    pub fn purge_cash(&mut self, cash_uid: u64) {
+        self.cash_pricing.remove(&cash_uid);
+        self.cash_meta.remove(&cash_uid);
+        // 逾期分期被删除时，尚未处理的滞纳金一并删除；收款记录被删除时滞纳金恢复为待付款
+        self.late_fees.retain(|_, fee| {
+            !(fee.installment_uid == cash_uid && fee.status == LateFeeStatus::Pending)
+        });
+        for fee in self.late_fees.values_mut() {
+            if fee.payment_uid == Some(cash_uid) {
+                fee.status = LateFeeStatus::Pending;
+                fee.payment_uid = None;
+                fee.paid_at = None;
+            }
+        }
+    }
+
+    /// 删除学生时清理其关联的扩展数据
+    pub fn purge_student(&mut self, student_uid: u64) {
+        self.membership_freezes.remove(&student_uid);
+        self.membership_history.remove(&student_uid);
+        self.attendance.remove(&student_uid);
+        self.student_guardians.remove(&student_uid);
+        self.referrals.remove(&student_uid);
+        self.trials.remove(&student_uid);
+        self.student_custom_fields.remove(&student_uid);
+        self.student_tags.remove(&student_uid);
+        // 删除前已确认钱包余额为0
+        self.wallets.remove(&student_uid);
+        for family in self.families.values_mut() {
+            family.member_uids.retain(|uid| *uid != student_uid);
+        }
+    }
+}
+
+/// 初始化扩展数据，并应用其中保存的俱乐部设置
+pub fn init_store() -> Result<&'static Mutex<ExtStore>, String> {
+    if let Some(store) = STORE.get() {
+        return Ok(store);
+    }
+    let loaded = ExtStore::load().map_err(|e| {
+        log::error!("扩展数据加载失败: {}", e);
+        e
+    })?;
+    match parse_timezone(&loaded.settings.timezone) {
+        Ok(tz) => set_club_timezone(tz),
+        Err(e) => log::warn!("{}，使用默认时区 {}", e, DEFAULT_TIMEZONE),
+    }
+    log::info!("扩展数据加载成功，俱乐部时区: {}", loaded.settings.timezone);
+    Ok(STORE.get_or_init(|| Mutex::new(loaded)))
+}
+
+/// 只读访问扩展数据
+pub fn read_store<T>(f: impl FnOnce(&ExtStore) -> T) -> Result<T, String> {
+    let store = init_store()?;
+    let guard = store.lock().map_err(|_| "扩展数据锁已损坏".to_string())?;
+    Ok(f(&guard))
+}
+
+/// 修改扩展数据，闭包成功返回后自动保存
+///
+/// 闭包在副本上执行，保存成功后才替换内存中的数据；闭包返回错误或保存失败时修改全部丢弃
+pub fn write_store<T>(f: impl FnOnce(&mut ExtStore) -> Result<T, String>) -> Result<T, String> {
+    let store = init_store()?;
+    let mut guard = store.lock().map_err(|_| "扩展数据锁已损坏".to_string())?;
+    let mut draft = guard.clone();
+    let result = f(&mut draft)?;
+    draft.save().map_err(|e| {
+        log::error!("扩展数据保存失败: {}", e);
+        e
+    })?;
+    *guard = draft;
+    Ok(result)
+}
@@ -0,0 +1,19 @@
+//! 标签模块 - 学员的自由标签（如 比赛队、待跟进、左撇子）
+
+use std::collections::BTreeSet;
+
+/// 单个学员最多可添加的标签数
+pub const MAX_TAGS_PER_STUDENT: usize = 30;
+
+/// 整理标签列表：去除首尾空白、空项和重复项
+pub fn normalize_tags(tags: Vec<String>) -> BTreeSet<String> {
+    tags.into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// 学员是否同时拥有全部指定标签
+pub fn has_all_tags(student_tags: Option<&BTreeSet<String>>, required: &BTreeSet<String>) -> bool {
+    required.is_empty() || student_tags.is_some_and(|tags| required.is_subset(tags))
+}
@@ -0,0 +1,345 @@
+//! 收支趋势模块 - 按日/周/月汇总收入、支出和净收入，供图表直接使用
+//!
+//! 区间内没有交易的时间段补零，拆分序列在每个时间段中都完整列出，前端无需再对齐数据。
+
+use chrono::{Datelike, Days, Months, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 单次查询最多返回的时间段数量
+pub const MAX_BUCKETS: usize = 1000;
+
+/// 时间粒度
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Granularity {
+    Day,
+    /// 自然周，周一开始
+    Week,
+    Month,
+}
+
+pub fn parse_granularity(granularity: &str) -> Result<Granularity, String> {
+    match granularity {
+        "Day" => Ok(Granularity::Day),
+        "Week" => Ok(Granularity::Week),
+        "Month" => Ok(Granularity::Month),
+        _ => Err(format!("无效的时间粒度: {}", granularity)),
+    }
+}
+
+impl Granularity {
+    /// 日期所在时间段的第一天
+    pub fn bucket_start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => date,
+            Self::Week => date - Days::new(u64::from(date.weekday().num_days_from_monday())),
+            Self::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+
+    /// 下一个时间段的第一天
+    fn next_bucket(&self, start: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Self::Day => start.checked_add_days(Days::new(1)),
+            Self::Week => start.checked_add_days(Days::new(7)),
+            Self::Month => start.checked_add_months(Months::new(1)),
+        }
+    }
+
+    /// 区间内各时间段的起止日期（首尾时间段按区间截断）
+    pub fn buckets(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<Vec<(NaiveDate, NaiveDate)>, String> {
+        let mut buckets = Vec::new();
+        let mut bucket = self.bucket_start(start);
+        while bucket <= end {
+            if buckets.len() >= MAX_BUCKETS {
+                return Err(format!(
+                    "时间段数量不能超过{}个，请缩小区间或使用更大的时间粒度",
+                    MAX_BUCKETS
+                ));
+            }
+            let next = self.next_bucket(bucket).ok_or("日期超出范围")?;
+            let last_day = next.pred_opt().ok_or("日期超出范围")?;
+            buckets.push((bucket.max(start), last_day.min(end)));
+            bucket = next;
+        }
+        Ok(buckets)
+    }
+}
+
+/// 参与汇总的一条现金记录
+pub struct SeriesEntry {
+    /// 记录所在时间段的任意一天
+    pub date: NaiveDate,
+    pub amount: i64,
+    /// 拆分维度的取值，未拆分时忽略
+    pub split_key: Option<String>,
+}
+
+/// 拆分项的显示名称
+pub struct SplitLabels {
+    pub label_of: fn(&str) -> &'static str,
+    /// 记录缺少拆分取值时的名称
+    pub missing: &'static str,
+}
+
+/// 收入、支出和净收入
+#[derive(Clone, Default, Serialize)]
+pub struct SeriesValues {
+    pub income: i64,
+    pub expense: i64,
+    pub net: i64,
+}
+
+impl SeriesValues {
+    fn add(&mut self, amount: i64) {
+        if amount > 0 {
+            self.income += amount;
+        } else {
+            self.expense += amount.abs();
+        }
+        self.net = self.income - self.expense;
+    }
+}
+
+/// 时间段内某个拆分项的数值
+#[derive(Clone, Serialize)]
+pub struct SeriesSplit {
+    pub key: String,
+    pub label: String,
+    #[serde(flatten)]
+    pub values: SeriesValues,
+}
+
+/// 一个时间段
+#[derive(Clone, Serialize)]
+pub struct SeriesBucket {
+    pub start_date: String,
+    pub end_date: String,
+    #[serde(flatten)]
+    pub values: SeriesValues,
+    /// 按拆分项的数值，区间内出现过的拆分项在每个时间段都会列出
+    pub splits: Vec<SeriesSplit>,
+}
+
+/// 按时间段汇总，返回各时间段和区间合计
+///
+/// split 为 None 时不拆分
+pub fn build_series(
+    buckets: &[(NaiveDate, NaiveDate)],
+    entries: &[SeriesEntry],
+    split: Option<&SplitLabels>,
+) -> (Vec<SeriesBucket>, SeriesValues) {
+    let mut totals = SeriesValues::default();
+    let mut values: Vec<SeriesValues> = vec![SeriesValues::default(); buckets.len()];
+    let mut split_values: BTreeMap<String, Vec<SeriesValues>> = BTreeMap::new();
+
+    for entry in entries {
+        let index = buckets.partition_point(|(_, end)| *end < entry.date);
+        if buckets
+            .get(index)
+            .is_none_or(|(start, _)| entry.date < *start)
+        {
+            continue;
+        }
+        totals.add(entry.amount);
+        values[index].add(entry.amount);
+        if split.is_some() {
+            let key = entry.split_key.clone().unwrap_or_default();
+            split_values
+                .entry(key)
+                .or_insert_with(|| vec![SeriesValues::default(); buckets.len()])[index]
+                .add(entry.amount);
+        }
+    }
+
+    let series = buckets
+        .iter()
+        .zip(values)
+        .enumerate()
+        .map(|(index, ((start, end), values))| SeriesBucket {
+            start_date: start.format("%Y-%m-%d").to_string(),
+            end_date: end.format("%Y-%m-%d").to_string(),
+            values,
+            splits: split
+                .map(|labels| {
+                    split_values
+                        .iter()
+                        .map(|(key, values)| SeriesSplit {
+                            key: key.clone(),
+                            label: if key.is_empty() {
+                                labels.missing.to_string()
+                            } else {
+                                (labels.label_of)(key).to_string()
+                            },
+                            values: values[index].clone(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+        .collect();
+    (series, totals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn ranges(buckets: &[(NaiveDate, NaiveDate)]) -> Vec<(String, String)> {
+        buckets
+            .iter()
+            .map(|(start, end)| (start.to_string(), end.to_string()))
+            .collect()
+    }
+
+    fn entry(day: &str, amount: i64, split_key: Option<&str>) -> SeriesEntry {
+        SeriesEntry {
+            date: date(day),
+            amount,
+            split_key: split_key.map(str::to_string),
+        }
+    }
+
+    fn label_of(key: &str) -> &'static str {
+        match key {
+            "tuition" => "学费",
+            _ => "其他",
+        }
+    }
+
+    #[test]
+    fn week_buckets_start_monday_and_truncate_at_range_edges() {
+        // 2024-01-03 为周三，2024-01-17 为周三
+        let buckets = Granularity::Week
+            .buckets(date("2024-01-03"), date("2024-01-17"))
+            .unwrap();
+        assert_eq!(
+            ranges(&buckets),
+            vec![
+                ("2024-01-03".into(), "2024-01-07".into()),
+                ("2024-01-08".into(), "2024-01-14".into()),
+                ("2024-01-15".into(), "2024-01-17".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn month_buckets_truncate_at_range_edges() {
+        let buckets = Granularity::Month
+            .buckets(date("2024-01-15"), date("2024-03-10"))
+            .unwrap();
+        assert_eq!(
+            ranges(&buckets),
+            vec![
+                ("2024-01-15".into(), "2024-01-31".into()),
+                ("2024-02-01".into(), "2024-02-29".into()),
+                ("2024-03-01".into(), "2024-03-10".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn single_day_range_is_one_bucket() {
+        let buckets = Granularity::Month
+            .buckets(date("2024-05-31"), date("2024-05-31"))
+            .unwrap();
+        assert_eq!(
+            ranges(&buckets),
+            vec![("2024-05-31".into(), "2024-05-31".into())]
+        );
+    }
+
+    #[test]
+    fn bucket_count_is_limited() {
+        let start = date("2024-01-01");
+        let last_allowed = start + Days::new(MAX_BUCKETS as u64 - 1);
+        assert_eq!(
+            Granularity::Day.buckets(start, last_allowed).unwrap().len(),
+            MAX_BUCKETS
+        );
+        assert!(Granularity::Day
+            .buckets(start, last_allowed + Days::new(1))
+            .is_err());
+        assert!(Granularity::Month
+            .buckets(start, last_allowed + Days::new(1))
+            .is_ok());
+    }
+
+    #[test]
+    fn empty_buckets_are_zero_filled() {
+        let buckets = Granularity::Week
+            .buckets(date("2024-01-01"), date("2024-01-21"))
+            .unwrap();
+        let entries = [
+            entry("2024-01-02", 500, None),
+            entry("2024-01-04", -200, None),
+            entry("2024-01-20", 300, None),
+            // 区间外的记录不计入
+            entry("2024-01-22", 1000, None),
+        ];
+        let (series, totals) = build_series(&buckets, &entries, None);
+
+        assert_eq!(series.len(), 3);
+        assert_eq!(
+            (
+                series[0].values.income,
+                series[0].values.expense,
+                series[0].values.net
+            ),
+            (500, 200, 300)
+        );
+        assert_eq!(
+            (
+                series[1].values.income,
+                series[1].values.expense,
+                series[1].values.net
+            ),
+            (0, 0, 0)
+        );
+        assert_eq!(series[2].values.income, 300);
+        assert!(series.iter().all(|bucket| bucket.splits.is_empty()));
+        assert_eq!((totals.income, totals.expense, totals.net), (800, 200, 600));
+    }
+
+    #[test]
+    fn splits_are_listed_in_every_bucket() {
+        let buckets = Granularity::Month
+            .buckets(date("2024-01-01"), date("2024-03-31"))
+            .unwrap();
+        let entries = [
+            entry("2024-01-10", 800, Some("tuition")),
+            entry("2024-03-05", -100, Some("rent")),
+            entry("2024-03-06", 50, None),
+        ];
+        let labels = SplitLabels {
+            label_of,
+            missing: "未分类",
+        };
+        let (series, _) = build_series(&buckets, &entries, Some(&labels));
+
+        for bucket in &series {
+            let keys: Vec<&str> = bucket.splits.iter().map(|s| s.key.as_str()).collect();
+            assert_eq!(keys, vec!["", "rent", "tuition"]);
+        }
+        let labels: Vec<&str> = series[0].splits.iter().map(|s| s.label.as_str()).collect();
+        assert_eq!(labels, vec!["未分类", "其他", "学费"]);
+
+        // 二月没有交易，各拆分项均为0
+        assert!(series[1]
+            .splits
+            .iter()
+            .all(|s| s.values.income == 0 && s.values.expense == 0 && s.values.net == 0));
+        assert_eq!(series[0].splits[2].values.income, 800);
+        assert_eq!(series[2].splits[0].values.income, 50);
+        assert_eq!(series[2].splits[1].values.expense, 100);
+        assert_eq!(series[2].splits[2].values.income, 0);
+    }
+}
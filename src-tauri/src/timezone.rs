@@ -0,0 +1,126 @@
+//! 时区模块 - 俱乐部本地时间与UTC之间的转换
+//!
+//! 所有"今天"、"本周"、会员到期等按日期判断的逻辑都应使用俱乐部时区，
+//! 而不是UTC零点。时区可在设置中修改，默认为中国标准时间。
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::sync::RwLock;
+
+/// 默认俱乐部时区
+pub const DEFAULT_TIMEZONE: &str = "Asia/Shanghai";
+
+static CLUB_TZ: RwLock<Tz> = RwLock::new(Tz::Asia__Shanghai);
+
+/// 解析IANA时区名称，如 "Asia/Shanghai"
+pub fn parse_timezone(name: &str) -> Result<Tz, String> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| format!("无效的时区: {}", name))
+}
+
+/// 当前俱乐部时区
+pub fn club_timezone() -> Tz {
+    CLUB_TZ.read().map(|tz| *tz).unwrap_or(Tz::Asia__Shanghai)
+}
+
+/// 修改俱乐部时区（仅影响内存中的设置，持久化由调用方负责）
+pub fn set_club_timezone(tz: Tz) {
+    if let Ok(mut current) = CLUB_TZ.write() {
+        *current = tz;
+    }
+}
+
+/// 俱乐部本地时间的今天
+pub fn club_today() -> NaiveDate {
+    Utc::now().with_timezone(&club_timezone()).date_naive()
+}
+
+/// UTC时间对应的俱乐部本地日期
+pub fn to_local_date(date: &DateTime<Utc>) -> NaiveDate {
+    date.with_timezone(&club_timezone()).date_naive()
+}
+
+/// UTC时间对应的俱乐部本地日期字符串（YYYY-MM-DD）
+pub fn to_local_date_string(date: &DateTime<Utc>) -> String {
+    to_local_date(date).format("%Y-%m-%d").to_string()
+}
+
+/// UTC时间对应的俱乐部本地时间字符串（YYYY-MM-DD HH:MM）
+pub fn to_local_datetime_string(date: &DateTime<Utc>) -> String {
+    date.with_timezone(&club_timezone())
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+/// 本地日期当天 00:00:00 对应的UTC时间
+pub fn local_day_start(date: NaiveDate) -> DateTime<Utc> {
+    local_to_utc(date, NaiveTime::MIN)
+}
+
+/// 本地日期当天 23:59:59 对应的UTC时间
+pub fn local_day_end(date: NaiveDate) -> DateTime<Utc> {
+    let end_of_day = NaiveTime::from_hms_opt(23, 59, 59).unwrap_or(NaiveTime::MIN);
+    local_to_utc(date, end_of_day)
+}
+
+/// 按本地日期计算两个时间之间相差的自然日数
+pub fn local_days_between(start: &DateTime<Utc>, end: &DateTime<Utc>) -> i64 {
+    (to_local_date(end) - to_local_date(start)).num_days()
+}
+
+/// 解析日期输入：支持RFC3339时间或本地日期（YYYY-MM-DD，取当天开始）
+pub fn parse_date_input(input: &str) -> Result<DateTime<Utc>, String> {
+    let input = input.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Ok(local_day_start(date));
+    }
+    DateTime::parse_from_rfc3339(input)
+        .map(|d| d.with_timezone(&Utc))
+        .map_err(|e| format!("日期格式错误: {}", e))
+}
+
+/// 统计周期在俱乐部本地时间下的起止日期（含首尾两天）
+pub fn period_local_range(period: &str) -> Result<(NaiveDate, NaiveDate), String> {
+    let today = club_today();
+    let start = match period {
+        "Today" => today,
+        "ThisWeek" => today
+            .checked_sub_days(Days::new(u64::from(today.weekday().num_days_from_monday())))
+            .ok_or("日期超出范围")?,
+        "ThisMonth" => today.with_day(1).ok_or("日期超出范围")?,
+        "ThisYear" => NaiveDate::from_ymd_opt(today.year(), 1, 1).ok_or("日期超出范围")?,
+        _ => return Err("无效的时间段".to_string()),
+    };
+    Ok((start, today))
+}
+
+/// 报表区间：优先使用自定义起止日期，否则按统计周期（默认本月）
+pub fn report_local_range(
+    period: Option<&str>,
+    date_from: Option<&str>,
+    date_to: Option<&str>,
+) -> Result<(NaiveDate, NaiveDate), String> {
+    match (date_from, date_to) {
+        (Some(from), Some(to)) => {
+            let start = to_local_date(&parse_date_input(from)?);
+            let end = to_local_date(&parse_date_input(to)?);
+            if start > end {
+                return Err("开始日期不能晚于结束日期".to_string());
+            }
+            Ok((start, end))
+        }
+        (None, None) => period_local_range(period.unwrap_or("ThisMonth")),
+        _ => Err("自定义区间需要同时指定开始和结束日期".to_string()),
+    }
+}
+
+fn local_to_utc(date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let naive = date.and_time(time);
+    // 夏令时切换导致的不存在/重复时间，退回按UTC解释
+    club_timezone()
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or_else(|| Utc.from_utc_datetime(&naive))
+}
@@ -0,0 +1,86 @@
+//! 体验课模块 - TenTry 班级的体验次数、有效期及转化记录
+
+use chrono::{DateTime, Days, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::timezone::{local_day_end, to_local_date};
+
+/// 体验课包含的次数
+pub const TRIAL_CREDITS: u32 = 10;
+/// 体验课有效天数（含开始当天）
+pub const TRIAL_VALID_DAYS: u64 = 30;
+
+/// 体验课状态
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrialStatus {
+    Active,
+    /// 次数已用完
+    Exhausted,
+    Expired,
+    Converted,
+}
+
+/// 转为正式会员的记录
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TrialConversion {
+    /// 转入的班级：Month / Year
+    pub class_type: String,
+    pub payment_uid: u64,
+    pub operator: Option<String>,
+    pub converted_at: DateTime<Utc>,
+}
+
+/// 学员的体验课包，按学生UID保存
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TrialPackage {
+    pub credits_total: u32,
+    pub credits_used: u32,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub conversion: Option<TrialConversion>,
+}
+
+impl TrialPackage {
+    /// 从指定时间开始的标准体验课包，在有效期最后一天结束时到期
+    pub fn new(started_at: DateTime<Utc>) -> Self {
+        let last_day = to_local_date(&started_at)
+            .checked_add_days(Days::new(TRIAL_VALID_DAYS - 1))
+            .unwrap_or_else(|| to_local_date(&started_at));
+        Self {
+            credits_total: TRIAL_CREDITS,
+            credits_used: 0,
+            started_at,
+            expires_at: local_day_end(last_day),
+            conversion: None,
+        }
+    }
+
+    pub fn credits_left(&self) -> u32 {
+        self.credits_total.saturating_sub(self.credits_used)
+    }
+
+    pub fn status(&self, now: &DateTime<Utc>) -> TrialStatus {
+        if self.conversion.is_some() {
+            TrialStatus::Converted
+        } else if self.credits_left() == 0 {
+            TrialStatus::Exhausted
+        } else if self.expires_at < *now {
+            TrialStatus::Expired
+        } else {
+            TrialStatus::Active
+        }
+    }
+
+    /// 签到时扣除一次体验次数
+    pub fn consume(&mut self, at: &DateTime<Utc>) -> Result<(), String> {
+        match self.status(at) {
+            TrialStatus::Active => {
+                self.credits_used += 1;
+                Ok(())
+            }
+            TrialStatus::Exhausted => Err("体验课次数已用完，请先转为正式会员".to_string()),
+            TrialStatus::Expired => Err("体验课已过期，请先转为正式会员".to_string()),
+            TrialStatus::Converted => Err("学员已转为正式会员".to_string()),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //! 验证模块 - 包含所有输入验证函数
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 
 /// 验证学生姓名
 pub fn validate_student_name(name: &str) -> Result<(), String> {
@@ -33,6 +33,32 @@ pub fn validate_phone_number(phone: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// 验证监护人关系（如 父亲、母亲、爷爷）
+pub fn validate_guardian_relationship(relationship: &str) -> Result<(), String> {
+    let relationship = relationship.trim();
+    if relationship.is_empty() {
+        return Err("监护人关系不能为空".to_string());
+    }
+    if relationship.chars().count() > 20 {
+        return Err("监护人关系长度不能超过20个字符".to_string());
+    }
+    Ok(())
+}
+
+/// 验证监护人电话列表
+pub fn validate_guardian_phones(phones: &[String]) -> Result<(), String> {
+    if phones.is_empty() {
+        return Err("监护人至少需要一个电话号码".to_string());
+    }
+    if phones.len() > 5 {
+        return Err("监护人电话号码不能超过5个".to_string());
+    }
+    for phone in phones {
+        validate_phone_number(phone)?;
+    }
+    Ok(())
+}
+
 /// 验证备注
 pub fn validate_note(note: &str) -> Result<(), String> {
     if note.len() > 1000 {
@@ -63,6 +89,240 @@ pub fn validate_amount(amount: i64) -> Result<(), String> {
     Ok(())
 }
 
+/// 验证折扣百分比
+pub fn validate_discount_percent(percent: u32) -> Result<(), String> {
+    if percent > 100 {
+        return Err("折扣百分比必须在0-100之间".to_string());
+    }
+    Ok(())
+}
+
+/// 验证钱包扣款类别
+pub fn validate_wallet_category(category: &str) -> Result<(), String> {
+    match category {
+        "lesson" | "range" | "equipment" | "other" => Ok(()),
+        _ => Err(format!("无效的扣款类别: {}", category)),
+    }
+}
+
+/// 验证现金记录收支类别
+pub fn validate_cash_category(category: &str) -> Result<(), String> {
+    match category {
+        "tuition" | "membership" | "trial" | "equipment" | "range" | "competition" | "late_fee"
+        | "other" => Ok(()),
+        _ => Err(format!("无效的收支类别: {}", category)),
+    }
+}
+
+/// 验证付款方式
+pub fn validate_payment_method(method: &str) -> Result<(), String> {
+    match method {
+        "cash" | "wechat" | "alipay" | "bank_card" | "wallet" | "other" => Ok(()),
+        _ => Err(format!("无效的付款方式: {}", method)),
+    }
+}
+
+/// 验证折扣数值（百分比为1-100，固定金额必须大于0）
+pub fn validate_discount_value(kind: &str, value: i64) -> Result<(), String> {
+    match kind {
+        "Percentage" => {
+            if !(1..=100).contains(&value) {
+                return Err("百分比折扣必须在1-100之间".to_string());
+            }
+        }
+        _ => {
+            validate_amount(value)?;
+            if value <= 0 {
+                return Err("减免金额必须大于0".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 验证优惠券码
+pub fn validate_coupon_code(code: &str) -> Result<(), String> {
+    let code = code.trim();
+    if code.len() < 3 || code.len() > 32 {
+        return Err("优惠券码长度必须在3-32个字符之间".to_string());
+    }
+    if !code
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("优惠券码只能包含字母、数字、'-'和'_'".to_string());
+    }
+    Ok(())
+}
+
+/// 验证自定义字段标识（小写字母开头，仅含小写字母、数字和下划线）
+pub fn validate_custom_field_key(key: &str) -> Result<(), String> {
+    let key = key.trim();
+    if key.is_empty() || key.len() > 30 {
+        return Err("字段标识长度必须在1-30个字符之间".to_string());
+    }
+    if !key.starts_with(|c: char| c.is_ascii_lowercase())
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err("字段标识必须以小写字母开头，且只能包含小写字母、数字和'_'".to_string());
+    }
+    Ok(())
+}
+
+/// 验证自定义字段名称
+pub fn validate_custom_field_label(label: &str) -> Result<(), String> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err("字段名称不能为空".to_string());
+    }
+    if label.chars().count() > 30 {
+        return Err("字段名称长度不能超过30个字符".to_string());
+    }
+    if label.chars().any(|c| c.is_control()) {
+        return Err("字段名称包含非法字符".to_string());
+    }
+    Ok(())
+}
+
+/// 验证自定义字段的枚举选项
+pub fn validate_custom_field_options(options: &[String]) -> Result<(), String> {
+    if options.is_empty() {
+        return Err("枚举字段至少需要一个选项".to_string());
+    }
+    if options.len() > 50 {
+        return Err("枚举字段选项不能超过50个".to_string());
+    }
+    for (i, option) in options.iter().enumerate() {
+        if option.is_empty() || option.chars().count() > 50 {
+            return Err("枚举选项长度必须在1-50个字符之间".to_string());
+        }
+        if options[..i].contains(option) {
+            return Err(format!("枚举选项重复: {}", option));
+        }
+    }
+    Ok(())
+}
+
+/// 验证数值字段的取值范围
+pub fn validate_custom_field_range(min: Option<f64>, max: Option<f64>) -> Result<(), String> {
+    if min.is_some_and(|v| !v.is_finite()) || max.is_some_and(|v| !v.is_finite()) {
+        return Err("取值范围必须是有效数字".to_string());
+    }
+    if let (Some(min), Some(max)) = (min, max) {
+        if min > max {
+            return Err("最小值不能大于最大值".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// 验证学员标签
+pub fn validate_tags<'a>(tags: impl IntoIterator<Item = &'a String>) -> Result<(), String> {
+    for tag in tags {
+        if tag.chars().count() > 20 {
+            return Err(format!("标签长度不能超过20个字符: {}", tag));
+        }
+        if tag.chars().any(|c| c.is_control()) {
+            return Err(format!("标签包含非法字符: {}", tag));
+        }
+    }
+    Ok(())
+}
+
+/// 验证学员分组名称
+pub fn validate_segment_name(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("分组名称不能为空".to_string());
+    }
+    if name.chars().count() > 50 {
+        return Err("分组名称长度不能超过50个字符".to_string());
+    }
+    Ok(())
+}
+
+/// 验证现金记录的业务日期（可补录过去的日期，不能晚于今天）
+pub fn validate_business_date(date: NaiveDate, today: NaiveDate) -> Result<(), String> {
+    if date > today {
+        return Err("业务日期不能晚于今天".to_string());
+    }
+    if (today - date).num_days() > 3650 {
+        return Err("业务日期不能早于十年前".to_string());
+    }
+    Ok(())
+}
+
+/// 验证俱乐部名称
+pub fn validate_club_name(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("俱乐部名称不能为空".to_string());
+    }
+    if name.chars().count() > 50 {
+        return Err("俱乐部名称长度不能超过50个字符".to_string());
+    }
+    if name.chars().any(|c| c.is_control()) {
+        return Err("俱乐部名称包含非法字符".to_string());
+    }
+    Ok(())
+}
+
+/// 验证PDF输出路径
+pub fn validate_pdf_path(path: &str) -> Result<(), String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("输出路径不能为空".to_string());
+    }
+    if !path.to_lowercase().ends_with(".pdf") {
+        return Err("输出文件必须是 .pdf 格式".to_string());
+    }
+    Ok(())
+}
+
+/// 验证滞纳金规则数值（百分比为1-100，固定金额必须大于0）
+pub fn validate_late_fee_value(kind: &str, value: i64) -> Result<(), String> {
+    match kind {
+        "Percentage" => {
+            if !(1..=100).contains(&value) {
+                return Err("滞纳金百分比必须在1-100之间".to_string());
+            }
+        }
+        _ => {
+            validate_amount(value)?;
+            if value <= 0 {
+                return Err("滞纳金金额必须大于0".to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 验证滞纳金宽限天数
+pub fn validate_grace_days(days: u32) -> Result<(), String> {
+    if days > 365 {
+        return Err("宽限天数不能超过365天".to_string());
+    }
+    Ok(())
+}
+
+/// 验证滞纳金减免原因（必填）
+pub fn validate_waiver_reason(reason: &str) -> Result<(), String> {
+    if reason.trim().is_empty() {
+        return Err("减免原因不能为空".to_string());
+    }
+    validate_note(reason)
+}
+
+/// 验证分期到期日顺延天数（可为负数表示提前）
+pub fn validate_shift_days(days: i64) -> Result<(), String> {
+    if days == 0 || days.abs() > 365 {
+        return Err("顺延天数必须在-365到365之间且不能为0".to_string());
+    }
+    Ok(())
+}
+
 /// 验证课程类型
 pub fn validate_class_type(class_type: &str) -> Result<(), String> {
     match class_type {
@@ -100,7 +360,44 @@ pub fn validate_score_range(min: f64, max: f64) -> Result<(), String> {
     Ok(())
 }
 
+/// 验证操作员
+pub fn validate_operator(operator: &str) -> Result<(), String> {
+    let operator = operator.trim();
+    if operator.is_empty() {
+        return Err("操作员不能为空".to_string());
+    }
+    if operator.chars().count() > 50 {
+        return Err("操作员长度不能超过50个字符".to_string());
+    }
+    if operator.chars().any(|c| c.is_control()) {
+        return Err("操作员包含非法字符".to_string());
+    }
+    Ok(())
+}
+
+/// 验证会员类型
+pub fn validate_membership_type(membership_type: &str) -> Result<(), String> {
+    match membership_type {
+        "month" | "quarter" | "half_year" | "year" | "days" | "months" => Ok(()),
+        _ => Err(format!("无效的会员类型: {}", membership_type)),
+    }
+}
+
+/// 验证自定义会员天数
+pub fn validate_membership_days(days: u32) -> Result<(), String> {
+    if days == 0 || days > 3650 {
+        return Err("会员天数必须在1-3650之间".to_string());
+    }
+    Ok(())
+}
 
+/// 验证自定义会员月数
+pub fn validate_membership_months(months: u32) -> Result<(), String> {
+    if months == 0 || months > 120 {
+        return Err("会员月数必须在1-120之间".to_string());
+    }
+    Ok(())
+}
 
 /// 验证学生UID
 pub fn validate_student_uid(uid: u64) -> Result<(), String> {
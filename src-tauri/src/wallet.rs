@@ -0,0 +1,41 @@
+//! 储值钱包模块 - 学员预存余额及流水
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// 钱包流水类型
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WalletEntryKind {
+    TopUp,
+    Charge,
+}
+
+/// 钱包流水，每条流水都关联一条现金记录
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WalletEntry {
+    pub id: u64,
+    pub kind: WalletEntryKind,
+    /// 余额变动，充值为正、扣款为负
+    pub amount: i64,
+    pub balance_after: i64,
+    /// 扣款类别：lesson / range / equipment / other
+    pub category: Option<String>,
+    pub cash_uid: u64,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 当前钱包余额
+pub fn wallet_balance(entries: &[WalletEntry]) -> i64 {
+    entries.iter().map(|e| e.amount).sum()
+}
+
+/// 扣款类别的中文名称
+pub fn charge_category_label(category: &str) -> &'static str {
+    match category {
+        "lesson" => "课时",
+        "range" => "场地",
+        "equipment" => "器材",
+        _ => "其他",
+    }
+}